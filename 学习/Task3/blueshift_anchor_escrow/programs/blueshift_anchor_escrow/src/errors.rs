@@ -10,4 +10,14 @@ pub enum EscrowError { // 托管相关错误枚举开始。
     InvalidMintA, // mint A 与 escrow 配置不一致。
     #[msg("Invalid mint b")] // 当 mint B 不匹配时的错误消息。
     InvalidMintB, // mint B 与 escrow 配置不一致。
+    #[msg("Invalid transfer hook accounts")] // 缺少转账钩子所需账户时的错误消息。
+    InvalidTransferHookAccounts, // remaining accounts 中缺少钩子程序或 extra-account-metas PDA。
+    #[msg("Invalid taker")] // 托管指定了其他 taker 时的错误消息。
+    InvalidTaker, // 签名者不是 escrow 中指定的 taker。
+    #[msg("Missing token account")] // 非原生 SOL 一侧缺少可选的代币账户时的错误消息。
+    MissingTokenAccount, // 可选的 ATA 传入了占位地址。
+    #[msg("Slippage exceeded")] // 成交条件比 taker 签名时看到的更差时的错误消息。
+    SlippageExceeded, // maker 抢先 update 取走了 Token A 或提高了 receive。
+    #[msg("Escrow expired")] // 托管已过期或设置了过去的过期时间时的错误消息。
+    EscrowExpired, // 到达 expires_at 后不能再成交，只能 refund。
 } 
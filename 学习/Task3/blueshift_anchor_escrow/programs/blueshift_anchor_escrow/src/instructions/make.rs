@@ -1,14 +1,16 @@
 use anchor_lang::prelude::*; 
+use anchor_lang::system_program::{transfer, Transfer}; // 系统程序转账，用于包装原生 SOL。
 use anchor_spl::{ // 引入 Anchor SPL 辅助与 Token 接口。
     associated_token::AssociatedToken, // 关联代币程序类型。
     token_interface::{ // Token 接口模块，兼容 SPL Token / Token-2022。
-        transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked, // CPI 方法与账户类型。
+        sync_native, transfer_checked, Mint, SyncNative, TokenAccount, TokenInterface, TransferChecked, // CPI 方法与账户类型。
     }, // token_interface 导入结束。
 }; // anchor_spl 导入结束。
 
 use crate::{ // 引入当前 crate 的内容。
     errors::EscrowError, // 自定义错误定义。
-    state::{Escrow}, // Escrow 状态与 PDA 种子常量。
+    state::{Escrow, NativeSide, NATIVE_MINT}, // Escrow 状态与原生 SOL 标记。
+    transfer_hook::resolve_transfer_hook_accounts, // Token-2022 转账钩子账户解析。
 }; // crate 导入结束。
 /*
 1、自动生成账户验证逻辑
@@ -56,9 +58,7 @@ pub struct Make<'info> { // Make 账户结构体开始。
         mint::token_program = token_program // mint A 必须属于当前 token_program。
     )] // mint A 约束结束。
     pub mint_a: InterfaceAccount<'info, Mint>, // Token A 的 mint。
-    #[account( // mint B 约束。
-        mint::token_program = token_program // mint B 必须属于当前 token_program。
-    )] // mint B 约束结束。
+    // mint B 为 wSOL 时只作为标识，不要求与 mint A 使用同一个 token_program，在处理器中校验。
     pub mint_b: InterfaceAccount<'info, Mint>, // Token B 的 mint。
     #[account( // maker 的 ATA（mint A）。
         mut, // 该账户将被扣款。
@@ -66,7 +66,7 @@ pub struct Make<'info> { // Make 账户结构体开始。
         associated_token::authority = maker, // ATA 权限为 maker。
         associated_token::token_program = token_program // ATA 使用指定 token_program。
     )] // maker ATA 约束结束。
-    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>, // maker 的 Token A 账户。
+    pub maker_ata_a: Option<InterfaceAccount<'info, TokenAccount>>, // maker 的 Token A 账户；存入原生 SOL 时不需要。
     /*
     1. 为什么 vault 的权限是 escrow？
         安全控制：associated_token::authority = escrow 表示金库的权限设置为 escrow PDA，而不是 maker
@@ -167,27 +167,60 @@ vault 账户的作用
 */
 // Make 辅助方法实现。
 impl<'info> Make<'info> { // Make 的 impl 开始。
-    pub fn populate_escrow(&mut self, seed: u64, receive: u64, bump: u8) -> Result<()> { // 填充 escrow 字段。
+    pub fn populate_escrow(&mut self, seed: u64, receive: u64, taker: Option<Pubkey>, native: NativeSide, expires_at: i64, bump: u8) -> Result<()> { // 填充 escrow 字段。
         self.escrow.seed = seed; // 保存 seed 用于后续 PDA 推导。
         self.escrow.maker = self.maker.key(); // 保存 maker 公钥。
         self.escrow.mint_a = self.mint_a.key(); // 保存 mint A 公钥。
         self.escrow.mint_b = self.mint_b.key(); // 保存 mint B 公钥。
         self.escrow.receive = receive; // 保存期望接收的 Token B 数量。
         self.escrow.bump = bump; // 保存 PDA bump。
+        self.escrow.taker = taker; // 保存指定的 taker（可选）。
+        self.escrow.native = native; // 保存原生 SOL 标记。
+        self.escrow.expires_at = expires_at; // 保存过期时间（0 表示永不过期）。
         Ok(()) 
     } // populate_escrow 结束。
+    // 原生 SOL 一侧的 mint 必须是 wSOL；普通 mint B 必须属于当前 token_program。
+    pub fn check_native(&self, native: NativeSide) -> Result<()> { // 校验原生 SOL 标记与 mint 一致。
+        if native == NativeSide::TokenA { // maker 存入 SOL。
+            require_keys_eq!(self.mint_a.key(), NATIVE_MINT, EscrowError::InvalidMintA); // mint A 必须是 wSOL（同时保证 token_program 为 SPL Token）。
+        } 
+        if native == NativeSide::TokenB { // maker 期望收到 SOL。
+            require_keys_eq!(self.mint_b.key(), NATIVE_MINT, EscrowError::InvalidMintB); // mint B 必须是 wSOL。
+        } else { // mint B 是普通代币。
+            require_keys_eq!(*self.mint_b.to_account_info().owner, self.token_program.key(), EscrowError::InvalidMintB); // mint B 必须属于当前 token_program。
+        } 
+        Ok(()) 
+    } // check_native 结束。
     // 将 maker 的 Token A 存入金库。
-    pub fn deposit_tokens(&mut self, amount: u64) -> Result<()> { // 转账 Token A 到金库。
+    pub fn deposit_tokens(&mut self, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> { // 转账 Token A 到金库。
+        if self.escrow.native == NativeSide::TokenA { // 原生 SOL：转入 lamports 后同步为 wSOL 余额。
+            transfer( // CPI 调用系统程序转账。
+                CpiContext::new( // 构造 CPI 上下文。
+                    self.system_program.to_account_info(), // 系统程序账户。
+                    Transfer { // Transfer CPI 账户集合。
+                        from: self.maker.to_account_info(), // 转出：maker 钱包。
+                        to: self.vault.to_account_info(), // 转入：wSOL 金库。
+                    }, 
+                ), 
+                amount, 
+            )?; 
+            return sync_native(CpiContext::new( // 更新金库的 wSOL 余额。
+                self.token_program.to_account_info(), // Token 程序账户。
+                SyncNative { account: self.vault.to_account_info() }, // 要同步的金库账户。
+            )); 
+        } 
+        let maker_ata_a = self.maker_ata_a.as_ref().ok_or(EscrowError::MissingTokenAccount)?; // 普通代币必须传入 maker ATA。
+        let hook_accounts = resolve_transfer_hook_accounts(&self.mint_a, remaining_accounts)?; // mint A 的转账钩子账户。
         transfer_checked( // CPI 调用 Token 程序（带 decimals 校验）。
             CpiContext::new( // 构造 CPI 上下文。
                 self.token_program.to_account_info(), // Token 程序账户。
                 TransferChecked { // TransferChecked CPI 账户集合。
-                    from: self.maker_ata_a.to_account_info(), // 转出：maker ATA。
+                    from: maker_ata_a.to_account_info(), // 转出：maker ATA。
                     mint: self.mint_a.to_account_info(), // mint A 账户。
                     to: self.vault.to_account_info(), // 转入：金库 ATA。
                     authority: self.maker.to_account_info(), // 授权者：maker 签名者。
                 }, 
-            ), 
+            ).with_remaining_accounts(hook_accounts), // 转发转账钩子账户。
             amount, 
             self.mint_a.decimals, 
         )?; 
@@ -195,13 +228,15 @@ impl<'info> Make<'info> { // Make 的 impl 开始。
     } 
 } 
 // make 指令处理器。 
-pub fn handler(ctx: Context<Make>, seed: u64, receive: u64, amount: u64) -> Result<()> { // make 入口逻辑。
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, Make<'info>>, seed: u64, receive: u64, amount: u64, taker: Option<Pubkey>, native: NativeSide, expires_at: i64) -> Result<()> { // make 入口逻辑。
     // 校验数量参数。 // 校验说明。
     require_gt!(receive, 0, EscrowError::InvalidAmount); // receive 必须大于 0。
     require_gt!(amount, 0, EscrowError::InvalidAmount); // deposit 必须大于 0。
+    require!(expires_at == 0 || expires_at > Clock::get()?.unix_timestamp, EscrowError::EscrowExpired); // 不能创建已过期的托管。
+    ctx.accounts.check_native(native)?; // 校验原生 SOL 一侧的 mint。
     // 写入 Escrow 数据。 // 状态初始化说明。
-    ctx.accounts.populate_escrow(seed, receive, ctx.bumps.escrow)?; // 持久化 escrow 字段。
+    ctx.accounts.populate_escrow(seed, receive, taker, native, expires_at, ctx.bumps.escrow)?; // 持久化 escrow 字段。
     // 存入 Token。 // 转账说明。
-    ctx.accounts.deposit_tokens(amount)?; // 将 maker 的 Token A 存入金库。
+    ctx.accounts.deposit_tokens(amount, ctx.remaining_accounts)?; // 将 maker 的 Token A 存入金库。
     Ok(()) // 返回成功。
 } 
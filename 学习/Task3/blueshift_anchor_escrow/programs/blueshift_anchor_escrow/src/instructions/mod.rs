@@ -1,7 +1,11 @@
 pub mod make; // 暴露 make 指令模块。
 pub mod take; // 暴露 take 指令模块。
 pub mod refund; // 暴露 refund 指令模块。
+pub mod take_partial; // 暴露 take_partial 指令模块。
+pub mod update; // 暴露 update 指令模块。
 // 重新导出指令处理器与账户上下文。 
 pub use make::*; // 重新导出 make 模块内容。
 pub use take::*; // 重新导出 take 模块内容。
 pub use refund::*; // 重新导出 refund 模块内容。
+pub use take_partial::*; // 重新导出 take_partial 模块内容。
+pub use update::*; // 重新导出 update 模块内容。
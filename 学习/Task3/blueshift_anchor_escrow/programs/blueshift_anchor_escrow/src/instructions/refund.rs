@@ -9,7 +9,8 @@ use anchor_spl::{
 
 use crate::{ 
     errors::EscrowError, // 自定义错误定义。
-    state::{Escrow, ESCROW_SEED}, // Escrow 状态与 PDA 种子。
+    state::{Escrow, NativeSide, ESCROW_SEED}, // Escrow 状态、原生 SOL 标记与 PDA 种子。
+    transfer_hook::resolve_transfer_hook_accounts, // Token-2022 转账钩子账户解析。
 }; 
 // refund 指令的账户上下文。 
 #[derive(Accounts)] // 派生账户校验逻辑。
@@ -41,14 +42,14 @@ pub struct Refund<'info> { // Refund 账户结构体开始。
         associated_token::authority = maker, // ATA 权限为 maker。
         associated_token::token_program = token_program // ATA 使用指定 token_program。
     )] // maker ATA 约束结束。
-    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>, // maker 的 Token A 账户。
+    pub maker_ata_a: Option<InterfaceAccount<'info, TokenAccount>>, // maker 的 Token A 账户；Token A 为原生 SOL 时不需要。
     // 程序账户。 
     pub associated_token_program: Program<'info, AssociatedToken>, // 关联代币程序。
     pub token_program: Interface<'info, TokenInterface>, // Token 程序接口。
     pub system_program: Program<'info, System>, // 系统程序。
 } 
 // refund 指令处理器。 
-pub fn handler(ctx: Context<Refund>) -> Result<()> { // refund 入口逻辑。
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, Refund<'info>>) -> Result<()> { // refund 入口逻辑。
     let vault_amount = ctx.accounts.vault.amount; // 读取金库当前余额。
     let escrow = &ctx.accounts.escrow; // 绑定 escrow 引用用于种子。
     let seed_bytes = escrow.seed.to_le_bytes(); // 将 seed 转为小端字节。
@@ -59,18 +60,21 @@ pub fn handler(ctx: Context<Refund>) -> Result<()> { // refund 入口逻辑。
         &[escrow.bump], // PDA bump。
     ]; // 签名种子结束。
     let signer = &[signer_seeds]; // CPI 签名者种子包装。
-    if vault_amount > 0 { // 仅在金库有余额时转账。
+    // 原生 SOL：跳过转账，关闭 wSOL 金库时存入的 SOL 与租金一起退还 maker。
+    if vault_amount > 0 && escrow.native != NativeSide::TokenA { // 仅在金库有余额时转账。
+        let maker_ata_a = ctx.accounts.maker_ata_a.as_ref().ok_or(EscrowError::MissingTokenAccount)?; // 普通代币必须传入 maker ATA A。
+        let hook_accounts = resolve_transfer_hook_accounts(&ctx.accounts.mint_a, ctx.remaining_accounts)?; // mint A 的转账钩子账户。
         transfer_checked( // CPI 调用 Token 程序并使用 PDA 签名。
             CpiContext::new_with_signer( // 构造带签名的 CPI 上下文。
                 ctx.accounts.token_program.to_account_info(), // Token 程序账户。
                 TransferChecked { // TransferChecked CPI 账户集合。
                     from: ctx.accounts.vault.to_account_info(), // 转出：金库 ATA。
                     mint: ctx.accounts.mint_a.to_account_info(), // mint A 账户。
-                    to: ctx.accounts.maker_ata_a.to_account_info(), // 转入：maker ATA A。
+                    to: maker_ata_a.to_account_info(), // 转入：maker ATA A。
                     authority: ctx.accounts.escrow.to_account_info(), // 授权者：escrow PDA。
                 }, // TransferChecked 账户结束。
                 signer, // PDA 签名种子。
-            ), // CPI 上下文结束。
+            ).with_remaining_accounts(hook_accounts), // CPI 上下文结束，转发转账钩子账户。
             vault_amount, // 转出全部余额。
             ctx.accounts.mint_a.decimals, // mint A 精度。
         )?; // 传播 CPI 错误。
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*; 
+use anchor_lang::system_program::{transfer, Transfer}; // 系统程序转账，用于原生 SOL。
 use anchor_spl::{ 
     associated_token::AssociatedToken, // 关联代币程序类型。
     token_interface::{ // Token 接口模块，兼容 SPL Token / Token-2022。
@@ -9,7 +10,8 @@ use anchor_spl::{
 // 本 crate 内部导入。 
 use crate::{ 
     errors::EscrowError, // 自定义错误定义。
-    state::{Escrow}, // Escrow 状态与 PDA 种子。
+    state::{Escrow, NativeSide}, // Escrow 状态与原生 SOL 标记。
+    transfer_hook::resolve_transfer_hook_accounts, // Token-2022 转账钩子账户解析。
 }; // crate 导入结束。
 // take 指令的账户上下文。
 #[derive(Accounts)] // 派生账户校验逻辑。
@@ -26,6 +28,7 @@ pub struct Take<'info> {
       has_one = maker @ EscrowError::InvalidMaker, // 校验 maker 与 escrow 一致。
       has_one = mint_a @ EscrowError::InvalidMintA, // 校验 mint A 与 escrow 一致。
       has_one = mint_b @ EscrowError::InvalidMintB, // 校验 mint B 与 escrow 一致。
+      constraint = escrow.can_take(&taker.key()) @ EscrowError::InvalidTaker, // 指定 taker 时只允许该签名者接受。
   )] 
   pub escrow: Box<Account<'info, Escrow>>, // escrow PDA 账户。
   // Token 账户与 mint。 
@@ -45,14 +48,14 @@ pub struct Take<'info> {
       associated_token::authority = taker, // ATA 权限为 taker。
       associated_token::token_program = token_program // ATA 使用指定 token_program。
   )] // taker ATA A 约束结束。
-  pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>, // taker 的 Token A 账户。
+  pub taker_ata_a: Option<Box<InterfaceAccount<'info, TokenAccount>>>, // taker 的 Token A 账户；Token A 为原生 SOL 时不需要。
   #[account( // taker 的 ATA（mint B）。
       mut, // taker ATA B 将被扣款。
       associated_token::mint = mint_b, // ATA mint 为 mint B。
       associated_token::authority = taker, // ATA 权限为 taker。
       associated_token::token_program = token_program // ATA 使用指定 token_program。
   )] // taker ATA B 约束结束。
-  pub taker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>, // taker 的 Token B 账户。
+  pub taker_ata_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>, // taker 的 Token B 账户；Token B 为原生 SOL 时不需要。
   #[account( // maker 的 ATA（mint B）。
       init_if_needed, // 若不存在则创建。
       payer = taker, // 由 taker 支付创建费用。
@@ -60,7 +63,7 @@ pub struct Take<'info> {
       associated_token::authority = maker, // ATA 权限为 maker。
       associated_token::token_program = token_program // ATA 使用指定 token_program。
   )] // maker ATA B 约束结束。
-  pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>, // maker 的 Token B 账户。
+  pub maker_ata_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>, // maker 的 Token B 账户；Token B 为原生 SOL 时不需要。
   // 程序账户。 
   pub associated_token_program: Program<'info, AssociatedToken>, // 关联代币程序。
   pub token_program: Interface<'info, TokenInterface>, // Token 程序接口。
@@ -69,24 +72,39 @@ pub struct Take<'info> {
 // Take 辅助方法实现。 
 // maker 在不同交易中可能拥有的 ATA
 impl<'info> Take<'info> { // Take 的 impl 开始。
-    fn transfer_to_maker(&mut self) -> Result<()> { // 从 taker 转 Token B 给 maker。
+    fn transfer_to_maker(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> { // 从 taker 转 Token B 给 maker。
+        if self.escrow.native == NativeSide::TokenB { // 原生 SOL：taker 直接转 lamports 给 maker。
+            return transfer( // CPI 调用系统程序转账。
+                CpiContext::new( // 构造 CPI 上下文。
+                    self.system_program.to_account_info(), // 系统程序账户。
+                    Transfer { // Transfer CPI 账户集合。
+                        from: self.taker.to_account_info(), // 转出：taker 钱包。
+                        to: self.maker.to_account_info(), // 转入：maker 钱包。
+                    }, 
+                ), 
+                self.escrow.receive, // 转账数量（escrow 中约定的 receive）。
+            ); 
+        } 
+        let taker_ata_b = self.taker_ata_b.as_ref().ok_or(EscrowError::MissingTokenAccount)?; // 普通代币必须传入 taker ATA B。
+        let maker_ata_b = self.maker_ata_b.as_ref().ok_or(EscrowError::MissingTokenAccount)?; // 普通代币必须传入 maker ATA B。
+        let hook_accounts = resolve_transfer_hook_accounts(&self.mint_b, remaining_accounts)?; // mint B 的转账钩子账户。
         transfer_checked( // CPI 调用 Token 程序（带 decimals 校验）。
             CpiContext::new( // 构造 CPI 上下文。
                 self.token_program.to_account_info(), // Token 程序账户。
                 TransferChecked { // TransferChecked CPI 账户集合。
-                    from: self.taker_ata_b.to_account_info(), // 转出：taker ATA B。
-                    to: self.maker_ata_b.to_account_info(), // 转入：maker ATA B。
+                    from: taker_ata_b.to_account_info(), // 转出：taker ATA B。
+                    to: maker_ata_b.to_account_info(), // 转入：maker ATA B。
                     mint: self.mint_b.to_account_info(), // mint B 账户。
                     authority: self.taker.to_account_info(), // 授权者：taker 签名者。
                 }, // TransferChecked 账户结束。
-            ), // CPI 上下文结束。
+            ).with_remaining_accounts(hook_accounts), // CPI 上下文结束，转发转账钩子账户。
             self.escrow.receive, // 转账数量（escrow 中约定的 receive）。
             self.mint_b.decimals, // mint B 精度。
         )?; // 传播 CPI 错误。
         Ok(()) 
     } // transfer_to_maker 结束。
     // 从金库取出 Token A 并关闭金库。 
-    fn withdraw_and_close_vault(&mut self) -> Result<()> { // 处理金库提取与关闭。
+    fn withdraw_and_close_vault(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> { // 处理金库提取与关闭。
         // 构造金库 PDA 的签名种子。 
         let signer_seeds: [&[&[u8]]; 1] = [&[ // 构造 signer seeds 数组。
             b"escrow", // 种子前缀。
@@ -94,18 +112,42 @@ impl<'info> Take<'info> { // Take 的 impl 开始。
             &self.escrow.seed.to_le_bytes()[..], // escrow seed 的字节序。
             &[self.escrow.bump], // PDA bump。
         ]]; 
+        if self.escrow.native == NativeSide::TokenA { // 原生 SOL：关闭 wSOL 金库即解包，全部 lamports 归 taker。
+            let vault_rent = self.vault.to_account_info().lamports() - self.vault.amount; // 金库租金属于 maker。
+            close_account(CpiContext::new_with_signer( // CPI 关闭金库。
+                self.token_program.to_account_info(), // Token 程序账户。
+                CloseAccount { // CloseAccount CPI 账户集合。
+                    account: self.vault.to_account_info(), // 要关闭的金库账户。
+                    authority: self.escrow.to_account_info(), // 授权者：escrow PDA。
+                    destination: self.taker.to_account_info(), // 存入的 SOL 与租金先归 taker。
+                }, // CloseAccount 账户结束。
+                &signer_seeds, // PDA 签名种子。
+            ))?; // 传播 CPI 错误。
+            return transfer( // taker 把金库租金还给 maker。
+                CpiContext::new( // 构造 CPI 上下文。
+                    self.system_program.to_account_info(), // 系统程序账户。
+                    Transfer { // Transfer CPI 账户集合。
+                        from: self.taker.to_account_info(), // 转出：taker 钱包。
+                        to: self.maker.to_account_info(), // 转入：maker 钱包。
+                    }, 
+                ), 
+                vault_rent, // 金库租金。
+            ); 
+        } 
+        let taker_ata_a = self.taker_ata_a.as_ref().ok_or(EscrowError::MissingTokenAccount)?; // 普通代币必须传入 taker ATA A。
+        let hook_accounts = resolve_transfer_hook_accounts(&self.mint_a, remaining_accounts)?; // mint A 的转账钩子账户。
         // 转移 Token A（金库 -> taker）。 // 转账说明。
         transfer_checked( // CPI 调用 Token 程序并使用 PDA 签名。
             CpiContext::new_with_signer( // 构造带签名的 CPI 上下文。
                 self.token_program.to_account_info(), // Token 程序账户。
                 TransferChecked { // TransferChecked CPI 账户集合。
                     from: self.vault.to_account_info(), // 转出：金库 ATA。
-                    to: self.taker_ata_a.to_account_info(), // 转入：taker ATA A。
+                    to: taker_ata_a.to_account_info(), // 转入：taker ATA A。
                     mint: self.mint_a.to_account_info(), // mint A 账户。
                     authority: self.escrow.to_account_info(), // 授权者：escrow PDA。
                 }, // TransferChecked 账户结束。
                 &signer_seeds, // PDA 签名种子。
-            ), // CPI 上下文结束。
+            ).with_remaining_accounts(hook_accounts), // CPI 上下文结束，转发转账钩子账户。
             self.vault.amount, // 转出金库全部余额。
             self.mint_a.decimals, // mint A 精度。
         )?; // 传播 CPI 错误。
@@ -122,11 +164,14 @@ impl<'info> Take<'info> { // Take 的 impl 开始。
         Ok(()) // 返回成功。
     } // withdraw_and_close_vault 结束。
 } // Take 的 impl 结束。
-// take 指令处理器。 
-pub fn handler(ctx: Context<Take>) -> Result<()> { // take 入口逻辑。
+// take 指令处理器：min_amount_a 与 max_pay 防止 maker 抢先 update 改变成交条件。 
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, Take<'info>>, min_amount_a: u64, max_pay: Option<u64>) -> Result<()> { // take 入口逻辑。
+    require!(!ctx.accounts.escrow.is_expired(Clock::get()?.unix_timestamp), EscrowError::EscrowExpired); // 过期后只能 refund。
+    require!(ctx.accounts.vault.amount >= min_amount_a, EscrowError::SlippageExceeded); // 金库余额不少于 taker 签名时看到的数量。
+    require!(max_pay.is_none_or(|max_pay| ctx.accounts.escrow.receive <= max_pay), EscrowError::SlippageExceeded); // 支付的 Token B 不超过 taker 愿意支付的上限。
     // 将 Token B 转给 maker。 
-    ctx.accounts.transfer_to_maker()?; // 执行 Token B 转账。
+    ctx.accounts.transfer_to_maker(ctx.remaining_accounts)?; // 执行 Token B 转账。
     // 提取 Token A 并关闭金库。 
-    ctx.accounts.withdraw_and_close_vault()?; // 转出 Token A 并关闭金库。
+    ctx.accounts.withdraw_and_close_vault(ctx.remaining_accounts)?; // 转出 Token A 并关闭金库。
     Ok(()) // 返回成功。
 } // take 处理器结束。
@@ -0,0 +1,150 @@
+use anchor_lang::prelude::*; 
+use anchor_lang::system_program::{transfer, Transfer}; // 系统程序转账，用于原生 SOL。
+use anchor_spl::{ 
+    associated_token::AssociatedToken, // 关联代币程序类型。
+    token_interface::{ // Token 接口模块，兼容 SPL Token / Token-2022。
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface, // CPI 方法与账户类型。
+        TransferChecked, // TransferChecked CPI 账户结构。
+    }, 
+}; 
+// 本 crate 内部导入。 
+use crate::{ 
+    errors::EscrowError, // 自定义错误定义。
+    state::{Escrow, NativeSide, ESCROW_SEED}, // Escrow 状态、原生 SOL 标记与 PDA 种子。
+    transfer_hook::resolve_transfer_hook_accounts, // Token-2022 转账钩子账户解析。
+}; // crate 导入结束。
+// take_partial 指令的账户上下文：与 Take 相同，但 escrow 不自动关闭（只有全部成交时才在处理器中关闭）。
+#[derive(Accounts)] // 派生账户校验逻辑。
+pub struct TakePartial<'info> { 
+  #[account(mut)] // taker 需可变，用于支付租金并签名。
+  pub taker: Signer<'info>, // taker 签名者账户。
+  #[account(mut)] // maker 可能接收 lamports。
+  pub maker: SystemAccount<'info>, // maker 系统账户。
+  #[account( 
+      mut, // 需要更新剩余的 receive。
+      seeds = [ESCROW_SEED, maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()], // PDA 种子。
+      bump = escrow.bump, // 校验 PDA bump。
+      has_one = maker @ EscrowError::InvalidMaker, // 校验 maker 与 escrow 一致。
+      has_one = mint_a @ EscrowError::InvalidMintA, // 校验 mint A 与 escrow 一致。
+      has_one = mint_b @ EscrowError::InvalidMintB, // 校验 mint B 与 escrow 一致。
+      constraint = escrow.can_take(&taker.key()) @ EscrowError::InvalidTaker, // 指定 taker 时只允许该签名者接受。
+  )] 
+  pub escrow: Box<Account<'info, Escrow>>, // escrow PDA 账户。
+  // Token 账户与 mint。 
+  pub mint_a: Box<InterfaceAccount<'info, Mint>>, // mint A 账户。
+  pub mint_b: Box<InterfaceAccount<'info, Mint>>, // mint B 账户。
+  #[account( // 金库 ATA（escrow 持有的 Token A）。
+      mut, // 金库将被扣款，全部成交时关闭。
+      associated_token::mint = mint_a, // 金库 mint 必须为 mint A。
+      associated_token::authority = escrow, // 金库权限为 escrow PDA。
+      associated_token::token_program = token_program // 金库使用指定 token_program。
+  )] // 金库约束结束。
+  pub vault: Box<InterfaceAccount<'info, TokenAccount>>, // 金库存放 Token A。
+  #[account( // taker 的 ATA（mint A）。
+      init_if_needed, // 若不存在则创建。
+      payer = taker, // 由 taker 支付创建费用。
+      associated_token::mint = mint_a, // ATA mint 为 mint A。
+      associated_token::authority = taker, // ATA 权限为 taker。
+      associated_token::token_program = token_program // ATA 使用指定 token_program。
+  )] // taker ATA A 约束结束。
+  pub taker_ata_a: Box<InterfaceAccount<'info, TokenAccount>>, // taker 的 Token A 账户（Token A 为原生 SOL 时部分成交以 wSOL 交付）。
+  #[account( // taker 的 ATA（mint B）。
+      mut, // taker ATA B 将被扣款。
+      associated_token::mint = mint_b, // ATA mint 为 mint B。
+      associated_token::authority = taker, // ATA 权限为 taker。
+      associated_token::token_program = token_program // ATA 使用指定 token_program。
+  )] // taker ATA B 约束结束。
+  pub taker_ata_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>, // taker 的 Token B 账户；Token B 为原生 SOL 时不需要。
+  #[account( // maker 的 ATA（mint B）。
+      init_if_needed, // 若不存在则创建。
+      payer = taker, // 由 taker 支付创建费用。
+      associated_token::mint = mint_b, // ATA mint 为 mint B。
+      associated_token::authority = maker, // ATA 权限为 maker。
+      associated_token::token_program = token_program // ATA 使用指定 token_program。
+  )] // maker ATA B 约束结束。
+  pub maker_ata_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>, // maker 的 Token B 账户；Token B 为原生 SOL 时不需要。
+  // 程序账户。 
+  pub associated_token_program: Program<'info, AssociatedToken>, // 关联代币程序。
+  pub token_program: Interface<'info, TokenInterface>, // Token 程序接口。
+  pub system_program: Program<'info, System>, // 系统程序。
+} // TakePartial 账户结构体结束。
+// take_partial 指令处理器：支付 amount_b 个 Token B，按比例获得 Token A。
+// Token A 为原生 SOL 时金库在部分成交后仍需保留，不能关闭解包，因此 taker 收到的是 wSOL（taker_ata_a 为 wSOL ATA），需要自行关闭 ATA 解包为 SOL。
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, TakePartial<'info>>, amount_b: u64, min_amount_a: u64) -> Result<()> { // take_partial 入口逻辑。
+    require!(!ctx.accounts.escrow.is_expired(Clock::get()?.unix_timestamp), EscrowError::EscrowExpired); // 过期后只能 refund。
+    let amount_a = ctx.accounts.escrow.fill_amount_a(ctx.accounts.vault.amount, amount_b).ok_or(EscrowError::InvalidAmount)?; // 按比例计算释放的 Token A。
+    require!(amount_a >= min_amount_a, EscrowError::SlippageExceeded); // maker 抢先 update 取走 Token A 或提高 receive 时拒绝成交。
+    // 将 Token B 转给 maker。 
+    if ctx.accounts.escrow.native == NativeSide::TokenB { // 原生 SOL：taker 直接转 lamports 给 maker。
+        transfer( // CPI 调用系统程序转账。
+            CpiContext::new( // 构造 CPI 上下文。
+                ctx.accounts.system_program.to_account_info(), // 系统程序账户。
+                Transfer { // Transfer CPI 账户集合。
+                    from: ctx.accounts.taker.to_account_info(), // 转出：taker 钱包。
+                    to: ctx.accounts.maker.to_account_info(), // 转入：maker 钱包。
+                }, 
+            ), 
+            amount_b, // 本次成交的 lamports 数量。
+        )?; // 传播 CPI 错误。
+    } else { // 普通代币。
+        let taker_ata_b = ctx.accounts.taker_ata_b.as_ref().ok_or(EscrowError::MissingTokenAccount)?; // 必须传入 taker ATA B。
+        let maker_ata_b = ctx.accounts.maker_ata_b.as_ref().ok_or(EscrowError::MissingTokenAccount)?; // 必须传入 maker ATA B。
+        let hook_accounts = resolve_transfer_hook_accounts(&ctx.accounts.mint_b, ctx.remaining_accounts)?; // mint B 的转账钩子账户。
+        transfer_checked( // CPI 调用 Token 程序（带 decimals 校验）。
+            CpiContext::new( // 构造 CPI 上下文。
+                ctx.accounts.token_program.to_account_info(), // Token 程序账户。
+                TransferChecked { // TransferChecked CPI 账户集合。
+                    from: taker_ata_b.to_account_info(), // 转出：taker ATA B。
+                    to: maker_ata_b.to_account_info(), // 转入：maker ATA B。
+                    mint: ctx.accounts.mint_b.to_account_info(), // mint B 账户。
+                    authority: ctx.accounts.taker.to_account_info(), // 授权者：taker 签名者。
+                }, // TransferChecked 账户结束。
+            ).with_remaining_accounts(hook_accounts), // CPI 上下文结束，转发转账钩子账户。
+            amount_b, // 本次成交的 Token B 数量。
+            ctx.accounts.mint_b.decimals, // mint B 精度。
+        )?; // 传播 CPI 错误。
+    } 
+    // 构造金库 PDA 的签名种子。 
+    let seed_bytes = ctx.accounts.escrow.seed.to_le_bytes(); // 将 seed 转为小端字节。
+    let maker_key = ctx.accounts.maker.key(); // maker 公钥。
+    let signer_seeds: &[&[u8]] = &[ // 构造 PDA 签名种子切片。
+        ESCROW_SEED, // 种子前缀。
+        maker_key.as_ref(), // maker 公钥种子。
+        seed_bytes.as_ref(), // seed 字节。
+        &[ctx.accounts.escrow.bump], // PDA bump。
+    ]; // 签名种子结束。
+    let signer = &[signer_seeds]; // CPI 签名者种子包装。
+    // 转移 Token A（金库 -> taker）。 
+    let hook_accounts = resolve_transfer_hook_accounts(&ctx.accounts.mint_a, ctx.remaining_accounts)?; // mint A 的转账钩子账户。
+    transfer_checked( // CPI 调用 Token 程序并使用 PDA 签名。
+        CpiContext::new_with_signer( // 构造带签名的 CPI 上下文。
+            ctx.accounts.token_program.to_account_info(), // Token 程序账户。
+            TransferChecked { // TransferChecked CPI 账户集合。
+                from: ctx.accounts.vault.to_account_info(), // 转出：金库 ATA。
+                to: ctx.accounts.taker_ata_a.to_account_info(), // 转入：taker ATA A。
+                mint: ctx.accounts.mint_a.to_account_info(), // mint A 账户。
+                authority: ctx.accounts.escrow.to_account_info(), // 授权者：escrow PDA。
+            }, // TransferChecked 账户结束。
+            signer, // PDA 签名种子。
+        ).with_remaining_accounts(hook_accounts), // CPI 上下文结束，转发转账钩子账户。
+        amount_a, // 按比例释放的 Token A。
+        ctx.accounts.mint_a.decimals, // mint A 精度。
+    )?; // 传播 CPI 错误。
+    // 更新剩余数量（fill_amount_a 已保证 amount_b <= receive）。 
+    ctx.accounts.escrow.receive -= amount_b; // 扣减剩余 receive。
+    if ctx.accounts.escrow.receive > 0 { // 尚未全部成交。
+        return Ok(()); // 保留金库与 escrow。
+    } 
+    // 全部成交：金库此时为空，关闭金库与 escrow。 
+    close_account(CpiContext::new_with_signer( // CPI 关闭金库账户。
+        ctx.accounts.token_program.to_account_info(), // Token 程序账户。
+        CloseAccount { // CloseAccount CPI 账户集合。
+            account: ctx.accounts.vault.to_account_info(), // 要关闭的金库账户。
+            destination: ctx.accounts.maker.to_account_info(), // 关闭后 lamports 归 maker。
+            authority: ctx.accounts.escrow.to_account_info(), // 授权者：escrow PDA。
+        }, // CloseAccount 账户结束。
+        signer, // PDA 签名种子。
+    ))?; // 传播 CPI 错误。
+    ctx.accounts.escrow.close(ctx.accounts.maker.to_account_info())?; // 关闭 escrow 并把租金返还给 maker。
+    Ok(()) // 返回成功。
+} // take_partial 处理器结束。
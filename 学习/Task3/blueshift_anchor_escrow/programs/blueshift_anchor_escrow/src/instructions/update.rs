@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*; 
+use anchor_lang::system_program::{transfer, Transfer}; // 系统程序转账，用于包装原生 SOL。
+use anchor_spl::{ 
+    associated_token::AssociatedToken, // 关联代币程序类型。
+    token_interface::{ // Token 接口模块，兼容 SPL Token / Token-2022。
+        sync_native, transfer_checked, Mint, SyncNative, TokenAccount, TokenInterface, TransferChecked, // CPI 方法与账户类型。
+    }, 
+}; 
+
+use crate::{ 
+    errors::EscrowError, // 自定义错误定义。
+    state::{Escrow, NativeSide, ESCROW_SEED}, // Escrow 状态、原生 SOL 标记与 PDA 种子。
+    transfer_hook::resolve_transfer_hook_accounts, // Token-2022 转账钩子账户解析。
+}; 
+// update 指令的账户上下文：maker 修改未成交的托管，escrow 与金库保持不变。
+#[derive(Accounts)] // 派生账户校验逻辑。
+pub struct Update<'info> { // Update 账户结构体开始。
+    #[account(mut)] // maker 需要可变并签名。
+    pub maker: Signer<'info>, // maker 签名者账户。
+    #[account( // escrow PDA 账户约束。
+        mut, // 需要更新 receive。
+        seeds = [ESCROW_SEED, maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()], // PDA 种子。
+        bump = escrow.bump, // 校验 PDA bump。
+        has_one = maker @ EscrowError::InvalidMaker, // 只有 maker 可以修改托管。
+        has_one = mint_a @ EscrowError::InvalidMintA // 校验 mint A 与 escrow 一致。
+    )] 
+    pub escrow: Account<'info, Escrow>, // escrow PDA 账户。
+    #[account(mint::token_program = token_program)] // mint A 必须属于 token_program。
+    pub mint_a: InterfaceAccount<'info, Mint>, // mint A 账户。
+    #[account( // 金库 ATA（escrow 持有的 Token A）。
+        mut, // 金库余额会变化。
+        associated_token::mint = mint_a, // 金库 mint 必须为 mint A。
+        associated_token::authority = escrow, // 金库权限为 escrow PDA。
+        associated_token::token_program = token_program // 金库使用指定 token_program。
+    )] 
+    pub vault: InterfaceAccount<'info, TokenAccount>, // 金库存放 Token A。
+    #[account( // maker 的 ATA（mint A）。
+        init_if_needed, // 取回时 maker 的 ATA 可能已被关闭。
+        payer = maker, // 由 maker 支付创建费用。
+        associated_token::mint = mint_a, // ATA mint 为 mint A。
+        associated_token::authority = maker, // ATA 权限为 maker。
+        associated_token::token_program = token_program // ATA 使用指定 token_program。
+    )] 
+    pub maker_ata_a: Option<InterfaceAccount<'info, TokenAccount>>, // maker 的 Token A 账户；原生 SOL 只追加时不需要，取回时以 wSOL 交付。
+    // 程序账户。 
+    pub associated_token_program: Program<'info, AssociatedToken>, // 关联代币程序。
+    pub token_program: Interface<'info, TokenInterface>, // Token 程序接口。
+    pub system_program: Program<'info, System>, // 系统程序。
+} 
+// update 指令处理器：修改 receive 与过期时间，追加或部分取回 Token A（二者只能选其一）。
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, Update<'info>>, receive: Option<u64>, deposit: u64, withdraw: u64, expires_at: Option<i64>) -> Result<()> { // update 入口逻辑。
+    require!(deposit == 0 || withdraw == 0, EscrowError::InvalidAmount); // 不能同时追加和取回。
+    require!(withdraw < ctx.accounts.vault.amount, EscrowError::InvalidAmount); // 金库必须保留余额，全部取回请使用 refund。
+    if let Some(receive) = receive { // 传入新的期望数量。
+        require!(receive > 0, EscrowError::InvalidAmount); // 期望数量必须大于 0。
+        ctx.accounts.escrow.receive = receive; // 更新 receive。
+    } 
+    if let Some(expires_at) = expires_at { // 延长、缩短或取消过期时间。
+        require!(expires_at == 0 || expires_at > Clock::get()?.unix_timestamp, EscrowError::EscrowExpired); // 新的过期时间必须在未来（0 表示永不过期）。
+        ctx.accounts.escrow.expires_at = expires_at; // 更新过期时间。
+    } 
+    if deposit > 0 && ctx.accounts.escrow.native == NativeSide::TokenA { // 原生 SOL：转入 lamports 后同步为 wSOL 余额。
+        transfer( // CPI 调用系统程序转账。
+            CpiContext::new( // 构造 CPI 上下文。
+                ctx.accounts.system_program.to_account_info(), // 系统程序账户。
+                Transfer { // Transfer CPI 账户集合。
+                    from: ctx.accounts.maker.to_account_info(), // 转出：maker 钱包。
+                    to: ctx.accounts.vault.to_account_info(), // 转入：wSOL 金库。
+                }, 
+            ), 
+            deposit, // 追加数量。
+        )?; // 传播 CPI 错误。
+        sync_native(CpiContext::new( // 更新金库的 wSOL 余额。
+            ctx.accounts.token_program.to_account_info(), // Token 程序账户。
+            SyncNative { account: ctx.accounts.vault.to_account_info() }, // 要同步的金库账户。
+        ))?; // 传播 CPI 错误。
+    } else if deposit > 0 { // 追加存入 Token A（maker -> 金库）。
+        let maker_ata_a = ctx.accounts.maker_ata_a.as_ref().ok_or(EscrowError::MissingTokenAccount)?; // 必须传入 maker ATA A。
+        let hook_accounts = resolve_transfer_hook_accounts(&ctx.accounts.mint_a, ctx.remaining_accounts)?; // mint A 的转账钩子账户。
+        transfer_checked( // CPI 调用 Token 程序（带 decimals 校验）。
+            CpiContext::new( // 构造 CPI 上下文。
+                ctx.accounts.token_program.to_account_info(), // Token 程序账户。
+                TransferChecked { // TransferChecked CPI 账户集合。
+                    from: maker_ata_a.to_account_info(), // 转出：maker ATA A。
+                    mint: ctx.accounts.mint_a.to_account_info(), // mint A 账户。
+                    to: ctx.accounts.vault.to_account_info(), // 转入：金库 ATA。
+                    authority: ctx.accounts.maker.to_account_info(), // 授权者：maker 签名者。
+                }, 
+            ).with_remaining_accounts(hook_accounts), // 转发转账钩子账户。
+            deposit, // 追加数量。
+            ctx.accounts.mint_a.decimals, // mint A 精度。
+        )?; // 传播 CPI 错误。
+    } 
+    if withdraw > 0 { // 部分取回 Token A（金库 -> maker）。
+        let maker_ata_a = ctx.accounts.maker_ata_a.as_ref().ok_or(EscrowError::MissingTokenAccount)?; // 必须传入 maker ATA A。
+        let escrow = &ctx.accounts.escrow; // 绑定 escrow 引用用于种子。
+        let seed_bytes = escrow.seed.to_le_bytes(); // 将 seed 转为小端字节。
+        let signer_seeds: &[&[u8]] = &[ // 构造 PDA 签名种子切片。
+            ESCROW_SEED, // 种子前缀。
+            escrow.maker.as_ref(), // maker 公钥种子。
+            seed_bytes.as_ref(), // seed 字节。
+            &[escrow.bump], // PDA bump。
+        ]; // 签名种子结束。
+        let hook_accounts = resolve_transfer_hook_accounts(&ctx.accounts.mint_a, ctx.remaining_accounts)?; // mint A 的转账钩子账户。
+        transfer_checked( // CPI 调用 Token 程序并使用 PDA 签名。
+            CpiContext::new_with_signer( // 构造带签名的 CPI 上下文。
+                ctx.accounts.token_program.to_account_info(), // Token 程序账户。
+                TransferChecked { // TransferChecked CPI 账户集合。
+                    from: ctx.accounts.vault.to_account_info(), // 转出：金库 ATA。
+                    mint: ctx.accounts.mint_a.to_account_info(), // mint A 账户。
+                    to: maker_ata_a.to_account_info(), // 转入：maker ATA A。
+                    authority: ctx.accounts.escrow.to_account_info(), // 授权者：escrow PDA。
+                }, 
+                &[signer_seeds], // PDA 签名种子。
+            ).with_remaining_accounts(hook_accounts), // 转发转账钩子账户。
+            withdraw, // 取回数量。
+            ctx.accounts.mint_a.decimals, // mint A 精度。
+        )?; // 传播 CPI 错误。
+    } 
+    Ok(()) // 返回成功。
+} // update 处理器结束。
@@ -3,7 +3,9 @@ use anchor_lang::prelude::*;
 mod errors; // 错误定义模块。
 mod instructions; // 指令模块（make/take/refund）。
 mod state; // 状态定义模块。
+mod transfer_hook; // Token-2022 转账钩子账户解析。
 use instructions::*; // 使用重新导出的指令账户类型。
+use state::NativeSide; // make 的原生 SOL 参数类型。
 // 程序 ID 声明。 
 declare_id!("22222222222222222222222222222222222222222222"); 
 // 程序入口模块。 
@@ -12,17 +14,27 @@ pub mod anchor_escrow { // 程序模块名。
     use super::*; // 将外层作用域内容引入当前模块。
     // 指令：make（鉴别器 = 0）。
     #[instruction(discriminator = 0)] // make 指令自定义鉴别器。
-    pub fn make(ctx: Context<Make>, seed: u64, deposit: u64, receive: u64) -> Result<()> { // make 入口函数。
-        instructions::make::handler(ctx, seed, deposit, receive) // 调用 make 处理器。
+    pub fn make<'info>(ctx: Context<'_, '_, 'info, 'info, Make<'info>>, seed: u64, deposit: u64, receive: u64, taker: Option<Pubkey>, native: NativeSide, expires_at: i64) -> Result<()> { // make 入口函数。
+        instructions::make::handler(ctx, seed, receive, deposit, taker, native, expires_at) // 调用 make 处理器（处理器参数顺序为 receive, amount）。
     } 
     // 指令：take（鉴别器 = 1）。 
     #[instruction(discriminator = 1)] 
-    pub fn take(ctx: Context<Take>) -> Result<()> { // take 入口函数。
-        instructions::take::handler(ctx) // 调用 take 处理器。
+    pub fn take<'info>(ctx: Context<'_, '_, 'info, 'info, Take<'info>>, min_amount_a: u64, max_pay: Option<u64>) -> Result<()> { // take 入口函数。
+        instructions::take::handler(ctx, min_amount_a, max_pay) // 调用 take 处理器。
     } 
     // 指令：refund（鉴别器 = 2）。
     #[instruction(discriminator = 2)] // refund 指令自定义鉴别器。
-    pub fn refund(ctx: Context<Refund>) -> Result<()> { // refund 入口函数。
+    pub fn refund<'info>(ctx: Context<'_, '_, 'info, 'info, Refund<'info>>) -> Result<()> { // refund 入口函数。
         instructions::refund::handler(ctx) // 调用 refund 处理器。
     } 
+    // 指令：take_partial（鉴别器 = 3）。
+    #[instruction(discriminator = 3)] // take_partial 指令自定义鉴别器。
+    pub fn take_partial<'info>(ctx: Context<'_, '_, 'info, 'info, TakePartial<'info>>, amount_b: u64, min_amount_a: u64) -> Result<()> { // take_partial 入口函数。
+        instructions::take_partial::handler(ctx, amount_b, min_amount_a) // 调用 take_partial 处理器。
+    } 
+    // 指令：update（鉴别器 = 4）。
+    #[instruction(discriminator = 4)] // update 指令自定义鉴别器。
+    pub fn update<'info>(ctx: Context<'_, '_, 'info, 'info, Update<'info>>, receive: Option<u64>, deposit: u64, withdraw: u64, expires_at: Option<i64>) -> Result<()> { // update 入口函数。
+        instructions::update::handler(ctx, receive, deposit, withdraw, expires_at) // 调用 update 处理器。
+    } 
 } 
@@ -1,6 +1,14 @@
 use anchor_lang::prelude::*; 
 // PDA 种子常量与托管账户状态。 
 pub const ESCROW_SEED: &[u8] = b"escrow"; // 用于派生 escrow PDA 的静态种子。
+pub const NATIVE_MINT: Pubkey = anchor_spl::token::spl_token::native_mint::ID; // 原生 SOL 使用 wSOL mint 作为标识。
+// 哪一侧使用原生 SOL。 
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)] // 可作为指令参数与账户字段。
+pub enum NativeSide { // NativeSide 枚举开始。
+    None, // 两侧都是普通代币。
+    TokenA, // maker 存入 SOL：金库为 wSOL ATA，take/refund 时关闭金库解包为 SOL。
+    TokenB, // maker 期望收到 SOL：taker 直接通过系统程序转账 lamports。
+} // NativeSide 枚举结束。
 // Escrow 账户数据结构。 // 账户结构体章节标题。
 #[derive(InitSpace)] // 自动计算账户大小用于租金豁免。
 #[account(discriminator = 1)] // 使用自定义账户鉴别器值 1。
@@ -11,4 +19,92 @@ pub struct Escrow { // Escrow 链上状态开始。
     pub mint_b: Pubkey, // maker 期望获得的 Token B 的 Mint。
     pub receive: u64, // maker 期望接收的 Token B 数量。
     pub bump: u8, // PDA bump，用于派生 escrow 地址。
+    pub taker: Option<Pubkey>, // 指定的 taker（私下协商的 OTC 交易）；None 表示任何人都可以接受。
+    pub native: NativeSide, // 原生 SOL 所在的一侧。
+    pub expires_at: i64, // 过期时间（unix 秒）；0 表示永不过期。
 } // Escrow 结构体结束
+// Escrow 辅助方法。
+impl Escrow { // Escrow 的 impl 开始。
+    // 部分成交：taker 支付 amount_b 个 Token B 时按比例释放的 Token A 数量。
+    // amount_a = floor(vault_amount * amount_b / receive)，向下取整，舍入误差留在金库中（有利于 maker）。
+    pub fn fill_amount_a(&self, vault_amount: u64, amount_b: u64) -> Option<u64> { // 返回 None 表示数量非法。
+        if amount_b == 0 || amount_b > self.receive { // amount_b 必须在 (0, receive] 区间内。
+            return None; // 数量非法。
+        } 
+        let amount_a = (vault_amount as u128 * amount_b as u128 / self.receive as u128) as u64; // u128 中计算不会溢出，结果不超过 vault_amount。
+        if amount_a == 0 { // 换算后得不到任何 Token A。
+            return None; // 拒绝，避免 taker 白付 Token B。
+        } 
+        Some(amount_a) // 返回释放数量。
+    } // fill_amount_a 结束。
+    // 指定 taker 时只允许该地址接受托管，防止链上报价被抢跑。
+    pub fn can_take(&self, taker: &Pubkey) -> bool { // 校验 taker 是否有权接受。
+        self.taker.is_none_or(|designated| designated == *taker) // 未指定或地址一致。
+    } // can_take 结束。
+    // 当前时间（Clock::unix_timestamp）是否已经到达过期时间；expires_at 为 0 的托管永不过期。
+    pub fn is_expired(&self, now: i64) -> bool { // 到达过期时间的那一秒起不能成交。
+        self.expires_at != 0 && now >= self.expires_at // 0 表示永不过期。
+    } // is_expired 结束。
+} // Escrow 的 impl 结束。
+// Escrow 辅助方法的单元测试。
+#[cfg(test)] // 仅在 cargo test 时编译。
+mod tests { // tests 模块开始。
+    use super::*; // 引入 Escrow 与 NativeSide。
+    fn escrow(receive: u64, taker: Option<Pubkey>) -> Escrow { // 构造测试用的 Escrow。
+        Escrow { // Escrow 字段。
+            seed: 42, // 任意 seed。
+            maker: Pubkey::new_from_array([1; 32]), // maker 地址。
+            mint_a: Pubkey::new_from_array([2; 32]), // mint A 地址。
+            mint_b: Pubkey::new_from_array([3; 32]), // mint B 地址。
+            receive, // 期望接收的 Token B 数量。
+            bump: 255, // 任意 bump。
+            taker, // 指定的 taker。
+            native: NativeSide::None, // 两侧都是普通代币。
+            expires_at: 0, // 永不过期。
+        } // Escrow 字段结束。
+    } // escrow 结束。
+    #[test] // 按比例释放，向下取整。
+    fn fill_amount_a_is_proportional_and_rounds_down() { // 测试开始。
+        let escrow = escrow(300, None); // 金库 1000 个 Token A 换 300 个 Token B。
+        assert_eq!(escrow.fill_amount_a(1_000, 300), Some(1_000)); // 全部成交释放整个金库。
+        assert_eq!(escrow.fill_amount_a(1_000, 150), Some(500)); // 一半换一半。
+        assert_eq!(escrow.fill_amount_a(1_000, 1), Some(3)); // 1000 / 300 = 3.33，向下取整。
+        assert_eq!(escrow.fill_amount_a(1_000, 299), Some(996)); // 1000 * 299 / 300 = 996.67，舍入误差留在金库。
+        assert_eq!(escrow.fill_amount_a(u64::MAX, 300), Some(u64::MAX)); // 乘法在 u128 中进行，不会溢出。
+    } // 测试结束。
+    #[test] // 非法数量返回 None。
+    fn fill_amount_a_rejects_invalid_amounts() { // 测试开始。
+        let escrow = escrow(300, None); // receive = 300。
+        assert_eq!(escrow.fill_amount_a(1_000, 0), None); // amount_b 为 0。
+        assert_eq!(escrow.fill_amount_a(1_000, 301), None); // amount_b 超过剩余的 receive。
+        assert_eq!(escrow.fill_amount_a(100, 2), None); // 100 * 2 / 300 = 0，taker 得不到任何 Token A。
+        assert_eq!(escrow.fill_amount_a(0, 300), None); // 金库为空。
+    } // 测试结束。
+    #[test] // 未指定 taker 时任何人都可以接受。
+    fn can_take_without_designated_taker() { // 测试开始。
+        let escrow = escrow(300, None); // 未指定 taker。
+        assert!(escrow.can_take(&Pubkey::new_from_array([4; 32]))); // 任意地址。
+        assert!(escrow.can_take(&escrow.maker)); // 包括 maker 自己。
+    } // 测试结束。
+    #[test] // 指定 taker 时只有该地址可以接受。
+    fn can_take_with_designated_taker() { // 测试开始。
+        let taker = Pubkey::new_from_array([4; 32]); // 指定的 taker。
+        let escrow = escrow(300, Some(taker)); // 私下协商的 OTC 交易。
+        assert!(escrow.can_take(&taker)); // 指定的 taker 可以接受。
+        assert!(!escrow.can_take(&Pubkey::new_from_array([5; 32]))); // 其他地址被拒绝。
+        assert!(!escrow.can_take(&escrow.maker)); // maker 也不行。
+    } // 测试结束。
+    #[test] // expires_at 为 0 时永不过期。
+    fn is_expired_never_without_expiry() { // 测试开始。
+        let escrow = escrow(300, None); // expires_at = 0。
+        assert!(!escrow.is_expired(0)); // 任意时间都不过期。
+        assert!(!escrow.is_expired(i64::MAX)); // 包括最大时间戳。
+    } // 测试结束。
+    #[test] // 到达过期时间的那一秒起过期。
+    fn is_expired_from_expires_at() { // 测试开始。
+        let escrow = Escrow { expires_at: 1_000, ..escrow(300, None) }; // 1000 秒后过期。
+        assert!(!escrow.is_expired(999)); // 过期前一秒仍可成交。
+        assert!(escrow.is_expired(1_000)); // 过期时间当秒起不能成交。
+        assert!(escrow.is_expired(1_001)); // 之后也不能成交。
+    } // 测试结束。
+} // tests 模块结束。
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*; 
+use anchor_spl::token_interface::{ // Token 接口模块，兼容 SPL Token / Token-2022。
+    get_mint_extension_data, Mint, // 读取 Mint 扩展数据的辅助函数与 Mint 类型。
+    spl_token_2022::extension::transfer_hook::TransferHook, // Token-2022 转账钩子扩展。
+}; 
+
+use crate::errors::EscrowError; // 自定义错误定义。
+// extra-account-metas PDA 的种子前缀（与 spl-transfer-hook-interface 一致）。
+pub const EXTRA_ACCOUNT_METAS_SEED: &[u8] = b"extra-account-metas"; 
+/*
+Token-2022 的 TransferHook 扩展会在每次 transfer_checked 时 CPI 调用钩子程序。
+钩子程序需要的额外账户记录在 extra-account-metas PDA 中：
+    PDA = find_program_address(["extra-account-metas", mint], hook_program_id)
+Token-2022 只能从本次 CPI 传入的账户中查找这些账户，所以客户端需要通过
+remaining accounts 传入：钩子程序、extra-account-metas PDA 以及其中列出的额外账户，
+程序再通过 with_remaining_accounts 转发给 transfer_checked。
+*/
+// 返回需要转发给 transfer_checked 的钩子账户；mint 没有转账钩子时返回空列表。
+pub fn resolve_transfer_hook_accounts<'info>( 
+    mint: &InterfaceAccount<'info, Mint>, // 被转账代币的 mint。
+    remaining_accounts: &[AccountInfo<'info>], // 指令的 remaining accounts。
+) -> Result<Vec<AccountInfo<'info>>> { 
+    let mint_info = mint.to_account_info(); // 取得 mint 的 AccountInfo。
+    if mint_info.owner != &anchor_spl::token_2022::ID { // 旧版 Token Program 没有扩展。
+        return Ok(Vec::new()); 
+    } 
+    let Ok(transfer_hook) = get_mint_extension_data::<TransferHook>(&mint_info) else { // 没有 TransferHook 扩展。
+        return Ok(Vec::new()); 
+    }; 
+    let Some(program_id) = Option::<Pubkey>::from(transfer_hook.program_id) else { // 钩子程序未设置。
+        return Ok(Vec::new()); 
+    }; 
+    let (extra_account_metas, _) = Pubkey::find_program_address( // 推导 extra-account-metas PDA。
+        &[EXTRA_ACCOUNT_METAS_SEED, mint.key().as_ref()], // PDA 种子。
+        &program_id, // 钩子程序 ID。
+    ); 
+    require!( // 钩子程序与 extra-account-metas PDA 必须都被传入。
+        remaining_accounts.iter().any(|account| account.key == &program_id) 
+            && remaining_accounts.iter().any(|account| account.key == &extra_account_metas), 
+        EscrowError::InvalidTransferHookAccounts 
+    ); 
+    Ok(remaining_accounts.to_vec()) // Token-2022 会按 extra-account-metas 自行挑选需要的账户。
+} 
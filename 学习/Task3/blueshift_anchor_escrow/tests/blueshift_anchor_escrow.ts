@@ -1,16 +1,412 @@
 import * as anchor from "@coral-xyz/anchor";
-import { Program } from "@coral-xyz/anchor";
+import { BN, Program } from "@coral-xyz/anchor";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+} from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  NATIVE_MINT,
+} from "@solana/spl-token";
+import { expect } from "chai";
 import { BlueshiftAnchorEscrow } from "../target/types/blueshift_anchor_escrow";
 
 describe("blueshift_anchor_escrow", () => {
-  // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  // 使用本地集群
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const connection = provider.connection;
 
-  const program = anchor.workspace.blueshiftAnchorEscrow as Program<BlueshiftAnchorEscrow>;
+  const program = anchor.workspace
+    .blueshiftAnchorEscrow as Program<BlueshiftAnchorEscrow>;
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+  const DEPOSIT = 1_000;
+  const RECEIVE = 500;
+
+  const programs = {
+    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    systemProgram: SystemProgram.programId,
+  };
+
+  async function airdrop(...wallets: Keypair[]) {
+    for (const wallet of wallets) {
+      const signature = await connection.requestAirdrop(
+        wallet.publicKey,
+        10 * LAMPORTS_PER_SOL
+      );
+      await connection.confirmTransaction(signature);
+    }
+  }
+
+  function escrowAddress(maker: PublicKey, seed: BN): PublicKey {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("escrow"),
+        maker.toBuffer(),
+        seed.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+  }
+
+  // 每个测试使用新的 maker、taker 和 mint，互不影响
+  async function make() {
+    const maker = Keypair.generate();
+    const taker = Keypair.generate();
+    await airdrop(maker, taker);
+
+    const mintA = await createMint(connection, maker, maker.publicKey, null, 6);
+    const mintB = await createMint(connection, taker, taker.publicKey, null, 6);
+    const makerAtaA = (
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        maker,
+        mintA,
+        maker.publicKey
+      )
+    ).address;
+    const takerAtaB = (
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        taker,
+        mintB,
+        taker.publicKey
+      )
+    ).address;
+    await mintTo(connection, maker, mintA, makerAtaA, maker, DEPOSIT);
+    await mintTo(connection, taker, mintB, takerAtaB, taker, RECEIVE);
+
+    const seed = new BN(42);
+    const escrow = escrowAddress(maker.publicKey, seed);
+    const vault = getAssociatedTokenAddressSync(mintA, escrow, true);
+
+    await program.methods
+      .make(seed, new BN(DEPOSIT), new BN(RECEIVE), null, { none: {} }, new BN(0))
+      .accountsPartial({
+        maker: maker.publicKey,
+        escrow,
+        mintA,
+        mintB,
+        makerAtaA,
+        vault,
+        ...programs,
+      })
+      .signers([maker])
+      .rpc();
+
+    return { maker, taker, mintA, mintB, makerAtaA, takerAtaB, escrow, vault };
+  }
+
+  async function balance(address: PublicKey): Promise<number> {
+    return Number((await getAccount(connection, address)).amount);
+  }
+
+  async function chainTime(): Promise<number> {
+    return (await connection.getBlockTime(await connection.getSlot())) ?? 0;
+  }
+
+  const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+  async function errorOf(promise: Promise<unknown>): Promise<string> {
+    try {
+      await promise;
+    } catch (error) {
+      return String(error);
+    }
+    return "";
+  }
+
+  it("take_partial releases token A pro rata until the escrow is filled", async () => {
+    const { maker, taker, mintA, mintB, takerAtaB, escrow, vault } =
+      await make();
+    const takerAtaA = getAssociatedTokenAddressSync(mintA, taker.publicKey);
+    const makerAtaB = getAssociatedTokenAddressSync(mintB, maker.publicKey);
+    const takePartial = (amountB: number, minAmountA = 0) =>
+      program.methods
+        .takePartial(new BN(amountB), new BN(minAmountA))
+        .accountsPartial({
+          taker: taker.publicKey,
+          maker: maker.publicKey,
+          escrow,
+          mintA,
+          mintB,
+          vault,
+          takerAtaA,
+          takerAtaB,
+          makerAtaB,
+          ...programs,
+        })
+        .signers([taker])
+        .rpc();
+
+    // 按比例只能得到 200 个 Token A，低于 min_amount_a 时拒绝成交
+    expect(await errorOf(takePartial(100, 201))).to.include(
+      "SlippageExceeded"
+    );
+
+    // 支付 1/5 的 Token B，获得 1/5 的 Token A，escrow 保持打开
+    await takePartial(100, 200);
+    expect(await balance(takerAtaA)).to.equal(200);
+    expect(await balance(makerAtaB)).to.equal(100);
+    expect(await balance(vault)).to.equal(800);
+    expect(
+      (await program.account.escrow.fetch(escrow)).receive.toNumber()
+    ).to.equal(400);
+
+    // 超过剩余的 receive 被拒绝
+    expect(await errorOf(takePartial(401))).to.include("InvalidAmount");
+
+    // 支付剩余数量后金库和 escrow 被关闭
+    await takePartial(400);
+    expect(await balance(takerAtaA)).to.equal(DEPOSIT);
+    expect(await balance(makerAtaB)).to.equal(RECEIVE);
+    expect(await connection.getAccountInfo(vault)).to.be.null;
+    expect(await connection.getAccountInfo(escrow)).to.be.null;
+  });
+
+  it("update reprices the escrow and moves token A in and out of the vault", async () => {
+    const { maker, taker, mintA, mintB, makerAtaA, takerAtaB, escrow, vault } =
+      await make();
+    const update = (
+      receive: number | null,
+      deposit: number,
+      withdraw: number,
+      expiresAt: number | null = null
+    ) =>
+      program.methods
+        .update(
+          receive === null ? null : new BN(receive),
+          new BN(deposit),
+          new BN(withdraw),
+          expiresAt === null ? null : new BN(expiresAt)
+        )
+        .accountsPartial({
+          maker: maker.publicKey,
+          escrow,
+          mintA,
+          vault,
+          makerAtaA,
+          ...programs,
+        })
+        .signers([maker])
+        .rpc();
+    const receive = async () =>
+      (await program.account.escrow.fetch(escrow)).receive.toNumber();
+
+    // 取回 400 个 Token A，并把期望数量改为 300
+    await update(300, 0, 400);
+    expect(await balance(vault)).to.equal(DEPOSIT - 400);
+    expect(await balance(makerAtaA)).to.equal(400);
+    expect(await receive()).to.equal(300);
+
+    // 追加 100 个 Token A，receive 保持不变
+    await update(null, 100, 0);
+    expect(await balance(vault)).to.equal(DEPOSIT - 300);
+    expect(await balance(makerAtaA)).to.equal(300);
+    expect(await receive()).to.equal(300);
+
+    // 不能取空金库、不能同时追加和取回、receive 不能为 0
+    expect(await errorOf(update(null, 0, DEPOSIT - 300))).to.include(
+      "InvalidAmount"
+    );
+    expect(await errorOf(update(null, 1, 1))).to.include("InvalidAmount");
+    expect(await errorOf(update(0, 0, 0))).to.include("InvalidAmount");
+
+    // 设置、延长或取消过期时间；过去的时间被拒绝
+    const now = Math.floor(Date.now() / 1000);
+    const expiresAt = async () =>
+      (await program.account.escrow.fetch(escrow)).expiresAt.toNumber();
+    await update(null, 0, 0, now + 3600);
+    expect(await expiresAt()).to.equal(now + 3600);
+    expect(await errorOf(update(null, 0, 0, now - 3600))).to.include(
+      "EscrowExpired"
+    );
+    await update(null, 0, 0, 0);
+    expect(await expiresAt()).to.equal(0);
+
+    // taker 按签名时看到的条件设置 min_amount_a / max_pay，maker 抢先修改后成交失败
+    const take = (minAmountA: number, maxPay: number | null) =>
+      program.methods
+        .take(new BN(minAmountA), maxPay === null ? null : new BN(maxPay))
+        .accountsPartial({
+          taker: taker.publicKey,
+          maker: maker.publicKey,
+          escrow,
+          mintA,
+          mintB,
+          vault,
+          takerAtaA: getAssociatedTokenAddressSync(mintA, taker.publicKey),
+          takerAtaB,
+          makerAtaB: getAssociatedTokenAddressSync(mintB, maker.publicKey),
+          ...programs,
+        })
+        .signers([taker])
+        .rpc();
+    expect(await errorOf(take(DEPOSIT, null))).to.include("SlippageExceeded");
+    expect(await errorOf(take(0, 299))).to.include("SlippageExceeded");
+
+    // 缩短过期时间后到期，成交被拒绝；取消过期时间后可以成交
+    await update(null, 0, 0, (await chainTime()) + 2);
+    await sleep(4_000);
+    expect(await errorOf(take(DEPOSIT - 300, 300))).to.include(
+      "EscrowExpired"
+    );
+    await update(null, 0, 0, 0);
+    await take(DEPOSIT - 300, 300);
+    expect(await connection.getAccountInfo(escrow)).to.be.null;
+  });
+
+  it("make and take move native SOL through a wrapped SOL vault", async () => {
+    const maker = Keypair.generate();
+    const taker = Keypair.generate();
+    await airdrop(maker, taker);
+    const mintB = await createMint(connection, taker, taker.publicKey, null, 6);
+    const takerAtaB = (
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        taker,
+        mintB,
+        taker.publicKey
+      )
+    ).address;
+    await mintTo(connection, taker, mintB, takerAtaB, taker, RECEIVE);
+
+    const seed = new BN(7);
+    const escrow = escrowAddress(maker.publicKey, seed);
+    const vault = getAssociatedTokenAddressSync(NATIVE_MINT, escrow, true);
+    const makerAtaB = getAssociatedTokenAddressSync(mintB, maker.publicKey);
+
+    // maker 直接存入 1 SOL，无需 wSOL 账户
+    await program.methods
+      .make(
+        seed,
+        new BN(LAMPORTS_PER_SOL),
+        new BN(RECEIVE),
+        null,
+        { tokenA: {} },
+        new BN(0)
+      )
+      .accountsPartial({
+        maker: maker.publicKey,
+        escrow,
+        mintA: NATIVE_MINT,
+        mintB,
+        makerAtaA: null,
+        vault,
+        ...programs,
+      })
+      .signers([maker])
+      .rpc();
+    expect(await balance(vault)).to.equal(LAMPORTS_PER_SOL);
+
+    // taker 支付 Token B，关闭金库时收到解包后的 SOL
+    const before = await connection.getBalance(taker.publicKey);
+    await program.methods
+      .take(new BN(LAMPORTS_PER_SOL), null)
+      .accountsPartial({
+        taker: taker.publicKey,
+        maker: maker.publicKey,
+        escrow,
+        mintA: NATIVE_MINT,
+        mintB,
+        vault,
+        takerAtaA: null,
+        takerAtaB,
+        makerAtaB,
+        ...programs,
+      })
+      .signers([taker])
+      .rpc();
+    expect(await balance(makerAtaB)).to.equal(RECEIVE);
+    expect(await connection.getBalance(taker.publicKey)).to.be.greaterThan(
+      before + 0.99 * LAMPORTS_PER_SOL
+    );
+    expect(await connection.getAccountInfo(vault)).to.be.null;
+    expect(await connection.getAccountInfo(escrow)).to.be.null;
+  });
+
+  it("take_partial pays out wrapped SOL when token A is native SOL", async () => {
+    const maker = Keypair.generate();
+    const taker = Keypair.generate();
+    await airdrop(maker, taker);
+    const mintB = await createMint(connection, taker, taker.publicKey, null, 6);
+    const takerAtaB = (
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        taker,
+        mintB,
+        taker.publicKey
+      )
+    ).address;
+    await mintTo(connection, taker, mintB, takerAtaB, taker, RECEIVE);
+
+    const seed = new BN(8);
+    const escrow = escrowAddress(maker.publicKey, seed);
+    const vault = getAssociatedTokenAddressSync(NATIVE_MINT, escrow, true);
+    const takerAtaA = getAssociatedTokenAddressSync(
+      NATIVE_MINT,
+      taker.publicKey
+    );
+    const makerAtaB = getAssociatedTokenAddressSync(mintB, maker.publicKey);
+
+    await program.methods
+      .make(
+        seed,
+        new BN(LAMPORTS_PER_SOL),
+        new BN(RECEIVE),
+        null,
+        { tokenA: {} },
+        new BN(0)
+      )
+      .accountsPartial({
+        maker: maker.publicKey,
+        escrow,
+        mintA: NATIVE_MINT,
+        mintB,
+        makerAtaA: null,
+        vault,
+        ...programs,
+      })
+      .signers([maker])
+      .rpc();
+
+    const takePartial = (amountB: number) =>
+      program.methods
+        .takePartial(new BN(amountB), new BN(0))
+        .accountsPartial({
+          taker: taker.publicKey,
+          maker: maker.publicKey,
+          escrow,
+          mintA: NATIVE_MINT,
+          mintB,
+          vault,
+          takerAtaA,
+          takerAtaB,
+          makerAtaB,
+          ...programs,
+        })
+        .signers([taker])
+        .rpc();
+
+    // 部分成交时金库保持打开，taker 的 wSOL ATA 收到按比例释放的 wSOL
+    await takePartial(RECEIVE / 2);
+    expect(await balance(takerAtaA)).to.equal(LAMPORTS_PER_SOL / 2);
+    expect(await balance(vault)).to.equal(LAMPORTS_PER_SOL / 2);
+
+    // 剩余部分同样以 wSOL 交付，之后金库和 escrow 被关闭
+    await takePartial(RECEIVE / 2);
+    expect(await balance(takerAtaA)).to.equal(LAMPORTS_PER_SOL);
+    expect(await balance(makerAtaB)).to.equal(RECEIVE);
+    expect(await connection.getAccountInfo(vault)).to.be.null;
+    expect(await connection.getAccountInfo(escrow)).to.be.null;
   });
 });
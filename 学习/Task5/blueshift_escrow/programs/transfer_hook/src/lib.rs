@@ -0,0 +1,53 @@
+// =============================================================================
+// 测试用 Token-2022 转账钩子程序
+// =============================================================================
+// 只用于 escrow 的集成测试（tests/transfer_hook.rs），不用于部署
+//
+// 行为：
+// - 只处理 spl-transfer-hook-interface 的 Execute 指令
+// - 要求 extra-account-metas 中登记的第一个额外账户被转发进来
+// - 每次被调用时将该账户中的 u64 计数器加 1，测试据此确认钩子确实执行
+//
+// Token-2022 调用 Execute 时的账户顺序：
+//   0. source token account
+//   1. mint
+//   2. destination token account
+//   3. source authority
+//   4. extra-account-metas PDA
+//   5.. extra-account-metas 中登记的额外账户
+use pinocchio::{AccountView, Address, entrypoint, ProgramResult};
+use pinocchio::error::ProgramError;
+
+entrypoint!(process_instruction);
+
+// sha256("spl-transfer-hook-interface:execute")[..8]
+pub const EXECUTE_DISCRIMINATOR: [u8; 8] = [105, 37, 101, 197, 75, 251, 102, 26];
+
+fn process_instruction(
+    program_id: &Address,
+    accounts: &[AccountView],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let Some((discriminator, _amount)) = instruction_data.split_first_chunk::<8>() else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+    if discriminator.ne(&EXECUTE_DISCRIMINATOR) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let [_source, _mint, _destination, _authority, _extra_account_metas, counter, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !counter.owned_by(program_id) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    let mut data = counter.try_borrow_mut()?;
+    let Some(count) = data.first_chunk_mut::<8>() else {
+        return Err(ProgramError::InvalidAccountData);
+    };
+    *count = (u64::from_le_bytes(*count) + 1).to_le_bytes();
+
+    Ok(())
+}
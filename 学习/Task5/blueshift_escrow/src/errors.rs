@@ -20,6 +20,37 @@ pub enum EscrowError{
     InvalidAccountData=3,
 
     InvalidAddress = 4,
+
+    InvalidTransferHookAccounts = 5,
+
+    EscrowExpired = 6,
+
+    EscrowNotExpired = 7,
+
+    InvalidAmount = 8,
+
+    InvalidTaker = 9,
+
+    TakerNotAllowlisted = 10,
+
+    InvalidFee = 11,
+
+    InvalidAdmin = 12,
+
+    InvalidPreimage = 13,
+
+    InvalidAuthority = 14,
+
+    MilestoneAlreadyReleased = 15,
+    InvalidSignature = 16,
+    NonceAlreadyUsed = 17,
+    SlippageExceeded = 18,
+    BidTooLow = 19,
+    OffersDoNotCross = 20,
+    InvalidFlashTake = 21,
+    SeedInUse = 22,
+    InvalidReferrer = 23,
+    MissingMarket = 24,
 }
 
 
@@ -42,6 +73,26 @@ impl fmt::Display for EscrowError {
             EscrowError::InvalidOwner => write!(f, "Invalid owner"),
             EscrowError::InvalidAccountData => write!(f, "Invalid account data"),
             EscrowError::InvalidAddress => write!(f, "Invalid address"),
+            EscrowError::InvalidTransferHookAccounts => write!(f, "Invalid transfer hook accounts"),
+            EscrowError::EscrowExpired => write!(f, "Escrow expired"),
+            EscrowError::EscrowNotExpired => write!(f, "Escrow not expired"),
+            EscrowError::InvalidAmount => write!(f, "Invalid amount"),
+            EscrowError::InvalidTaker => write!(f, "Invalid taker"),
+            EscrowError::TakerNotAllowlisted => write!(f, "Taker not allowlisted"),
+            EscrowError::InvalidFee => write!(f, "Invalid fee"),
+            EscrowError::InvalidAdmin => write!(f, "Invalid admin"),
+            EscrowError::InvalidPreimage => write!(f, "Invalid preimage"),
+            EscrowError::InvalidAuthority => write!(f, "Invalid authority"),
+            EscrowError::MilestoneAlreadyReleased => write!(f, "Milestone already released"),
+            EscrowError::InvalidSignature => write!(f, "Invalid signature"),
+            EscrowError::NonceAlreadyUsed => write!(f, "Nonce already used"),
+            EscrowError::SlippageExceeded => write!(f, "Slippage exceeded"),
+            EscrowError::BidTooLow => write!(f, "Bid too low"),
+            EscrowError::OffersDoNotCross => write!(f, "Offers do not cross"),
+            EscrowError::InvalidFlashTake => write!(f, "Invalid flash take"),
+            EscrowError::SeedInUse => write!(f, "Seed in use"),
+            EscrowError::InvalidReferrer => write!(f, "Invalid referrer"),
+            EscrowError::MissingMarket => write!(f, "Missing market account"),
         }
     }
 }
\ No newline at end of file
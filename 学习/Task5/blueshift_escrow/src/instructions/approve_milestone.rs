@@ -0,0 +1,181 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use solana_address::Address;
+use crate::{AccountCheck, AccountClose, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, CloseAccountInterface, EscrowError, MilestoneEscrow, MintInterface, ProgramAccount, ProgramAccountCheck, ProgramAccountType, SignerAccount, TokenProgramInterface, TransferCheckedInterface, TransferHook};
+
+// ApproveMilestone：批准一个里程碑，把该阶段的代币释放给收款方
+// 最后一个里程碑释放后金库为空，关闭金库和托管，租金退还给付款方
+pub struct ApproveMilestoneAccounts<'info> {
+    // 授权者：付款方或仲裁人
+    pub authority: &'info AccountView,
+    pub payer: &'info AccountView,
+    pub payee: &'info AccountView,
+    pub escrow: &'info AccountView,
+    pub mint: &'info AccountView,
+    pub vault: &'info AccountView,
+    pub payee_ata: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    // 剩余账户：Token-2022 转账钩子需要的账户
+    pub remaining_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for ApproveMilestoneAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, payer, payee, escrow, mint, vault, payee_ata, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        ProgramAccount::check::<MilestoneEscrow>(escrow)?;
+
+        MintInterface::check(mint)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        AssociatedTokenAccount::check(vault, escrow, mint, token_program)?;
+
+        Ok(Self{
+            authority,
+            payer,
+            payee,
+            escrow,
+            mint,
+            vault,
+            payee_ata,
+            system_program,
+            token_program,
+            remaining_accounts,
+        })
+    }
+}
+
+pub struct ApproveMilestoneInstructionData {
+    pub index: usize,
+}
+
+impl<'info> TryFrom<&'info [u8]> for ApproveMilestoneInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        // 数据布局：index(1)
+        let [index] = data else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        Ok(Self{
+            index: *index as usize,
+        })
+    }
+}
+
+pub struct ApproveMilestone<'info> {
+    pub accounts: ApproveMilestoneAccounts<'info>,
+    pub instruction_data: ApproveMilestoneInstructionData,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for ApproveMilestone<'info> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = ApproveMilestoneAccounts::try_from(accounts)?;
+        let instruction_data = ApproveMilestoneInstructionData::try_from(data)?;
+
+        // 收款方的 ATA 可能还不存在，由授权者支付创建费用
+        AssociatedTokenAccount::init_if_needed(
+            accounts.payee_ata,
+            accounts.mint,
+            accounts.authority,
+            accounts.payee,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self{
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'info> ApproveMilestone<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &16;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump, amount, complete) = {
+            let mut data = self.accounts.escrow.try_borrow_mut()?;
+            let escrow = MilestoneEscrow::load_mut(&mut data)?;
+            // PDA 由付款方地址派生，验证通过即说明传入的 payer 就是托管的付款方
+            let escrow_key = Address::create_program_address(
+                &[
+                    b"milestone",
+                    self.accounts.payer.address().as_ref(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump(),
+                ],
+                &crate::ID
+            )?;
+            if &escrow_key != self.accounts.escrow.address() {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+            if escrow.payee() != self.accounts.payee.address() || escrow.mint() != self.accounts.mint.address() {
+                return Err(EscrowError::InvalidAddress.into());
+            }
+            if !escrow.can_approve(self.accounts.authority.address()) {
+                return Err(EscrowError::InvalidAuthority.into());
+            }
+            let amount = escrow.release(self.instruction_data.index)?;
+            (escrow.seed(), escrow.bump(), amount, escrow.is_complete())
+        };
+
+        let seed_binding = seed.to_le_bytes();
+        let bump_binding = bump;
+        let escrow_seeds = [
+            Seed::from(b"milestone"),
+            Seed::from(self.accounts.payer.address().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+        let signers = [Signer::from(&escrow_seeds)];
+
+        // 最后一个里程碑转出金库的全部余额：任何人都可以向金库 ATA 转入代币，
+        // 只转出该阶段的数量会留下余额，导致金库无法关闭
+        let amount = if complete {
+            let vault_data = self.accounts.vault.try_borrow()?;
+            u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
+        } else {
+            amount
+        };
+        TransferCheckedInterface {
+            from: self.accounts.vault,
+            mint: self.accounts.mint,
+            to: self.accounts.payee_ata,
+            authority: self.accounts.escrow,
+            token_program: self.accounts.token_program,
+            extra_accounts: TransferHook::resolve(self.accounts.mint, self.accounts.remaining_accounts)?,
+            amount,
+            decimals: MintInterface::decimals(self.accounts.mint)?,
+        }.invoke_signed(&signers)?;
+
+        if !complete {
+            return Ok(());
+        }
+
+        // 全部里程碑已释放，金库余额已全部转出
+        CloseAccountInterface {
+            account: self.accounts.vault,
+            destination: self.accounts.payer,
+            authority: self.accounts.escrow,
+            token_program: self.accounts.token_program,
+        }.invoke_signed(&signers)?;
+
+        ProgramAccount::close(
+            self.accounts.escrow,
+            self.accounts.payer
+        )
+    }
+}
@@ -0,0 +1,174 @@
+use pinocchio::{Address, AccountView, ProgramResult};
+use pinocchio::cpi::Seed;
+use pinocchio::error::ProgramError;
+use crate::{EscrowError, AccountCheck, SignerAccount, MintInterface, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, ProgramAccount, ProgramAccountInit, ProgramAccountType, ArbiterEscrow, TokenProgramInterface, TransferHook, TransferCheckedInterface};
+
+// ArbiterMake：买方把代币存入三方托管，记录卖方和仲裁人
+pub struct ArbiterMakeAccounts<'info> {
+    pub buyer: &'info AccountView,
+    pub escrow: &'info AccountView,
+    pub mint: &'info AccountView,
+    pub buyer_ata: &'info AccountView,
+    pub vault: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    // 剩余账户：Token-2022 转账钩子需要的账户
+    pub remaining_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for ArbiterMakeAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [buyer, escrow, mint, buyer_ata, vault, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(buyer)?;
+
+        MintInterface::check(mint)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        AssociatedTokenAccount::check(buyer_ata, buyer, mint, token_program)?;
+
+        Ok(Self{
+            buyer,
+            escrow,
+            mint,
+            buyer_ata,
+            vault,
+            system_program,
+            token_program,
+            remaining_accounts,
+        })
+    }
+}
+
+pub struct ArbiterMakeInstructionData {
+    pub seed: u64,
+    pub amount: u64,
+    pub seller: Address,
+    pub arbiter: Address,
+}
+
+impl<'info> TryFrom<&'info [u8]> for ArbiterMakeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        // 数据布局：seed(8) + amount(8) + seller(32) + arbiter(32)
+        if data.len() != 80 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let amount = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let seller = Address::new_from_array(data[16..48].try_into().unwrap());
+        let arbiter = Address::new_from_array(data[48..80].try_into().unwrap());
+
+        if amount == 0 {
+            return Err(EscrowError::InvalidAmount.into());
+        }
+
+        Ok(Self{
+            seed,
+            amount,
+            seller,
+            arbiter,
+        })
+    }
+}
+
+pub struct ArbiterMake<'info> {
+    pub accounts: ArbiterMakeAccounts<'info>,
+    pub instruction_data: ArbiterMakeInstructionData,
+    pub bump: u8,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for ArbiterMake<'info> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = ArbiterMakeAccounts::try_from(accounts)?;
+        let instruction_data = ArbiterMakeInstructionData::try_from(data)?;
+
+        // 三方必须互不相同：买方兼任仲裁人时可以自己发起争议再把代币退回
+        let buyer = accounts.buyer.address();
+        if &instruction_data.seller == buyer
+            || &instruction_data.arbiter == buyer
+            || instruction_data.arbiter == instruction_data.seller
+        {
+            return Err(EscrowError::InvalidAddress.into());
+        }
+
+        let (_, bump) = Address::find_program_address(
+            &[
+                b"arbiter_escrow",
+                buyer.as_ref(),
+                &instruction_data.seed.to_le_bytes(),
+            ],
+            &crate::ID,
+        );
+
+        let seed_binding = instruction_data.seed.to_le_bytes();
+        let bump_binding = [bump];
+        let escrow_seeds = [
+            Seed::from(b"arbiter_escrow"),
+            Seed::from(buyer.as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+
+        ProgramAccount::init::<ArbiterEscrow>(
+            accounts.buyer,
+            accounts.escrow,
+            &escrow_seeds,
+        )?;
+
+        AssociatedTokenAccount::init(
+            accounts.vault,
+            accounts.mint,
+            accounts.buyer,
+            accounts.escrow,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self{
+            accounts,
+            instruction_data,
+            bump,
+        })
+    }
+}
+
+impl<'info> ArbiterMake<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &11;
+
+    pub fn process(&mut self) -> ProgramResult {
+        {
+            let mut data = self.accounts.escrow.try_borrow_mut()?;
+            let escrow = ArbiterEscrow::load_mut(data.as_mut())?;
+            escrow.set_inner(
+                self.instruction_data.seed,
+                *self.accounts.buyer.address(),
+                self.instruction_data.seller,
+                self.instruction_data.arbiter,
+                *self.accounts.mint.address(),
+                [self.bump],
+            );
+        }
+
+        TransferCheckedInterface{
+            from: self.accounts.buyer_ata,
+            mint: self.accounts.mint,
+            to: self.accounts.vault,
+            authority: self.accounts.buyer,
+            token_program: self.accounts.token_program,
+            extra_accounts: TransferHook::resolve(self.accounts.mint, self.accounts.remaining_accounts)?,
+            amount: self.instruction_data.amount,
+            decimals: MintInterface::decimals(self.accounts.mint)?,
+        }.invoke()?;
+
+        Ok(())
+    }
+}
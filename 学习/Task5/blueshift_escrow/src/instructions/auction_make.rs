@@ -0,0 +1,178 @@
+use pinocchio::{Address, AccountView, ProgramResult};
+use pinocchio::cpi::Seed;
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use crate::{EscrowError, AccountCheck, SignerAccount, MintInterface, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, ProgramAccount, ProgramAccountInit, ProgramAccountType, Auction, TokenProgramInterface, TransferHook, TransferCheckedInterface};
+
+// AuctionMake：创建者把代币 A 存入拍卖金库，设定最低出价（代币 B）和结束时间
+pub struct AuctionMakeAccounts<'info> {
+    pub maker: &'info AccountView,
+    pub auction: &'info AccountView,
+    pub mint_a: &'info AccountView,
+    pub mint_b: &'info AccountView,
+    pub maker_ata_a: &'info AccountView,
+    pub vault: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    // 剩余账户：Token-2022 转账钩子需要的账户
+    pub remaining_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for AuctionMakeAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [maker, auction, mint_a, mint_b, maker_ata_a, vault, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(maker)?;
+
+        MintInterface::check(mint_a)?;
+
+        MintInterface::check(mint_b)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        AssociatedTokenAccount::check(maker_ata_a, maker, mint_a, token_program)?;
+
+        Ok(Self{
+            maker,
+            auction,
+            mint_a,
+            mint_b,
+            maker_ata_a,
+            vault,
+            system_program,
+            token_program,
+            remaining_accounts,
+        })
+    }
+}
+
+pub struct AuctionMakeInstructionData {
+    pub seed: u64,
+    pub amount: u64,
+    pub min_bid: u64,
+    pub end_time: i64,
+}
+
+impl<'info> TryFrom<&'info [u8]> for AuctionMakeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        // 数据布局：seed(8) + amount(8) + min_bid(8) + end_time(8)
+        if data.len() != 32 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let amount = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let min_bid = u64::from_le_bytes(data[16..24].try_into().unwrap());
+        let end_time = i64::from_le_bytes(data[24..32].try_into().unwrap());
+
+        // 最低出价为 0 时第一笔出价可以是 0，创建者会白白送出代币 A
+        if amount == 0 || min_bid == 0 {
+            return Err(EscrowError::InvalidAmount.into());
+        }
+
+        Ok(Self{
+            seed,
+            amount,
+            min_bid,
+            end_time,
+        })
+    }
+}
+
+pub struct AuctionMake<'info> {
+    pub accounts: AuctionMakeAccounts<'info>,
+    pub instruction_data: AuctionMakeInstructionData,
+    pub bump: u8,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for AuctionMake<'info> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = AuctionMakeAccounts::try_from(accounts)?;
+        let instruction_data = AuctionMakeInstructionData::try_from(data)?;
+
+        // 结束时间必须在未来，否则创建出来就无法出价
+        if instruction_data.end_time <= Clock::get()?.unix_timestamp {
+            return Err(EscrowError::EscrowExpired.into());
+        }
+
+        let (_, bump) = Address::find_program_address(
+            &[
+                b"auction",
+                accounts.maker.address().as_ref(),
+                &instruction_data.seed.to_le_bytes(),
+            ],
+            &crate::ID,
+        );
+
+        let seed_binding = instruction_data.seed.to_le_bytes();
+        let bump_binding = [bump];
+        let auction_seeds = [
+            Seed::from(b"auction"),
+            Seed::from(accounts.maker.address().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+
+        ProgramAccount::init::<Auction>(
+            accounts.maker,
+            accounts.auction,
+            &auction_seeds,
+        )?;
+
+        AssociatedTokenAccount::init(
+            accounts.vault,
+            accounts.mint_a,
+            accounts.maker,
+            accounts.auction,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self{
+            accounts,
+            instruction_data,
+            bump,
+        })
+    }
+}
+
+impl<'info> AuctionMake<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &19;
+
+    pub fn process(&mut self) -> ProgramResult {
+        {
+            let mut data = self.accounts.auction.try_borrow_mut()?;
+            let auction = Auction::load_mut(data.as_mut())?;
+            auction.set_inner(
+                self.instruction_data.seed,
+                *self.accounts.maker.address(),
+                *self.accounts.mint_a.address(),
+                *self.accounts.mint_b.address(),
+                self.instruction_data.min_bid,
+                self.instruction_data.end_time,
+                [self.bump],
+            );
+        }
+
+        TransferCheckedInterface{
+            from: self.accounts.maker_ata_a,
+            mint: self.accounts.mint_a,
+            to: self.accounts.vault,
+            authority: self.accounts.maker,
+            token_program: self.accounts.token_program,
+            extra_accounts: TransferHook::resolve(self.accounts.mint_a, self.accounts.remaining_accounts)?,
+            amount: self.instruction_data.amount,
+            decimals: MintInterface::decimals(self.accounts.mint_a)?,
+        }.invoke()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+use pinocchio::{Address, AccountView, ProgramResult};
+use pinocchio::cpi::Seed;
+use pinocchio::error::ProgramError;
+use crate::{AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, BundleEscrow, MintInterface, ProgramAccount, ProgramAccountInit, ProgramAccountType, SignerAccount, TokenProgramInterface, TransferCheckedInterface, TransferHook, MAX_BUNDLE_LEGS};
+
+// BundleMake：创建者一次存入多种代币，要求换回多种代币
+// 剩余账户按顺序传入：
+//   每种存入的代币 [mint, maker_ata, vault] × give_count
+//   每种要求换回的代币 [mint] × receive_count
+//   其余为 Token-2022 转账钩子需要的账户
+pub struct BundleMakeAccounts<'info> {
+    pub maker: &'info AccountView,
+    pub escrow: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    pub remaining_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for BundleMakeAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [maker, escrow, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(maker)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        Ok(Self{
+            maker,
+            escrow,
+            system_program,
+            token_program,
+            remaining_accounts,
+        })
+    }
+}
+
+pub struct BundleMakeInstructionData {
+    pub seed: u64,
+    pub give_count: usize,
+    pub give_amounts: [u64; MAX_BUNDLE_LEGS],
+    pub receive_count: usize,
+    pub receive_amounts: [u64; MAX_BUNDLE_LEGS],
+}
+
+impl<'info> TryFrom<&'info [u8]> for BundleMakeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        // 数据布局：seed(8) + give_count(1) + receive_count(1)
+        //   + give_amounts(8 * give_count) + receive_amounts(8 * receive_count)
+        if data.len() < 10 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let give_count = data[8] as usize;
+        let receive_count = data[9] as usize;
+        if !(1..=MAX_BUNDLE_LEGS).contains(&give_count)
+            || !(1..=MAX_BUNDLE_LEGS).contains(&receive_count)
+            || data.len() != 10 + 8 * (give_count + receive_count)
+        {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let mut amounts = data[10..].chunks_exact(8).map(|amount| u64::from_le_bytes(amount.try_into().unwrap()));
+        let mut give_amounts = [0; MAX_BUNDLE_LEGS];
+        for slot in give_amounts.iter_mut().take(give_count) {
+            *slot = amounts.next().unwrap();
+        }
+        let mut receive_amounts = [0; MAX_BUNDLE_LEGS];
+        for slot in receive_amounts.iter_mut().take(receive_count) {
+            *slot = amounts.next().unwrap();
+        }
+
+        Ok(Self{
+            seed,
+            give_count,
+            give_amounts,
+            receive_count,
+            receive_amounts,
+        })
+    }
+}
+
+pub struct BundleMake<'info> {
+    pub accounts: BundleMakeAccounts<'info>,
+    pub instruction_data: BundleMakeInstructionData,
+    // 每种存入的代币 [mint, maker_ata, vault]
+    pub give_legs: &'info [AccountView],
+    // 每种要求换回的代币 [mint]
+    pub receive_mints: &'info [AccountView],
+    // Token-2022 转账钩子需要的账户
+    pub hook_accounts: &'info [AccountView],
+    pub bump: u8,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for BundleMake<'info> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = BundleMakeAccounts::try_from(accounts)?;
+        let instruction_data = BundleMakeInstructionData::try_from(data)?;
+
+        let (give_legs, rest) = accounts.remaining_accounts
+            .split_at_checked(instruction_data.give_count * 3)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let (receive_mints, hook_accounts) = rest
+            .split_at_checked(instruction_data.receive_count)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+        for leg in give_legs.chunks_exact(3) {
+            MintInterface::check(&leg[0])?;
+            AssociatedTokenAccount::check(&leg[1], accounts.maker, &leg[0], accounts.token_program)?;
+        }
+        for mint in receive_mints {
+            MintInterface::check(mint)?;
+        }
+
+        let (_, bump) = Address::find_program_address(
+            &[
+                b"bundle",
+                accounts.maker.address().as_ref(),
+                &instruction_data.seed.to_le_bytes(),
+            ],
+            &crate::ID,
+        );
+
+        let seed_binding = instruction_data.seed.to_le_bytes();
+        let bump_binding = [bump];
+        let escrow_seeds = [
+            Seed::from(b"bundle"),
+            Seed::from(accounts.maker.address().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+
+        ProgramAccount::init::<BundleEscrow>(
+            accounts.maker,
+            accounts.escrow,
+            &escrow_seeds,
+        )?;
+
+        // 每种存入的代币各创建一个金库；mint 重复时第二次创建会失败
+        for leg in give_legs.chunks_exact(3) {
+            AssociatedTokenAccount::init(
+                &leg[2],
+                &leg[0],
+                accounts.maker,
+                accounts.escrow,
+                accounts.system_program,
+                accounts.token_program,
+            )?;
+        }
+
+        Ok(Self{
+            accounts,
+            instruction_data,
+            give_legs,
+            receive_mints,
+            hook_accounts,
+            bump,
+        })
+    }
+}
+
+impl<'info> BundleMake<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &22;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut give = [(Address::default(), 0); MAX_BUNDLE_LEGS];
+        for ((slot, leg), amount) in give.iter_mut().zip(self.give_legs.chunks_exact(3)).zip(self.instruction_data.give_amounts) {
+            *slot = (*leg[0].address(), amount);
+        }
+        let mut receive = [(Address::default(), 0); MAX_BUNDLE_LEGS];
+        for ((slot, mint), amount) in receive.iter_mut().zip(self.receive_mints).zip(self.instruction_data.receive_amounts) {
+            *slot = (*mint.address(), amount);
+        }
+
+        {
+            let mut data = self.accounts.escrow.try_borrow_mut()?;
+            let escrow = BundleEscrow::load_mut(data.as_mut())?;
+            escrow.set_inner(
+                self.instruction_data.seed,
+                *self.accounts.maker.address(),
+                &give[..self.instruction_data.give_count],
+                &receive[..self.instruction_data.receive_count],
+                [self.bump],
+            )?;
+        }
+
+        for (leg, amount) in self.give_legs.chunks_exact(3).zip(self.instruction_data.give_amounts) {
+            TransferCheckedInterface{
+                from: &leg[1],
+                mint: &leg[0],
+                to: &leg[2],
+                authority: self.accounts.maker,
+                token_program: self.accounts.token_program,
+                extra_accounts: TransferHook::resolve(&leg[0], self.hook_accounts)?,
+                amount,
+                decimals: MintInterface::decimals(&leg[0])?,
+            }.invoke()?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use solana_address::Address;
+use crate::{AccountCheck, AccountClose, AssociatedTokenAccount, AssociatedTokenAccountInit, BundleEscrow, BundleVault, EscrowError, ProgramAccount, ProgramAccountCheck, ProgramAccountType, SignerAccount, TokenProgramInterface};
+
+// BundleRefund：创建者取回全部存入的代币并关闭组合托管
+// 剩余账户按顺序传入：
+//   每种存入的代币 [mint, vault, maker_ata] × give_count
+//   其余为 Token-2022 转账钩子需要的账户
+pub struct BundleRefundAccounts<'info> {
+    pub maker: &'info AccountView,
+    pub escrow: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    pub remaining_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for BundleRefundAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [maker, escrow, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(maker)?;
+
+        ProgramAccount::check::<BundleEscrow>(escrow)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        Ok(Self{
+            maker,
+            escrow,
+            system_program,
+            token_program,
+            remaining_accounts,
+        })
+    }
+}
+
+pub struct BundleRefund<'info> {
+    pub accounts: BundleRefundAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for BundleRefund<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        Ok(Self{
+            accounts: BundleRefundAccounts::try_from(accounts)?,
+        })
+    }
+}
+
+impl<'info> BundleRefund<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &24;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump, give_count, give) = {
+            let data = self.accounts.escrow.try_borrow()?;
+            let escrow = BundleEscrow::load(&data)?;
+            // PDA 由签名者地址派生，验证通过即说明签名者就是托管的创建者
+            let escrow_key = Address::create_program_address(
+                &[
+                    b"bundle",
+                    self.accounts.maker.address().as_ref(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump(),
+                ],
+                &crate::ID
+            )?;
+            if &escrow_key != self.accounts.escrow.address() {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+            (escrow.seed(), escrow.bump(), escrow.give_count(), escrow.give_legs())
+        };
+
+        let (give_legs, hook_accounts) = self.accounts.remaining_accounts
+            .split_at_checked(give_count * 3)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+        let seed_binding = seed.to_le_bytes();
+        let bump_binding = bump;
+        let escrow_seeds = [
+            Seed::from(b"bundle"),
+            Seed::from(self.accounts.maker.address().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+        let signers = [Signer::from(&escrow_seeds)];
+
+        for (leg, (expected_mint, _)) in give_legs.chunks_exact(3).zip(give) {
+            let [mint, vault, maker_ata] = leg else {
+                unreachable!()
+            };
+            if &expected_mint != mint.address() {
+                return Err(EscrowError::InvalidAddress.into());
+            }
+            // 创建者的 ATA 可能已经被关闭
+            AssociatedTokenAccount::init_if_needed(
+                maker_ata,
+                mint,
+                self.accounts.maker,
+                self.accounts.maker,
+                self.accounts.system_program,
+                self.accounts.token_program,
+            )?;
+            BundleVault {
+                escrow: self.accounts.escrow,
+                maker: self.accounts.maker,
+                mint,
+                vault,
+                token_program: self.accounts.token_program,
+                hook_accounts,
+            }.release(maker_ata, &signers)?;
+        }
+
+        ProgramAccount::close(
+            self.accounts.escrow,
+            self.accounts.maker
+        )
+    }
+}
@@ -0,0 +1,206 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use solana_address::Address;
+use crate::{AccountCheck, AccountClose, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, BundleEscrow, CloseAccountInterface, EscrowError, MintInterface, ProgramAccount, ProgramAccountCheck, ProgramAccountType, ProgramLog, SignerAccount, TokenProgramInterface, TransferCheckedInterface, TransferHook};
+
+// 组合托管的一个金库：把全部余额转给 to，然后关闭金库，租金退还给创建者
+pub struct BundleVault<'a> {
+    pub escrow: &'a AccountView,
+    pub maker: &'a AccountView,
+    pub mint: &'a AccountView,
+    pub vault: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub hook_accounts: &'a [AccountView],
+}
+
+impl BundleVault<'_> {
+    pub fn release(&self, to: &AccountView, signers: &[Signer]) -> ProgramResult {
+        AssociatedTokenAccount::check(self.vault, self.escrow, self.mint, self.token_program)?;
+
+        let amount = {
+            let vault_data = self.vault.try_borrow()?;
+            u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
+        };
+        TransferCheckedInterface {
+            from: self.vault,
+            mint: self.mint,
+            to,
+            authority: self.escrow,
+            token_program: self.token_program,
+            extra_accounts: TransferHook::resolve(self.mint, self.hook_accounts)?,
+            amount,
+            decimals: MintInterface::decimals(self.mint)?,
+        }.invoke_signed(signers)?;
+
+        CloseAccountInterface {
+            account: self.vault,
+            destination: self.maker,
+            authority: self.escrow,
+            token_program: self.token_program,
+        }.invoke_signed(signers)
+    }
+}
+
+// BundleTake：接受者一次性支付全部 receive、取走全部 give，任何一笔失败整条指令回滚
+// 剩余账户按顺序传入：
+//   每种存入的代币 [mint, vault, taker_ata] × give_count
+//   每种要求换回的代币 [mint, taker_ata, maker_ata] × receive_count
+//   其余为 Token-2022 转账钩子需要的账户
+// 账户的 mint 必须与托管中记录的顺序一致，接受者的代币 A ATA 和创建者的代币 B ATA 按需创建（由接受者支付）
+pub struct BundleTakeAccounts<'info> {
+    pub taker: &'info AccountView,
+    pub maker: &'info AccountView,
+    pub escrow: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    pub remaining_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for BundleTakeAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [taker, maker, escrow, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(taker)?;
+
+        ProgramAccount::check::<BundleEscrow>(escrow)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        Ok(Self{
+            taker,
+            maker,
+            escrow,
+            system_program,
+            token_program,
+            remaining_accounts,
+        })
+    }
+}
+
+pub struct BundleTake<'info> {
+    pub accounts: BundleTakeAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for BundleTake<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        Ok(Self{
+            accounts: BundleTakeAccounts::try_from(accounts)?,
+        })
+    }
+}
+
+impl<'info> BundleTake<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &23;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump, give_count, give, receive_count, receive) = {
+            let data = self.accounts.escrow.try_borrow()?;
+            let escrow = BundleEscrow::load(&data)?;
+            // PDA 由创建者地址派生，验证通过即说明传入的 maker 就是托管的创建者
+            let escrow_key = Address::create_program_address(
+                &[
+                    b"bundle",
+                    self.accounts.maker.address().as_ref(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump(),
+                ],
+                &crate::ID
+            )?;
+            if &escrow_key != self.accounts.escrow.address() {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+            (escrow.seed(), escrow.bump(), escrow.give_count(), escrow.give_legs(), escrow.receive_count(), escrow.receive_legs())
+        };
+
+        let (give_legs, rest) = self.accounts.remaining_accounts
+            .split_at_checked(give_count * 3)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let (receive_legs, hook_accounts) = rest
+            .split_at_checked(receive_count * 3)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+        // 代币 B 一侧：接受者 -> 创建者
+        for (leg, (expected_mint, amount)) in receive_legs.chunks_exact(3).zip(receive) {
+            let [mint, taker_ata, maker_ata] = leg else {
+                unreachable!()
+            };
+            if &expected_mint != mint.address() {
+                return Err(EscrowError::InvalidAddress.into());
+            }
+            AssociatedTokenAccount::check(taker_ata, self.accounts.taker, mint, self.accounts.token_program)?;
+            AssociatedTokenAccount::init_if_needed(
+                maker_ata,
+                mint,
+                self.accounts.taker,
+                self.accounts.maker,
+                self.accounts.system_program,
+                self.accounts.token_program,
+            )?;
+            TransferCheckedInterface {
+                from: taker_ata,
+                mint,
+                to: maker_ata,
+                authority: self.accounts.taker,
+                token_program: self.accounts.token_program,
+                extra_accounts: TransferHook::resolve(mint, hook_accounts)?,
+                amount,
+                decimals: MintInterface::decimals(mint)?,
+            }.invoke()?;
+        }
+
+        // 代币 A 一侧：金库 -> 接受者
+        let seed_binding = seed.to_le_bytes();
+        let bump_binding = bump;
+        let escrow_seeds = [
+            Seed::from(b"bundle"),
+            Seed::from(self.accounts.maker.address().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+        let signers = [Signer::from(&escrow_seeds)];
+
+        for (leg, (expected_mint, _)) in give_legs.chunks_exact(3).zip(give) {
+            let [mint, vault, taker_ata] = leg else {
+                unreachable!()
+            };
+            if &expected_mint != mint.address() {
+                return Err(EscrowError::InvalidAddress.into());
+            }
+            AssociatedTokenAccount::init_if_needed(
+                taker_ata,
+                mint,
+                self.accounts.taker,
+                self.accounts.taker,
+                self.accounts.system_program,
+                self.accounts.token_program,
+            )?;
+            BundleVault {
+                escrow: self.accounts.escrow,
+                maker: self.accounts.maker,
+                mint,
+                vault,
+                token_program: self.accounts.token_program,
+                hook_accounts,
+            }.release(taker_ata, &signers)?;
+        }
+
+        // 日志：["bundle_take", 创建者, seed]
+        ProgramLog::data(&[
+            b"bundle_take",
+            self.accounts.maker.address().as_ref(),
+            &seed_binding,
+        ]);
+
+        ProgramAccount::close(
+            self.accounts.escrow,
+            self.accounts.maker
+        )
+    }
+}
@@ -0,0 +1,29 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::error::ProgramError;
+use crate::{ArbiterEscrow, ArbiterSettleAccounts};
+
+// Cancel：把代币退还给买方
+// 卖方放弃订单时调用；发起争议后仲裁人也可以调用
+// 账户顺序与 Release 相同，recipient_ata 传入买方的 ATA
+pub struct Cancel<'info> {
+    pub accounts: ArbiterSettleAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for Cancel<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        Ok(Self{
+            accounts: ArbiterSettleAccounts::try_from(accounts)?,
+        })
+    }
+}
+
+impl<'info> Cancel<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &13;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump) = self.accounts.verify(ArbiterEscrow::can_cancel)?;
+        self.accounts.pay_out(self.accounts.buyer, seed, bump)
+    }
+}
@@ -0,0 +1,62 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::error::ProgramError;
+use crate::{AccountCheck, ArbiterEscrow, EscrowError, ProgramAccount, ProgramAccountCheck, ProgramAccountType, SignerAccount};
+
+// Dispute：买方或卖方发起争议，之后仲裁人可以执行 Release 或 Cancel
+// 争议一旦发起不能撤销；重复发起不会报错
+pub struct DisputeAccounts<'info> {
+    // 发起者：买方或卖方
+    pub authority: &'info AccountView,
+    pub escrow: &'info AccountView,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for DisputeAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, escrow, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        ProgramAccount::check::<ArbiterEscrow>(escrow)?;
+
+        Ok(Self{
+            authority,
+            escrow,
+        })
+    }
+}
+
+pub struct Dispute<'info> {
+    pub accounts: DisputeAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for Dispute<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        Ok(Self{
+            accounts: DisputeAccounts::try_from(accounts)?,
+        })
+    }
+}
+
+impl<'info> Dispute<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &14;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut data = self.accounts.escrow.try_borrow_mut()?;
+        let escrow = ArbiterEscrow::load_mut(&mut data)?;
+
+        // 托管账户由本程序拥有（ProgramAccount::check），买方和卖方地址来自账户数据本身
+        let authority = self.accounts.authority.address();
+        if authority != escrow.buyer() && authority != escrow.seller() {
+            return Err(EscrowError::InvalidAuthority.into());
+        }
+
+        escrow.set_disputed();
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use solana_address::Address;
+use crate::{NativeSide, AccountCheck, AccountClose, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, CloseAccountInterface, Escrow, EscrowError, MarketIndex, MintInterface, NativeVault, RentRecipient, ProgramAccount, ProgramAccountCheck, ProgramAccountType, SignerAccount, TokenProgramInterface, TransferCheckedInterface, TransferHook};
+
+// Expire：过期托管的无需许可退还
+// 托管到期后任何人都可以调用，把金库中的代币 A 退还给创建者，租金退还给 rent_recipient（Make 的支付者）
+// 调用者（cranker）从托管账户的租金中获得少量 lamports 作为奖励
+// 与 Refund 的区别：创建者不需要签名，但托管必须已经过期
+pub struct ExpireAccounts<'info> {
+    // 调用者：任何人，需要签名（如有需要为创建者支付 ATA 的创建费用）
+    pub cranker: &'info AccountView,
+    pub maker: &'info AccountView,
+    pub escrow: &'info AccountView,
+    pub mint_a: &'info AccountView,
+    pub vault: &'info AccountView,
+    pub maker_ata_a: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    // 剩余账户：Token-2022 转账钩子需要的账户
+    // 可选：交易对的挂单索引（Market），托管关闭时从中移除
+    pub remaining_accounts: &'info [AccountView],
+    // 托管中记录的原生 SOL 标记
+    pub native: NativeSide,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for ExpireAccounts<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [cranker, maker, escrow, mint_a, vault, maker_ata_a, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        SignerAccount::check(cranker)?;
+
+        ProgramAccount::check::<Escrow>(escrow)?;
+
+        let native = Escrow::load(&escrow.try_borrow()?)?.native()?;
+
+        MintInterface::check(mint_a)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        // 调用者不受信任：金库必须是托管 PDA 的 ATA，防止传入其他代币账户后关闭托管
+        // 原生 SOL 的金库就是托管账户本身
+        if native != NativeSide::TokenA {
+            AssociatedTokenAccount::check(vault, escrow, mint_a, token_program)?;
+        }
+
+        Ok(Self{
+            cranker,
+            maker,
+            escrow,
+            mint_a,
+            vault,
+            maker_ata_a,
+            system_program,
+            token_program,
+            remaining_accounts,
+            native,
+        })
+    }
+}
+
+pub struct Expire<'info> {
+    pub accounts: ExpireAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for Expire<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = ExpireAccounts::try_from(accounts)?;
+        // 由调用者支付创建费用，创建者的 ATA 可能已经被关闭
+        if accounts.native != NativeSide::TokenA {
+            AssociatedTokenAccount::init_if_needed(
+                accounts.maker_ata_a,
+                accounts.mint_a,
+                accounts.cranker,
+                accounts.maker,
+                accounts.system_program,
+                accounts.token_program,
+            )?;
+        }
+        Ok(Self{
+            accounts,
+        })
+    }
+}
+
+impl<'info> Expire<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &3;
+
+    // 调用者奖励（lamports）：从托管账户的租金中扣除，剩余租金退还给 rent_recipient
+    // 相当于一笔交易的基础签名费，足够覆盖 crank 的成本
+    pub const CRANK_REWARD: u64 = 5_000;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump, mint_b, rent_recipient) = {
+            let data = self.accounts.escrow.try_borrow()?;
+            let escrow = Escrow::load(&data)?;
+            // PDA 由 maker 地址派生，验证通过即说明传入的 maker 就是托管的创建者
+            let escrow_key = Address::create_program_address(
+                &[
+                    b"escrow",
+                    self.accounts.maker.address().as_ref(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump(),
+                ],
+                &crate::ID
+            )?;
+            if &escrow_key != self.accounts.escrow.address() {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+            if escrow.mint_a() != self.accounts.mint_a.address() {
+                return Err(EscrowError::InvalidAddress.into());
+            }
+            // 只有已过期的托管才能被无需许可地退还
+            if !escrow.is_expired(Clock::get()?.unix_timestamp) {
+                return Err(EscrowError::EscrowNotExpired.into());
+            }
+            (escrow.seed(), escrow.bump(), *escrow.mint_b(), *escrow.rent_recipient())
+        };
+        let seed_binding = seed.to_le_bytes();
+        let bump_binding = bump;
+        let escrow_seeds = [
+            Seed::from(b"escrow"),
+            Seed::from(self.accounts.maker.address().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+        let signers = [Signer::from(&escrow_seeds)];
+        // 金库和托管账户的租金退还给 Make 时的支付者
+        let rent_recipient = RentRecipient::find(&rent_recipient, self.accounts.maker, self.accounts.remaining_accounts)?;
+        if self.accounts.native != NativeSide::TokenA {
+            let amount = {
+                let vault_data = self.accounts.vault.try_borrow()?;
+                u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
+            };
+            TransferCheckedInterface {
+                from: self.accounts.vault,
+                mint: self.accounts.mint_a,
+                to: self.accounts.maker_ata_a,
+                authority: self.accounts.escrow,
+                token_program: self.accounts.token_program,
+                extra_accounts: TransferHook::resolve(self.accounts.mint_a, self.accounts.remaining_accounts)?,
+                amount,
+                decimals: MintInterface::decimals(self.accounts.mint_a)?,
+            }.invoke_signed(&signers)?;
+
+            CloseAccountInterface {
+                account: self.accounts.vault,
+                destination: rent_recipient,
+                authority: self.accounts.escrow,
+                token_program: self.accounts.token_program,
+            }.invoke_signed(&signers)?;
+        } else {
+            // 原生 SOL 的存款在托管账户中：先退还给创建者，剩余的租金在关闭时退还给 rent_recipient
+            NativeVault::withdraw(self.accounts.escrow, self.accounts.maker, NativeVault::amount(self.accounts.escrow)?)?;
+        }
+
+        MarketIndex::unlist(self.accounts.remaining_accounts, self.accounts.mint_a.address(), &mint_b, self.accounts.escrow)?;
+
+        // 先把奖励从托管账户转给调用者（托管账户由本程序拥有，可以直接扣减 lamports）
+        // 奖励不超过托管账户的余额，剩余部分在关闭时退还给 rent_recipient
+        let reward = Self::CRANK_REWARD.min(self.accounts.escrow.lamports());
+        self.accounts.escrow.set_lamports(self.accounts.escrow.lamports() - reward);
+        self.accounts.cranker.set_lamports(self.accounts.cranker.lamports() + reward);
+
+        ProgramAccount::close(
+            self.accounts.escrow,
+            rent_recipient
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,303 @@
+use pinocchio::{Address, AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use crate::{AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, Ed25519Instruction, EscrowError, MintInterface, NonceBitmap, ProgramAccount, ProgramAccountCheck, ProgramAccountInit, ProgramAccountType, ProgramLog, SignerAccount, TokenProgramInterface, TransferCheckedInterface, TransferHook};
+
+// delegate PDA 的种子：["delegate", bump]
+// 创建者用 Approve 把自己代币 A ATA 的 delegate 设为这个 PDA，授权额度即所有签名订单可以转出的上限
+pub const DELEGATE_SEED: &[u8] = b"delegate";
+
+/*
+SignedOrder：创建者在链下签名的订单，也是 FillSignedOrder 的指令数据
+签名的消息就是下面 160 字节的编码，以本程序 ID 开头，防止签名被其他程序复用
+
+  program_id(32) + maker(32) + mint_a(32) + mint_b(32)
+  + amount_a(8) + amount_b(8) + expires_at(8) + nonce(8)
+
+- amount_a：创建者付出的代币 A 数量，amount_b：创建者要收到的代币 B 数量
+- expires_at：过期时间（unix 秒），0 表示永不过期
+- nonce：防重放，每个 nonce 只能成交一次（见 NonceBitmap）
+*/
+pub struct SignedOrder {
+    pub maker: Address,
+    pub mint_a: Address,
+    pub mint_b: Address,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub expires_at: i64,
+    pub nonce: u64,
+}
+
+impl SignedOrder {
+    pub const LEN: usize = 32 * 4 + 8 * 4;
+
+    // 创建者需要签名的消息
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[0..32].copy_from_slice(crate::ID.as_ref());
+        bytes[32..64].copy_from_slice(self.maker.as_ref());
+        bytes[64..96].copy_from_slice(self.mint_a.as_ref());
+        bytes[96..128].copy_from_slice(self.mint_b.as_ref());
+        bytes[128..136].copy_from_slice(&self.amount_a.to_le_bytes());
+        bytes[136..144].copy_from_slice(&self.amount_b.to_le_bytes());
+        bytes[144..152].copy_from_slice(&self.expires_at.to_le_bytes());
+        bytes[152..160].copy_from_slice(&self.nonce.to_le_bytes());
+        bytes
+    }
+
+    // 是否已过期：到达 expires_at 的那一秒起不能成交
+    #[inline(always)]
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+}
+
+impl<'info> TryFrom<&'info [u8]> for SignedOrder {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        if &data[0..32] != crate::ID.as_ref() {
+            return Err(EscrowError::InvalidSignature.into());
+        }
+
+        let order = Self{
+            maker: Address::new_from_array(data[32..64].try_into().unwrap()),
+            mint_a: Address::new_from_array(data[64..96].try_into().unwrap()),
+            mint_b: Address::new_from_array(data[96..128].try_into().unwrap()),
+            amount_a: u64::from_le_bytes(data[128..136].try_into().unwrap()),
+            amount_b: u64::from_le_bytes(data[136..144].try_into().unwrap()),
+            expires_at: i64::from_le_bytes(data[144..152].try_into().unwrap()),
+            nonce: u64::from_le_bytes(data[152..160].try_into().unwrap()),
+        };
+        if order.amount_a == 0 || order.amount_b == 0 {
+            return Err(EscrowError::InvalidAmount.into());
+        }
+
+        Ok(order)
+    }
+}
+
+// FillSignedOrder：接受者提交创建者的链下签名订单，直接成交，创建者不需要发送 Make 交易
+// 交易中紧挨在本指令之前必须有一条 Ed25519 预编译指令，验证创建者对 SignedOrder 的签名
+// 代币 A 由 delegate PDA 从创建者的 ATA 直接转给接受者，不经过金库
+pub struct FillSignedOrderAccounts<'info> {
+    pub taker: &'info AccountView,
+
+    // 创建者：不需要签名，身份由 Ed25519 签名证明
+    pub maker: &'info AccountView,
+
+    // delegate PDA：创建者代币 A ATA 的授权转账人
+    pub delegate: &'info AccountView,
+
+    // 防重放位图：该页第一次成交时由接受者创建
+    pub nonces: &'info AccountView,
+
+    pub mint_a: &'info AccountView,
+
+    pub mint_b: &'info AccountView,
+
+    pub maker_ata_a: &'info AccountView,
+
+    pub maker_ata_b: &'info AccountView,
+
+    pub taker_ata_a: &'info AccountView,
+
+    pub taker_ata_b: &'info AccountView,
+
+    // Instructions sysvar：用于读取前一条 Ed25519 预编译指令
+    pub instructions: &'info AccountView,
+
+    pub system_program: &'info AccountView,
+
+    pub token_program: &'info AccountView,
+
+    // 剩余账户：Token-2022 转账钩子需要的账户（mint_a 与 mint_b 的钩子账户都放在这里）
+    pub remaining_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for FillSignedOrderAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [taker, maker, delegate, nonces, mint_a, mint_b, maker_ata_a, maker_ata_b, taker_ata_a, taker_ata_b, instructions, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(taker)?;
+
+        MintInterface::check(mint_a)?;
+
+        MintInterface::check(mint_b)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        AssociatedTokenAccount::check(maker_ata_a, maker, mint_a, token_program)?;
+
+        AssociatedTokenAccount::check(taker_ata_b, taker, mint_b, token_program)?;
+
+        Ok(Self{
+            taker,
+            maker,
+            delegate,
+            nonces,
+            mint_a,
+            mint_b,
+            maker_ata_a,
+            maker_ata_b,
+            taker_ata_a,
+            taker_ata_b,
+            instructions,
+            system_program,
+            token_program,
+            remaining_accounts,
+        })
+    }
+}
+
+pub struct FillSignedOrder<'info> {
+    pub accounts: FillSignedOrderAccounts<'info>,
+    pub instruction_data: SignedOrder,
+    pub delegate_bump: u8,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for FillSignedOrder<'info> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = FillSignedOrderAccounts::try_from(accounts)?;
+        let instruction_data = SignedOrder::try_from(data)?;
+
+        if &instruction_data.maker != accounts.maker.address()
+            || &instruction_data.mint_a != accounts.mint_a.address()
+            || &instruction_data.mint_b != accounts.mint_b.address()
+        {
+            return Err(EscrowError::InvalidAddress.into());
+        }
+
+        Ed25519Instruction::verify_preceding(
+            accounts.instructions,
+            accounts.maker.address(),
+            &instruction_data.to_bytes(),
+        )?;
+
+        if instruction_data.is_expired(Clock::get()?.unix_timestamp) {
+            return Err(EscrowError::EscrowExpired.into());
+        }
+
+        let (delegate, delegate_bump) = Address::find_program_address(&[DELEGATE_SEED], &crate::ID);
+        if &delegate != accounts.delegate.address() {
+            return Err(EscrowError::InvalidAddress.into());
+        }
+
+        // nonce 所在页的位图：不存在则由接受者创建
+        let page = NonceBitmap::page_of(instruction_data.nonce);
+        let (nonces, nonces_bump) = Address::find_program_address(
+            &[
+                b"nonces",
+                accounts.maker.address().as_ref(),
+                &page.to_le_bytes(),
+            ],
+            &crate::ID,
+        );
+        if &nonces != accounts.nonces.address() {
+            return Err(EscrowError::InvalidAddress.into());
+        }
+        if accounts.nonces.owned_by(&crate::ID) {
+            ProgramAccount::check::<NonceBitmap>(accounts.nonces)?;
+        } else {
+            let page_binding = page.to_le_bytes();
+            let bump_binding = [nonces_bump];
+            let nonces_seeds = [
+                Seed::from(b"nonces"),
+                Seed::from(accounts.maker.address().as_ref()),
+                Seed::from(&page_binding),
+                Seed::from(&bump_binding),
+            ];
+            ProgramAccount::init::<NonceBitmap>(
+                accounts.taker,
+                accounts.nonces,
+                &nonces_seeds,
+            )?.set_inner(*accounts.maker.address(), page, bump_binding);
+        }
+
+        // 接受者的代币 A ATA 和创建者的代币 B ATA 可能还不存在，由接受者支付创建费用
+        AssociatedTokenAccount::init_if_needed(
+            accounts.taker_ata_a,
+            accounts.mint_a,
+            accounts.taker,
+            accounts.taker,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.maker_ata_b,
+            accounts.mint_b,
+            accounts.taker,
+            accounts.maker,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self{
+            accounts,
+            instruction_data,
+            delegate_bump,
+        })
+    }
+}
+
+impl<'info> FillSignedOrder<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &18;
+
+    pub fn process(&mut self) -> ProgramResult {
+        NonceBitmap::load_mut(&mut self.accounts.nonces.try_borrow_mut()?)?
+            .use_nonce(self.instruction_data.nonce)?;
+
+        // 代币 B：接受者 -> 创建者
+        TransferCheckedInterface{
+            from: self.accounts.taker_ata_b,
+            mint: self.accounts.mint_b,
+            to: self.accounts.maker_ata_b,
+            authority: self.accounts.taker,
+            token_program: self.accounts.token_program,
+            extra_accounts: TransferHook::resolve(self.accounts.mint_b, self.accounts.remaining_accounts)?,
+            amount: self.instruction_data.amount_b,
+            decimals: MintInterface::decimals(self.accounts.mint_b)?,
+        }.invoke()?;
+
+        // 代币 A：创建者 -> 接受者，由 delegate PDA 签名
+        // 授权额度不足或已被撤销时 Token Program 会拒绝转账
+        let bump_binding = [self.delegate_bump];
+        let delegate_seeds = [
+            Seed::from(DELEGATE_SEED),
+            Seed::from(&bump_binding),
+        ];
+        let signers = [Signer::from(&delegate_seeds)];
+
+        TransferCheckedInterface{
+            from: self.accounts.maker_ata_a,
+            mint: self.accounts.mint_a,
+            to: self.accounts.taker_ata_a,
+            authority: self.accounts.delegate,
+            token_program: self.accounts.token_program,
+            extra_accounts: TransferHook::resolve(self.accounts.mint_a, self.accounts.remaining_accounts)?,
+            amount: self.instruction_data.amount_a,
+            decimals: MintInterface::decimals(self.accounts.mint_a)?,
+        }.invoke_signed(&signers)?;
+
+        // 日志：["fill_signed_order", 创建者, nonce]
+        ProgramLog::data(&[
+            b"fill_signed_order",
+            self.accounts.maker.address().as_ref(),
+            &self.instruction_data.nonce.to_le_bytes(),
+        ]);
+
+        Ok(())
+    }
+}
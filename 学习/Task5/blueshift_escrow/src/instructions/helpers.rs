@@ -11,13 +11,17 @@
 // 本模块通过 Trait 和零大小类型（ZST）实现类型安全的账户验证
 
 use pinocchio::{AccountView, Address, ProgramResult};
-use pinocchio::cpi::{Seed, Signer};
+use pinocchio::account::RefMut;
+use pinocchio::cpi::{invoke_signed, invoke_signed_with_bounds, Seed, Signer};
 use pinocchio::error::ProgramError;
+use pinocchio::instruction::{InstructionAccount, InstructionView};
+use pinocchio::sysvars::instructions::Instructions;
 use pinocchio::sysvars::rent::Rent;
 use pinocchio::sysvars::Sysvar;
 use pinocchio_associated_token_account::instructions::Create;
-use pinocchio_system::instructions::CreateAccount;
+use pinocchio_system::instructions::{CreateAccount, Transfer};
 use crate::errors::EscrowError;
+use crate::state::{AccountDiscriminator, ProgramAccountType};
 
 // =============================================================================
 // AccountCheck Trait - 基础账户验证
@@ -327,6 +331,288 @@ impl AccountCheck for MintInterface {
     }
 }
 
+// Mint 账户中 decimals 字段的偏移量
+// mint_authority (COption<Address>, 36) + supply (u64, 8) = 44
+// Token Program 和 Token-2022 的 Mint 基础布局相同
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+impl MintInterface {
+    // 读取 Mint 的精度（decimals）
+    // TransferChecked 指令需要传入 decimals，由 Token Program 校验是否与 Mint 一致
+    pub fn decimals(account: &AccountView) -> Result<u8, ProgramError> {
+        let data = account.try_borrow()?;
+
+        data.get(MINT_DECIMALS_OFFSET)
+            .copied()
+            .ok_or(EscrowError::InvalidAccountData.into())
+    }
+}
+
+// =============================================================================
+// TokenProgramInterface - Token Program 验证
+// =============================================================================
+// 对应 Anchor 的约束：Interface<'info, TokenInterface>
+//
+// Anchor 版本：
+//   pub token_program: Interface<'info, TokenInterface>,
+//
+// 功能：
+// - 验证传入的 token_program 是 Token Program 或 Token-2022 Program
+//
+// 为什么需要？
+// - CPI 的目标程序来自 token_program 账户，并且 escrow PDA 会为其签名
+// - 如果不校验，调用者可以传入任意程序并借用 PDA 的签名
+pub struct TokenProgramInterface;
+
+impl AccountCheck for TokenProgramInterface {
+    fn check(account: &AccountView) -> Result<(), ProgramError> {
+        if account.address().ne(&pinocchio_token::ID)
+            && account.address().ne(&TOKEN_2022_PROGRAM_ID)
+        {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        Ok(())
+    }
+}
+
+// =============================================================================
+// TransferHook - Token-2022 转账钩子（Transfer Hook）解析
+// =============================================================================
+// Token-2022 的 TransferHook 扩展会在每次 TransferChecked 时 CPI 调用钩子程序
+// 钩子程序需要的额外账户记录在 extra-account-metas PDA 中：
+//   PDA = find_program_address(["extra-account-metas", mint], hook_program_id)
+//
+// 对应 Anchor 的做法：
+// - 通过 ctx.remaining_accounts 传入钩子程序、extra-account-metas PDA 及其列出的账户
+// - CPI 时使用 with_remaining_accounts 转发
+//
+// 本程序的约定：
+// - 钩子账户放在指令的固定账户之后（remaining accounts），每个带钩子的 Mint 连续排列：
+//   [钩子程序, extra-account-metas PDA, 该 PDA 中列出的账户...]
+// - 只转发上述连续的一段，剩余账户中的其他账户（租金退还对象、Instructions sysvar、
+//   另一个 Mint 的钩子账户等）不计入单次转账的账户上限
+
+// Token-2022 Mint 的扩展（TLV）数据起始偏移量：紧跟在账户类型字节之后
+const TOKEN_2022_EXTENSIONS_OFFSET: usize = TOKEN_2022_ACCOUNT_DISCRIMINATOR_OFFSET + 1;
+
+// TLV 头部长度：type (u16) + length (u16)
+const TOKEN_2022_EXTENSION_HEADER_LEN: usize = 4;
+
+// ExtensionType::Uninitialized，用于填充，遇到即表示没有更多扩展
+const EXTENSION_TYPE_UNINITIALIZED: u16 = 0;
+
+// ExtensionType::TransferHook
+const EXTENSION_TYPE_TRANSFER_HOOK: u16 = 14;
+
+// extra-account-metas PDA 的种子前缀（与 spl-transfer-hook-interface 一致）
+pub const EXTRA_ACCOUNT_METAS_SEED: &[u8] = b"extra-account-metas";
+
+// 单次转账最多转发的钩子账户数量
+pub const MAX_TRANSFER_HOOK_ACCOUNTS: usize = 16;
+
+// ExtraAccountMetaList 中记录数量的偏移量：TLV 类型（8 字节 discriminator）+ 长度（u32）之后
+const EXTRA_ACCOUNT_METAS_COUNT_OFFSET: usize = 12;
+
+pub struct TransferHook;
+
+impl TransferHook {
+    // 读取 Mint 的 TransferHook 扩展，返回钩子程序 ID
+    // 旧版 Token Program 的 Mint、没有该扩展或 program_id 为空时返回 None
+    pub fn program_id(mint: &AccountView) -> Result<Option<Address>, ProgramError> {
+        if !mint.owned_by(&TOKEN_2022_PROGRAM_ID) {
+            return Ok(None);
+        }
+
+        let data = mint.try_borrow()?;
+        let mut offset = TOKEN_2022_EXTENSIONS_OFFSET;
+
+        while offset + TOKEN_2022_EXTENSION_HEADER_LEN <= data.len() {
+            let extension_type = u16::from_le_bytes([data[offset], data[offset + 1]]);
+            let extension_len = u16::from_le_bytes([data[offset + 2], data[offset + 3]]) as usize;
+            let start = offset + TOKEN_2022_EXTENSION_HEADER_LEN;
+            let end = start + extension_len;
+
+            if extension_type == EXTENSION_TYPE_UNINITIALIZED {
+                break;
+            }
+            if end > data.len() {
+                return Err(EscrowError::InvalidAccountData.into());
+            }
+
+            if extension_type == EXTENSION_TYPE_TRANSFER_HOOK {
+                // TransferHook 扩展布局：authority (32) + program_id (32)
+                if extension_len < 64 {
+                    return Err(EscrowError::InvalidAccountData.into());
+                }
+                let program_id: [u8; 32] = data[start + 32..start + 64].try_into().unwrap();
+                if program_id == [0u8; 32] {
+                    return Ok(None);
+                }
+                return Ok(Some(Address::new_from_array(program_id)));
+            }
+
+            offset = end;
+        }
+
+        Ok(None)
+    }
+
+    // 返回需要转发给 TransferChecked 的钩子账户
+    // - Mint 没有转账钩子：返回空切片
+    // - Mint 有转账钩子：在 remaining accounts 中找到钩子程序，要求紧随其后的是 extra-account-metas PDA，
+    //   返回 [钩子程序, extra-account-metas PDA, 列出的账户...] 这一段
+    pub fn resolve<'a>(
+        mint: &AccountView,
+        remaining_accounts: &'a [AccountView],
+    ) -> Result<&'a [AccountView], ProgramError> {
+        let Some(program_id) = Self::program_id(mint)? else {
+            return Ok(&[]);
+        };
+
+        let (extra_account_metas, _) = Address::find_program_address(
+            &[EXTRA_ACCOUNT_METAS_SEED, mint.address().as_ref()],
+            &program_id,
+        );
+
+        let hook_accounts = remaining_accounts
+            .iter()
+            .position(|account| account.address().eq(&program_id))
+            .map(|start| &remaining_accounts[start..])
+            .ok_or(EscrowError::InvalidTransferHookAccounts)?;
+        let [_, metas, ..] = hook_accounts else {
+            return Err(EscrowError::InvalidTransferHookAccounts.into());
+        };
+        if metas.address().ne(&extra_account_metas) || !metas.owned_by(&program_id) {
+            return Err(EscrowError::InvalidTransferHookAccounts.into());
+        }
+
+        let count = {
+            let data = metas.try_borrow()?;
+            let count = data
+                .get(EXTRA_ACCOUNT_METAS_COUNT_OFFSET..EXTRA_ACCOUNT_METAS_COUNT_OFFSET + 4)
+                .ok_or(EscrowError::InvalidTransferHookAccounts)?;
+            u32::from_le_bytes(count.try_into().unwrap()) as usize
+        };
+
+        hook_accounts
+            .get(..2 + count)
+            .ok_or(EscrowError::InvalidTransferHookAccounts.into())
+    }
+}
+
+// =============================================================================
+// TransferCheckedInterface - 支持 Token-2022 的 TransferChecked CPI
+// =============================================================================
+// 对应 Anchor 的 token_interface::transfer_checked + with_remaining_accounts
+//
+// 与 pinocchio_token::instructions::TransferChecked 的区别：
+// - pinocchio_token 固定调用旧版 Token Program
+// - 这里调用传入的 token_program，并在固定的 4 个账户之后转发钩子账户
+pub struct TransferCheckedInterface<'a> {
+    pub from: &'a AccountView,
+    pub mint: &'a AccountView,
+    pub to: &'a AccountView,
+    pub authority: &'a AccountView,
+    pub token_program: &'a AccountView,
+    // TransferHook::resolve 返回的钩子账户
+    pub extra_accounts: &'a [AccountView],
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+impl TransferCheckedInterface<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        const MAX_ACCOUNTS: usize = 4 + MAX_TRANSFER_HOOK_ACCOUNTS;
+
+        if self.extra_accounts.len() > MAX_TRANSFER_HOOK_ACCOUNTS {
+            return Err(EscrowError::InvalidTransferHookAccounts.into());
+        }
+        let len = 4 + self.extra_accounts.len();
+
+        // 账户顺序：[from, mint, to, authority, 钩子账户...]
+        // 未使用的位置用 from 填充，只取前 len 个
+        let fixed = [self.from, self.mint, self.to, self.authority];
+        let account_views: [&AccountView; MAX_ACCOUNTS] = core::array::from_fn(|i| {
+            if i < 4 {
+                fixed[i]
+            } else {
+                self.extra_accounts.get(i - 4).unwrap_or(self.from)
+            }
+        });
+        let instruction_accounts: [InstructionAccount; MAX_ACCOUNTS] =
+            core::array::from_fn(|i| match i {
+                0 => InstructionAccount::writable(self.from.address()),
+                1 => InstructionAccount::readonly(self.mint.address()),
+                2 => InstructionAccount::writable(self.to.address()),
+                3 => InstructionAccount::readonly_signer(self.authority.address()),
+                _ => InstructionAccount::new(
+                    account_views[i].address(),
+                    account_views[i].is_writable(),
+                    account_views[i].is_signer(),
+                ),
+            });
+
+        // 指令数据：[12 (TransferChecked)] + amount (u64) + decimals (u8)
+        let mut instruction_data = [0u8; 10];
+        instruction_data[0] = 12;
+        instruction_data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        instruction_data[9] = self.decimals;
+
+        let instruction = InstructionView {
+            program_id: self.token_program.address(),
+            accounts: &instruction_accounts[..len],
+            data: &instruction_data,
+        };
+
+        invoke_signed_with_bounds::<MAX_ACCOUNTS>(&instruction, &account_views[..len], signers)
+    }
+}
+
+// =============================================================================
+// CloseAccountInterface - 支持 Token-2022 的 CloseAccount CPI
+// =============================================================================
+// 对应 Anchor 的 token_interface::close_account
+pub struct CloseAccountInterface<'a> {
+    pub account: &'a AccountView,
+    pub destination: &'a AccountView,
+    pub authority: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl CloseAccountInterface<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable(self.account.address()),
+            InstructionAccount::writable(self.destination.address()),
+            InstructionAccount::readonly_signer(self.authority.address()),
+        ];
+
+        // 指令数据：[9 (CloseAccount)]
+        let instruction = InstructionView {
+            program_id: self.token_program.address(),
+            accounts: &instruction_accounts,
+            data: &[9],
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.account, self.destination, self.authority],
+            signers,
+        )
+    }
+}
+
 // =============================================================================
 // TokenAccountInterface - Token Account 验证
 // =============================================================================
@@ -493,7 +779,7 @@ impl AssociatedTokenAccountInit for AssociatedTokenAccount {
 // =============================================================================
 // ProgramAccount - 程序自定义账户验证
 // =============================================================================
-// 对应 Anchor 的约束：Account<'info, Escrow>
+// 对应 Anchor 的约束：Account<'info, T>
 //
 // Anchor 版本：
 //   #[account(
@@ -505,22 +791,33 @@ impl AssociatedTokenAccountInit for AssociatedTokenAccount {
 //
 // 功能：
 // - 验证账户由本程序拥有（owner == program_id）
-// - 验证账户数据长度正确
+// - 验证账户数据长度等于 T::LEN
+// - 验证账户类型判别器为 T::DISCRIMINATOR（对应 Anchor 的 8 字节判别器检查）
 //
 // 注意：
 // - PDA 验证（seeds、bump）需要在指令中单独进行
+// - 账户类型由 ProgramAccountType 描述，新增状态类型不需要修改这里
 pub struct ProgramAccount;
 
-impl AccountCheck for ProgramAccount {
-    fn check(account: &AccountView) -> Result<(), ProgramError> {
+pub trait ProgramAccountCheck {
+    fn check<T: ProgramAccountType>(account: &AccountView) -> Result<(), ProgramError>;
+}
+
+impl ProgramAccountCheck for ProgramAccount {
+    fn check<T: ProgramAccountType>(account: &AccountView) -> Result<(), ProgramError> {
         // 验证账户由本程序拥有
         // 对应 Anchor 的 Account<T> 自动进行的 owner 检查
         if !account.owned_by(&crate::ID) {
             return Err(EscrowError::InvalidOwner.into());
         }
 
-        // 验证账户数据长度是否匹配 Escrow 结构体
-        if account.data_len().ne(&crate::state::Escrow::LEN) {
+        // 验证账户数据长度是否匹配账户类型
+        if account.data_len().ne(&T::LEN) {
+            return Err(EscrowError::InvalidAccountData.into());
+        }
+
+        // 验证账户类型判别器，防止其他类型或已关闭的账户被误用
+        if account.try_borrow()?[0].ne(&(T::DISCRIMINATOR as u8)) {
             return Err(EscrowError::InvalidAccountData.into());
         }
 
@@ -548,25 +845,31 @@ impl AccountCheck for ProgramAccount {
 //   )]
 //   pub escrow: Account<'info, Escrow>,
 pub trait ProgramAccountInit {
-    // 创建程序拥有的 PDA 账户
-    fn init<'a, T: Sized>(
+    // 创建程序拥有的 PDA 账户，写入类型判别器并返回类型化的可变引用
+    // 空间（对应 space = xxx）和租金由 T::LEN 计算
+    fn init<'a, T: ProgramAccountType>(
         payer: &AccountView,      // 支付者（对应 payer = xxx）
-        account: &AccountView,    // 要创建的账户
-        seeds: &[Seed<'a>],       // PDA 种子（对应 seeds = [...]）
-        space: usize,             // 账户大小（对应 space = xxx）
-    ) -> ProgramResult;
+        account: &'a AccountView, // 要创建的账户
+        seeds: &[Seed],           // PDA 种子（对应 seeds = [...]），必须以 T::SEEDS 开头
+    ) -> Result<RefMut<'a, T>, ProgramError>;
 }
 
 impl ProgramAccountInit for ProgramAccount {
-    fn init<'a, T: Sized>(
+    fn init<'a, T: ProgramAccountType>(
         payer: &AccountView,
-        account: &AccountView,
-        seeds: &[Seed<'a>],
-        space: usize,
-    ) -> ProgramResult {
+        account: &'a AccountView,
+        seeds: &[Seed],
+    ) -> Result<RefMut<'a, T>, ProgramError> {
+        // 种子必须以账户类型声明的固定前缀开头，防止用错误的种子创建账户
+        if seeds.len() < T::SEEDS.len()
+            || seeds.iter().zip(T::SEEDS).any(|(seed, prefix)| &seed[..] != *prefix)
+        {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
         // 获取租金豁免所需的 lamports 数量
         // 对应 Anchor 自动进行的租金计算
-        let lamports = Rent::get()?.try_minimum_balance(space)?;
+        let lamports = Rent::get()?.try_minimum_balance(T::LEN)?;
 
         // 使用种子创建 PDA 签名者
         // 对应 Anchor 的 bump 自动处理
@@ -578,12 +881,14 @@ impl ProgramAccountInit for ProgramAccount {
             from: payer,              // 从支付者账户扣除 lamports
             to: account,              // 要创建的账户
             lamports,                 // 转账的 lamports 数量
-            space: space as u64,      // 账户数据空间大小
+            space: T::LEN as u64,     // 账户数据空间大小
             owner: &crate::ID,        // 账户拥有者：本程序
         }
             .invoke_signed(&signer)?;  // 使用 PDA 签名调用
 
-        Ok(())
+        // 写入类型判别器，返回映射到账户数据的 T
+        // 对应 Anchor 在指令结束时自动写入的 8 字节判别器
+        RefMut::try_map(account.try_borrow_mut()?, T::init).map_err(|(_, error)| error)
     }
 }
 
@@ -614,21 +919,263 @@ pub trait AccountClose {
 impl AccountClose for ProgramAccount {
     fn close(account: &AccountView, destination: &AccountView) -> ProgramResult {
         {
-            // 将账户数据的第一个字节设置为 0xff
-            // 这是 Solana 的惯例，表示账户已关闭
+            // 将账户类型判别器（第一个字节）设置为 Closed（0xff）
+            // 这是 Solana 的惯例，表示账户已关闭，之后任何 load 都会失败
             let mut data = account.try_borrow_mut()?;
-            data[0] = 0xff;
+            data[0] = AccountDiscriminator::Closed as u8;
         }
 
         // 将账户的 lamports 转给目标账户
         // 对应 Anchor 的 close = destination 约束
         destination.set_lamports(destination.lamports()+account.lamports());
 
-        // 将账户大小缩减到 1 字节（只剩下 0xff 标记）
+        // 将账户大小缩减到 1 字节（只剩下 Closed 标记）
         account.resize(1)?;
 
         // 关闭账户
         // 此时账户的 lamports 已被转移，数据被清零
         account.close()
     }
-}
\ No newline at end of file
+}
+// =============================================================================
+// MerkleProof - 接受者白名单验证
+// =============================================================================
+// 托管中只保存 32 字节的 Merkle 根，接受者在指令数据中提供证明
+// 一个报价可以面向成千上万个地址，而不需要把名单放到链上
+//
+// 哈希规则（sha256，通过 solana-address 的 Address::create_with_seed 计算，链上使用 sol_sha256 系统调用）：
+// - 叶子：sha256(taker || "leaf" || program_id)，白名单的根只对本程序有效
+// - 节点：sha256(min(left, right) || "node" || max(left, right))
+//   子节点按字节序排序后再拼接，证明中不需要记录左右方向
+//   叶子与节点使用不同的分隔字符串，防止把内部节点伪装成叶子
+//   create_with_seed 拒绝以 PDA 标记结尾的最后一段（概率可以忽略），此时返回全 0 哈希，证明必然验证失败
+// 证明格式：依次拼接的兄弟节点哈希，每个 32 字节，从叶子层到根
+pub struct MerkleProof;
+
+// 最大证明深度：2^32 个地址，远超实际需要，只用于限制计算量
+pub const MAX_MERKLE_PROOF_DEPTH: usize = 32;
+
+const MERKLE_LEAF_SEPARATOR: &str = "leaf";
+const MERKLE_NODE_SEPARATOR: &str = "node";
+
+impl MerkleProof {
+    // 计算地址对应的叶子哈希
+    pub fn leaf(address: &Address) -> [u8; 32] {
+        Address::create_with_seed(address, MERKLE_LEAF_SEPARATOR, &crate::ID)
+            .map_or([0; 32], |hash| hash.to_bytes())
+    }
+
+    // 计算两个子节点的父节点哈希（与顺序无关）
+    pub fn node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        let (left, right) = if a <= b { (a, b) } else { (b, a) };
+        Address::create_with_seed(&Address::new_from_array(*left), MERKLE_NODE_SEPARATOR, &Address::new_from_array(*right))
+            .map_or([0; 32], |hash| hash.to_bytes())
+    }
+
+    // 验证 address 是否在以 root 为根的白名单中
+    // proof 长度必须是 32 的整数倍且深度不超过 MAX_MERKLE_PROOF_DEPTH
+    pub fn verify(root: &[u8; 32], address: &Address, proof: &[u8]) -> Result<(), ProgramError> {
+        let siblings = proof.chunks_exact(32);
+        if !siblings.remainder().is_empty() || siblings.len() > MAX_MERKLE_PROOF_DEPTH {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let computed = siblings
+            .fold(Self::leaf(address), |hash, sibling| {
+                Self::node(&hash, sibling.try_into().unwrap())
+            });
+
+        if &computed != root {
+            return Err(EscrowError::TakerNotAllowlisted.into());
+        }
+
+        Ok(())
+    }
+}
+
+// =============================================================================
+// sha256 - 标准 sha256 哈希
+// =============================================================================
+// HTLC 的 hashlock 必须是标准的 sha256(preimage)，才能与其他链上的锁使用同一个值，
+// 因此不能像 MerkleProof 那样借用 derive_address（它会在末尾追加程序 ID 和 PDA 标记）
+// 链上直接调用 sol_sha256 系统调用（pinocchio::syscalls 再导出），不需要额外的哈希依赖
+// 主机端没有这个系统调用，程序的主机端构建只用于 cargo test，调用时 panic
+pub fn sha256(vals: &[&[u8]]) -> [u8; 32] {
+    #[cfg(any(target_os = "solana", target_arch = "bpf"))]
+    {
+        let mut hash = [0u8; 32];
+        // 安全性：&[u8] 的内存布局就是系统调用要求的 (指针, 长度)，sol_sha256 总是写满 32 字节
+        unsafe {
+            pinocchio::syscalls::sol_sha256(vals.as_ptr() as *const u8, vals.len() as u64, hash.as_mut_ptr());
+        }
+        hash
+    }
+    #[cfg(not(any(target_os = "solana", target_arch = "bpf")))]
+    {
+        let _ = vals;
+        panic!("sha256 is only available on target solana");
+    }
+}
+
+// =============================================================================
+// NativeVault - 原生 SOL 金库
+// =============================================================================
+// 代币 A 为原生 SOL（NativeSide::TokenA）时，托管 PDA 本身就是金库：
+// 存入的 lamports 直接保存在托管账户中，超出租金豁免的部分就是金库余额
+//
+// 托管账户由本程序拥有，取出时直接修改 lamports，不需要 CPI 和 PDA 签名
+// 取出后托管账户仍然保持租金豁免，关闭时剩余的 lamports 退还给 rent_recipient（见 RentRecipient）
+pub struct NativeVault;
+
+impl NativeVault {
+    // 金库余额：托管账户 lamports 减去租金豁免所需的最低余额
+    pub fn amount(escrow: &AccountView) -> Result<u64, ProgramError> {
+        let rent_exempt = Rent::get()?.try_minimum_balance(escrow.data_len())?;
+        Ok(escrow.lamports().saturating_sub(rent_exempt))
+    }
+
+    // 存入：通过 System Program 从签名者向托管账户转账
+    pub fn deposit(from: &AccountView, escrow: &AccountView, amount: u64) -> ProgramResult {
+        Transfer {
+            from,
+            to: escrow,
+            lamports: amount,
+        }.invoke()
+    }
+
+    // 取出：从托管账户直接扣减 lamports，不能动用租金豁免的部分
+    pub fn withdraw(escrow: &AccountView, to: &AccountView, amount: u64) -> ProgramResult {
+        if amount > Self::amount(escrow)? {
+            return Err(ProgramError::InsufficientFunds);
+        }
+        escrow.set_lamports(escrow.lamports() - amount);
+        to.set_lamports(
+            to.lamports()
+                .checked_add(amount)
+                .ok_or(ProgramError::ArithmeticOverflow)?,
+        );
+        Ok(())
+    }
+}
+
+// =============================================================================
+// RentRecipient - 租金退还对象
+// =============================================================================
+// Make 可以由单独的 payer（例如为用户代付租金的应用）支付托管账户和金库 ATA 的租金，
+// 托管中记录 rent_recipient，关闭金库和托管账户时租金退还给它而不是创建者
+//
+// rent_recipient 与创建者相同时直接使用 maker 账户；
+// 不同时由调用者放在剩余账户中传入（可写），不改变各指令的固定账户列表
+pub struct RentRecipient;
+
+impl RentRecipient {
+    pub fn find<'a>(
+        rent_recipient: &Address,
+        maker: &'a AccountView,
+        remaining_accounts: &'a [AccountView],
+    ) -> Result<&'a AccountView, ProgramError> {
+        if rent_recipient == maker.address() {
+            return Ok(maker);
+        }
+        remaining_accounts
+            .iter()
+            .find(|account| account.address() == rent_recipient)
+            .ok_or(EscrowError::InvalidAddress.into())
+    }
+}
+
+// =============================================================================
+// ProgramLog - 结构化日志
+// =============================================================================
+// 对应 Anchor 的 emit!：通过 sol_log_data 系统调用写入 "Program data: ..." 日志
+// 每个字段单独做 base64 编码，链下索引器可以直接从交易日志中解析
+pub struct ProgramLog;
+
+impl ProgramLog {
+    pub fn data(fields: &[&[u8]]) {
+        // sol_log_data 接收的是切片数组（每个切片是指针 + 长度），与 &[&[u8]] 的内存布局一致
+        #[cfg(target_os = "solana")]
+        unsafe {
+            pinocchio::syscalls::sol_log_data(fields.as_ptr() as *const u8, fields.len() as u64);
+        }
+
+        #[cfg(not(target_os = "solana"))]
+        let _ = fields;
+    }
+}
+
+// =============================================================================
+// Ed25519Instruction - 通过 Instructions sysvar 检查 Ed25519 预编译指令
+// =============================================================================
+// 链上程序无法直接验证 Ed25519 签名，做法是让交易中同时包含一条 Ed25519 预编译指令：
+// 运行时在执行任何指令之前验证所有预编译指令，签名无效时整笔交易失败
+// 程序只需要通过 Instructions sysvar 找到这条指令，确认它验证的是期望的公钥和消息
+//
+// 预编译指令数据布局（单个签名）：
+//   num_signatures (u8) + padding (u8)
+//   offsets：signature_offset, signature_instruction_index, public_key_offset,
+//            public_key_instruction_index, message_data_offset, message_data_size,
+//            message_instruction_index（7 个小端 u16）
+//   之后是公钥、签名、消息，位置由 offsets 指定
+//
+// 三个 instruction_index 都必须为 u16::MAX（数据就在预编译指令自身），
+// 否则预编译验证的可能是其他指令中的数据，这里读到的公钥和消息就不可信
+pub struct Ed25519Instruction;
+
+// Ed25519SigVerify111111111111111111111111111
+pub const ED25519_PROGRAM_ID: Address = Address::new_from_array([
+    0x03, 0x7d, 0x46, 0xd6, 0x7c, 0x93, 0xfb, 0xbe, 0x12, 0xf9, 0x42, 0x8f, 0x83, 0x8d, 0x40, 0xff,
+    0x05, 0x70, 0x74, 0x49, 0x27, 0xf4, 0x8a, 0x64, 0xfc, 0xca, 0x70, 0x44, 0x80, 0x00, 0x00, 0x00,
+]);
+
+// offsets 的起始位置与长度
+const ED25519_OFFSETS_START: usize = 2;
+const ED25519_OFFSETS_LEN: usize = 14;
+
+// instruction_index 为 u16::MAX 表示数据在预编译指令自身
+const ED25519_SELF_INSTRUCTION: u16 = u16::MAX;
+
+impl Ed25519Instruction {
+    // 检查预编译指令数据：恰好一个签名，且签名者为 signer、消息为 message
+    pub fn verify(data: &[u8], signer: &Address, message: &[u8]) -> Result<(), ProgramError> {
+        if data.len() < ED25519_OFFSETS_START + ED25519_OFFSETS_LEN || data[0] != 1 {
+            return Err(EscrowError::InvalidSignature.into());
+        }
+
+        let field = |index: usize| {
+            let offset = ED25519_OFFSETS_START + index * 2;
+            u16::from_le_bytes([data[offset], data[offset + 1]])
+        };
+        let [_, signature_index, public_key_offset, public_key_index, message_offset, message_size, message_index] =
+            core::array::from_fn(field);
+        if signature_index != ED25519_SELF_INSTRUCTION
+            || public_key_index != ED25519_SELF_INSTRUCTION
+            || message_index != ED25519_SELF_INSTRUCTION
+        {
+            return Err(EscrowError::InvalidSignature.into());
+        }
+
+        let public_key_offset = public_key_offset as usize;
+        let message_offset = message_offset as usize;
+        let signed_key = data.get(public_key_offset..public_key_offset + 32);
+        let signed_message = data.get(message_offset..message_offset + message_size as usize);
+        if signed_key != Some(signer.as_ref()) || signed_message != Some(message) {
+            return Err(EscrowError::InvalidSignature.into());
+        }
+
+        Ok(())
+    }
+
+    // 检查当前指令的前一条指令是 Ed25519 预编译指令，并且验证的是 signer 对 message 的签名
+    pub fn verify_preceding(instructions: &AccountView, signer: &Address, message: &[u8]) -> ProgramResult {
+        let instructions = Instructions::try_from(instructions)?;
+        let instruction = instructions
+            .get_instruction_relative(-1)
+            .map_err(|_| EscrowError::InvalidSignature)?;
+        if instruction.get_program_id() != &ED25519_PROGRAM_ID {
+            return Err(EscrowError::InvalidSignature.into());
+        }
+
+        Self::verify(instruction.get_instruction_data(), signer, message)
+    }
+}
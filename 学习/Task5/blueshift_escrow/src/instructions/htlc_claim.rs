@@ -0,0 +1,188 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use solana_address::Address;
+use crate::{AccountCheck, AccountClose, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, CloseAccountInterface, EscrowError, Htlc, MintInterface, ProgramAccount, ProgramAccountCheck, ProgramAccountType, ProgramLog, sha256, SignerAccount, TokenProgramInterface, TransferCheckedInterface, TransferHook};
+
+// HtlcClaim：指定的接受者在超时前提交 preimage，取走金库中的全部代币 A
+// preimage 写入交易日志，另一条链上的创建者据此领取对价
+pub struct HtlcClaimAccounts<'info> {
+    pub taker: &'info AccountView,
+    // 创建者：接收金库和 Htlc 账户的租金
+    pub maker: &'info AccountView,
+    pub htlc: &'info AccountView,
+    pub mint_a: &'info AccountView,
+    pub vault: &'info AccountView,
+    pub taker_ata_a: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    // 剩余账户：Token-2022 转账钩子需要的账户
+    pub remaining_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for HtlcClaimAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [taker, maker, htlc, mint_a, vault, taker_ata_a, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(taker)?;
+
+        ProgramAccount::check::<Htlc>(htlc)?;
+
+        MintInterface::check(mint_a)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        AssociatedTokenAccount::check(vault, htlc, mint_a, token_program)?;
+
+        Ok(Self{
+            taker,
+            maker,
+            htlc,
+            mint_a,
+            vault,
+            taker_ata_a,
+            system_program,
+            token_program,
+            remaining_accounts,
+        })
+    }
+}
+
+pub struct HtlcClaimInstructionData {
+    pub preimage: [u8; 32],
+}
+
+impl<'info> TryFrom<&'info [u8]> for HtlcClaimInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        // preimage 固定为 32 字节：两条链对 preimage 长度的限制必须一致
+        // 否则接受者可以用一条链接受、另一条链拒绝的 preimage 领取，让对手方无法完成交换
+        let preimage: [u8; 32] = data
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        Ok(Self{
+            preimage,
+        })
+    }
+}
+
+pub struct HtlcClaim<'info> {
+    pub accounts: HtlcClaimAccounts<'info>,
+    pub instruction_data: HtlcClaimInstructionData,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for HtlcClaim<'info> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = HtlcClaimAccounts::try_from(accounts)?;
+        let instruction_data = HtlcClaimInstructionData::try_from(data)?;
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.taker_ata_a,
+            accounts.mint_a,
+            accounts.taker,
+            accounts.taker,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self{
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'info> HtlcClaim<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &9;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump, hashlock) = {
+            let data = self.accounts.htlc.try_borrow()?;
+            let htlc = Htlc::load(&data)?;
+            let htlc_key = Address::create_program_address(
+                &[
+                    b"htlc",
+                    self.accounts.maker.address().as_ref(),
+                    &htlc.seed().to_le_bytes(),
+                    &htlc.bump(),
+                ],
+                &crate::ID
+            )?;
+            if &htlc_key != self.accounts.htlc.address() {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+            if htlc.mint_a() != self.accounts.mint_a.address() {
+                return Err(EscrowError::InvalidAddress.into());
+            }
+            if htlc.taker() != self.accounts.taker.address() {
+                return Err(EscrowError::InvalidTaker.into());
+            }
+            // 到达超时时间后只能由创建者 Refund，避免双方同时可以取走代币
+            if htlc.is_timed_out(Clock::get()?.unix_timestamp) {
+                return Err(EscrowError::EscrowExpired.into());
+            }
+            (htlc.seed(), htlc.bump(), *htlc.hashlock())
+        };
+
+        // sha256 在链上使用 sol_sha256 系统调用
+        if sha256(&[&self.instruction_data.preimage]) != hashlock {
+            return Err(EscrowError::InvalidPreimage.into());
+        }
+
+        let seed_binding = seed.to_le_bytes();
+        let bump_binding = bump;
+        let htlc_seeds = [
+            Seed::from(b"htlc"),
+            Seed::from(self.accounts.maker.address().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+        let signers = [Signer::from(&htlc_seeds)];
+
+        let amount = {
+            let vault_data = self.accounts.vault.try_borrow()?;
+            u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
+        };
+        TransferCheckedInterface {
+            from: self.accounts.vault,
+            mint: self.accounts.mint_a,
+            to: self.accounts.taker_ata_a,
+            authority: self.accounts.htlc,
+            token_program: self.accounts.token_program,
+            extra_accounts: TransferHook::resolve(self.accounts.mint_a, self.accounts.remaining_accounts)?,
+            amount,
+            decimals: MintInterface::decimals(self.accounts.mint_a)?,
+        }.invoke_signed(&signers)?;
+
+        CloseAccountInterface {
+            account: self.accounts.vault,
+            destination: self.accounts.maker,
+            authority: self.accounts.htlc,
+            token_program: self.accounts.token_program,
+        }.invoke_signed(&signers)?;
+
+        // 日志：["htlc_claim", htlc 地址, hashlock, preimage]
+        ProgramLog::data(&[
+            b"htlc_claim",
+            self.accounts.htlc.address().as_ref(),
+            &hashlock,
+            &self.instruction_data.preimage,
+        ]);
+
+        ProgramAccount::close(
+            self.accounts.htlc,
+            self.accounts.maker
+        )?;
+        Ok(())
+    }
+}
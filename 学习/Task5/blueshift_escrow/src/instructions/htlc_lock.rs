@@ -0,0 +1,185 @@
+use pinocchio::{Address, AccountView, ProgramResult};
+use pinocchio::cpi::Seed;
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use crate::{EscrowError, AccountCheck, SignerAccount, MintInterface, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, ProgramAccount, ProgramAccountInit, ProgramAccountType, Htlc, TokenProgramInterface, TransferHook, TransferCheckedInterface};
+
+// HtlcLock：创建者把代币 A 锁定到 Htlc PDA 的金库中
+// 只有指定的接受者在超时前提交 preimage 才能取走，超时后创建者可以取回
+pub struct HtlcLockAccounts<'info> {
+    pub maker: &'info AccountView,
+    pub htlc: &'info AccountView,
+    pub mint_a: &'info AccountView,
+    pub maker_ata_a: &'info AccountView,
+    pub vault: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    // 剩余账户：Token-2022 转账钩子需要的账户
+    pub remaining_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for HtlcLockAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [maker, htlc, mint_a, maker_ata_a, vault, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(maker)?;
+
+        MintInterface::check(mint_a)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        AssociatedTokenAccount::check(maker_ata_a, maker, mint_a, token_program)?;
+
+        Ok(Self{
+            maker,
+            htlc,
+            mint_a,
+            maker_ata_a,
+            vault,
+            system_program,
+            token_program,
+            remaining_accounts,
+        })
+    }
+}
+
+pub struct HtlcLockInstructionData {
+    pub seed: u64,
+    pub amount: u64,
+    // sha256(preimage)，与另一条链上的锁使用同一个值
+    pub hashlock: [u8; 32],
+    // 超时时间（Unix 时间戳，秒），必须在未来
+    pub timeout: i64,
+    // 指定的接受者，必须设置
+    pub taker: Address,
+}
+
+impl<'info> TryFrom<&'info [u8]> for HtlcLockInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        // 数据布局：seed(8) + amount(8) + hashlock(32) + timeout(8) + taker(32)
+        if data.len() != 88 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let amount = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let hashlock: [u8; 32] = data[16..48].try_into().unwrap();
+        let timeout = i64::from_le_bytes(data[48..56].try_into().unwrap());
+        let taker = Address::new_from_array(data[56..88].try_into().unwrap());
+
+        if amount == 0 {
+            return Err(EscrowError::InvalidAmount.into());
+        }
+
+        // HTLC 只在双方事先约定的情况下才有意义，不支持任何人都可以领取
+        if taker == Address::default() {
+            return Err(EscrowError::InvalidTaker.into());
+        }
+
+        Ok(Self{
+            seed,
+            amount,
+            hashlock,
+            timeout,
+            taker,
+        })
+    }
+}
+
+pub struct HtlcLock<'info> {
+    pub accounts: HtlcLockAccounts<'info>,
+    pub instruction_data: HtlcLockInstructionData,
+    pub bump: u8,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for HtlcLock<'info> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = HtlcLockAccounts::try_from(accounts)?;
+        let instruction_data = HtlcLockInstructionData::try_from(data)?;
+
+        let (_, bump) = Address::find_program_address(
+            &[
+                b"htlc",
+                accounts.maker.address().as_ref(),
+                &instruction_data.seed.to_le_bytes(),
+            ],
+            &crate::ID,
+        );
+
+        let seed_binding = instruction_data.seed.to_le_bytes();
+        let bump_binding = [bump];
+        let htlc_seeds = [
+            Seed::from(b"htlc"),
+            Seed::from(accounts.maker.address().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+
+        ProgramAccount::init::<Htlc>(
+            accounts.maker,
+            accounts.htlc,
+            &htlc_seeds,
+        )?;
+
+        AssociatedTokenAccount::init(
+            accounts.vault,
+            accounts.mint_a,
+            accounts.maker,
+            accounts.htlc,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self{
+            accounts,
+            instruction_data,
+            bump,
+        })
+    }
+}
+
+impl<'info> HtlcLock<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &8;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 超时时间必须在未来，否则接受者没有领取的窗口
+        if self.instruction_data.timeout <= Clock::get()?.unix_timestamp {
+            return Err(EscrowError::EscrowExpired.into());
+        }
+
+        {
+            let mut data = self.accounts.htlc.try_borrow_mut()?;
+            let htlc = Htlc::load_mut(data.as_mut())?;
+            htlc.set_inner(
+                self.instruction_data.seed,
+                *self.accounts.maker.address(),
+                self.instruction_data.taker,
+                *self.accounts.mint_a.address(),
+                self.instruction_data.hashlock,
+                self.instruction_data.timeout,
+                [self.bump],
+            );
+        }
+
+        TransferCheckedInterface{
+            from: self.accounts.maker_ata_a,
+            mint: self.accounts.mint_a,
+            to: self.accounts.vault,
+            authority: self.accounts.maker,
+            token_program: self.accounts.token_program,
+            extra_accounts: TransferHook::resolve(self.accounts.mint_a, self.accounts.remaining_accounts)?,
+            amount: self.instruction_data.amount,
+            decimals: MintInterface::decimals(self.accounts.mint_a)?,
+        }.invoke()?;
+
+        Ok(())
+    }
+}
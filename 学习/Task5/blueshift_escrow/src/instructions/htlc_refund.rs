@@ -0,0 +1,147 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use solana_address::Address;
+use crate::{AccountCheck, AccountClose, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, CloseAccountInterface, EscrowError, Htlc, MintInterface, ProgramAccount, ProgramAccountCheck, ProgramAccountType, SignerAccount, TokenProgramInterface, TransferCheckedInterface, TransferHook};
+
+// HtlcRefund：到达超时时间后，创建者取回金库中的代币 A
+// 超时前不能退款：接受者可能已经在另一条链上锁定了对价，需要完整的领取窗口
+pub struct HtlcRefundAccounts<'info> {
+    pub maker: &'info AccountView,
+    pub htlc: &'info AccountView,
+    pub mint_a: &'info AccountView,
+    pub vault: &'info AccountView,
+    pub maker_ata_a: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    // 剩余账户：Token-2022 转账钩子需要的账户
+    pub remaining_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for HtlcRefundAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [maker, htlc, mint_a, vault, maker_ata_a, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(maker)?;
+
+        ProgramAccount::check::<Htlc>(htlc)?;
+
+        MintInterface::check(mint_a)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        AssociatedTokenAccount::check(vault, htlc, mint_a, token_program)?;
+
+        Ok(Self{
+            maker,
+            htlc,
+            mint_a,
+            vault,
+            maker_ata_a,
+            system_program,
+            token_program,
+            remaining_accounts,
+        })
+    }
+}
+
+pub struct HtlcRefund<'info> {
+    pub accounts: HtlcRefundAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for HtlcRefund<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = HtlcRefundAccounts::try_from(accounts)?;
+
+        // 创建者的 ATA 可能已经被关闭
+        AssociatedTokenAccount::init_if_needed(
+            accounts.maker_ata_a,
+            accounts.mint_a,
+            accounts.maker,
+            accounts.maker,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self{
+            accounts,
+        })
+    }
+}
+
+impl<'info> HtlcRefund<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &10;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump) = {
+            let data = self.accounts.htlc.try_borrow()?;
+            let htlc = Htlc::load(&data)?;
+            // PDA 由 maker 地址派生，验证通过即说明签名者就是创建者
+            let htlc_key = Address::create_program_address(
+                &[
+                    b"htlc",
+                    self.accounts.maker.address().as_ref(),
+                    &htlc.seed().to_le_bytes(),
+                    &htlc.bump(),
+                ],
+                &crate::ID
+            )?;
+            if &htlc_key != self.accounts.htlc.address() {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+            if htlc.mint_a() != self.accounts.mint_a.address() {
+                return Err(EscrowError::InvalidAddress.into());
+            }
+            if !htlc.is_timed_out(Clock::get()?.unix_timestamp) {
+                return Err(EscrowError::EscrowNotExpired.into());
+            }
+            (htlc.seed(), htlc.bump())
+        };
+
+        let seed_binding = seed.to_le_bytes();
+        let bump_binding = bump;
+        let htlc_seeds = [
+            Seed::from(b"htlc"),
+            Seed::from(self.accounts.maker.address().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+        let signers = [Signer::from(&htlc_seeds)];
+
+        let amount = {
+            let vault_data = self.accounts.vault.try_borrow()?;
+            u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
+        };
+        TransferCheckedInterface {
+            from: self.accounts.vault,
+            mint: self.accounts.mint_a,
+            to: self.accounts.maker_ata_a,
+            authority: self.accounts.htlc,
+            token_program: self.accounts.token_program,
+            extra_accounts: TransferHook::resolve(self.accounts.mint_a, self.accounts.remaining_accounts)?,
+            amount,
+            decimals: MintInterface::decimals(self.accounts.mint_a)?,
+        }.invoke_signed(&signers)?;
+
+        CloseAccountInterface {
+            account: self.accounts.vault,
+            destination: self.accounts.maker,
+            authority: self.accounts.htlc,
+            token_program: self.accounts.token_program,
+        }.invoke_signed(&signers)?;
+
+        ProgramAccount::close(
+            self.accounts.htlc,
+            self.accounts.maker
+        )?;
+        Ok(())
+    }
+}
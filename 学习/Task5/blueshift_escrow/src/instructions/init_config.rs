@@ -0,0 +1,107 @@
+use pinocchio::{Address, AccountView, ProgramResult};
+use pinocchio::cpi::Seed;
+use pinocchio::error::ProgramError;
+use crate::{AccountCheck, Config, FeeSide, ProgramAccount, ProgramAccountInit, ProgramAccountType, SignerAccount};
+
+// InitConfig：创建全局 Config 账户（单例 PDA，种子 ["config"]）
+// 签名者成为管理员；PDA 只能创建一次，部署后应立即调用
+pub struct InitConfigAccounts<'info> {
+    pub admin: &'info AccountView,
+    pub config: &'info AccountView,
+    pub system_program: &'info AccountView,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for InitConfigAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [admin, config, system_program, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(admin)?;
+
+        Ok(Self{
+            admin,
+            config,
+            system_program,
+        })
+    }
+}
+
+// 指令数据：fee_bps(2) + fee_side(1) + treasury(32)
+pub struct ConfigInstructionData {
+    pub fee_bps: u16,
+    pub fee_side: FeeSide,
+    pub treasury: Address,
+}
+
+impl<'info> TryFrom<&'info [u8]> for ConfigInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u16>() + size_of::<u8>() + size_of::<Address>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let fee_bps = u16::from_le_bytes(data[0..2].try_into().unwrap());
+        let fee_side = FeeSide::try_from(data[2])?;
+        let treasury = Address::new_from_array(data[3..35].try_into().unwrap());
+
+        Ok(Self{
+            fee_bps,
+            fee_side,
+            treasury,
+        })
+    }
+}
+
+pub struct InitConfig<'info> {
+    pub accounts: InitConfigAccounts<'info>,
+    pub instruction_data: ConfigInstructionData,
+    pub bump: u8,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for InitConfig<'info> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = InitConfigAccounts::try_from(accounts)?;
+        let instruction_data = ConfigInstructionData::try_from(data)?;
+
+        let (_, bump) = Address::find_program_address(&[b"config"], &crate::ID);
+        let bump_binding = [bump];
+        let config_seeds = [
+            Seed::from(b"config"),
+            Seed::from(&bump_binding),
+        ];
+        // 账户已存在时 System Program 的 CreateAccount 会失败，保证只能初始化一次
+        ProgramAccount::init::<Config>(
+            accounts.admin,
+            accounts.config,
+            &config_seeds,
+        )?;
+
+        Ok(Self{
+            accounts,
+            instruction_data,
+            bump,
+        })
+    }
+}
+
+impl<'info> InitConfig<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &5;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut data = self.accounts.config.try_borrow_mut()?;
+        let config = Config::load_mut(data.as_mut())?;
+
+        config.set_inner(
+            [self.bump],
+            self.instruction_data.fee_bps,
+            self.instruction_data.fee_side,
+            *self.accounts.admin.address(),
+            self.instruction_data.treasury,
+        )
+    }
+}
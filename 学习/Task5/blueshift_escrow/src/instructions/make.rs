@@ -1,8 +1,9 @@
 use pinocchio::{Address, AccountView, ProgramResult};
 use pinocchio::cpi::Seed;
 use pinocchio::error::ProgramError;
-use pinocchio_token::instructions::Transfer;
-use crate::{AccountCheck, SignerAccount, MintInterface, AssociatedTokenAccount, AssociatedTokenAccountCheck, ProgramAccount, Escrow, ProgramAccountInit, AssociatedTokenAccountInit};
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use crate::{BPS_DENOMINATOR, NATIVE_MINT, EscrowError, NativeSide, NativeVault, AccountCheck, SignerAccount, MintInterface, AssociatedTokenAccount, AssociatedTokenAccountCheck, ProgramAccount, Escrow, MakerState, MarketIndex, ProgramAccountCheck, ProgramAccountInit, ProgramAccountType, AssociatedTokenAccountInit, TokenProgramInterface, TransferHook, TransferCheckedInterface};
 
 pub struct MakeAccounts<'info> {
     pub maker: &'info AccountView,
@@ -48,6 +49,12 @@ pub struct MakeAccounts<'info> {
     */
     pub vault: &'info AccountView,
 
+    // 租金支付者，支付托管账户、金库 ATA 以及 MakerState / Market 的租金
+    // 托管中记录为 rent_recipient，Take / Refund 等关闭托管时租金退还给它
+    // 不单独指定支付者时传入创建者或本程序 ID 作为占位（与 Take 的 referrer 约定相同），此时由创建者支付
+    // 其他地址必须是签名者（可写）
+    pub payer: &'info AccountView,
+
     /*
     System program: 11111111111111111111111111111111，Solana 的内置 System Program。
     负责创建/分配/转移 lamports、创建账户并给账户赋 owner 等低级账户操作。
@@ -62,13 +69,18 @@ pub struct MakeAccounts<'info> {
      */
     pub token_program: &'info AccountView,
 
+    // 剩余账户：Token-2022 转账钩子需要的账户
+    // 钩子程序、extra-account-metas PDA 以及其中列出的额外账户
+    // 可选：创建者的 seed 计数器（MakerState PDA，种子 ["maker", maker]），指令数据中的 seed 为 0 时传入，托管的 seed 由计数器分配
+    // 可选：交易对的挂单索引（Market PDA，种子 ["market", mint_a, mint_b]），传入时托管按价格加入索引，不存在则由 payer 创建
+    pub remaining_accounts: &'info [AccountView],
 }
 
 impl<'info> TryFrom<&'info [AccountView]> for MakeAccounts<'info> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
-        let [maker, escrow, mint_a, mint_b, maker_ata_a, vault, system_program, token_program,_] = accounts else {
+        let [maker, escrow, mint_a, mint_b, maker_ata_a, vault, payer, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
@@ -78,7 +90,16 @@ impl<'info> TryFrom<&'info [AccountView]> for MakeAccounts<'info> {
 
         MintInterface::check(mint_b)?;
 
-        AssociatedTokenAccount::check(maker_ata_a,maker,mint_a, token_program)?;
+        TokenProgramInterface::check(token_program)?;
+
+        let payer = if payer.address() == &crate::ID || payer.address() == maker.address() {
+            maker
+        } else {
+            SignerAccount::check(payer)?;
+            payer
+        };
+
+        // maker_ata_a 的检查在 Make::try_from 中进行：代币 A 为原生 SOL 时不使用 ATA
         Ok(Self{
             maker,
             escrow,
@@ -86,25 +107,66 @@ impl<'info> TryFrom<&'info [AccountView]> for MakeAccounts<'info> {
             mint_b,
             maker_ata_a,
             vault,
+            payer,
             system_program,
             token_program,
+            remaining_accounts,
         })
     }
 }
 
 pub struct MakeInstructionData {
+    // 使用 MakerState 计数器时为 0，实际的 seed 由计数器分配
     pub seed: u64,
     pub receive: u64,
     pub amount: u64,
+    // 可选：过期时间（Unix 时间戳，秒），不传或为 0 表示永不过期
+    pub expires_at: i64,
+    // 可选：指定的接受者，不传或为全 0 地址表示任何人都可以接受
+    pub taker: Address,
+    // 可选：接受者白名单的 Merkle 根，不传或为全 0 表示不启用白名单
+    pub allowlist_root: [u8; 32],
+    // 可选：推荐费（基点），不传或为 0 表示不支付推荐费，不能超过 10_000
+    pub referral_bps: u16,
+    // 可选：原生 SOL 标记（NativeSide），不传或为 0 表示两侧都是 SPL 代币
+    pub native: NativeSide,
+    // 可选：荷兰式拍卖，期望数量在 auction_start 到 auction_end 之间从 receive 线性下降到 end_receive
+    // 不传或 auction_end 为 0 表示固定价格
+    pub end_receive: u64,
+    pub auction_start: i64,
+    pub auction_end: i64,
 }
 
 impl<'info> TryFrom<&'info [u8]> for MakeInstructionData {
     type Error = ProgramError;
 
     fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
-       if data.len() != size_of::<u64>() * 3 {
+       // 数据布局：seed(8) + receive(8) + amount(8) + [可选] expires_at(8) + [可选] taker(32) + [可选] allowlist_root(32) + [可选] referral_bps(2) + [可选] native(1)
+       //          + [可选] end_receive(8) + auction_start(8) + auction_end(8)
+       // 可选字段按顺序追加：传入后面的字段时必须同时传入前面的字段（可以为 0）
+       if !matches!(data.len(), 24 | 32 | 64 | 96 | 98 | 99 | 123) {
            return Err(ProgramError::InvalidInstructionData);
        }
+       let expires_at = data
+           .get(24..32)
+           .map_or(0, |bytes| i64::from_le_bytes(bytes.try_into().unwrap()));
+       let taker = data
+           .get(32..64)
+           .map_or(Address::default(), |bytes| Address::new_from_array(bytes.try_into().unwrap()));
+       let allowlist_root = data
+           .get(64..96)
+           .map_or([0; 32], |bytes| bytes.try_into().unwrap());
+       let referral_bps = data
+           .get(96..98)
+           .map_or(0, |bytes| u16::from_le_bytes(bytes.try_into().unwrap()));
+       let native = NativeSide::try_from(data.get(98).copied().unwrap_or(0))?;
+       let (end_receive, auction_start, auction_end) = data
+           .get(99..123)
+           .map_or((0, 0, 0), |bytes| (
+               u64::from_le_bytes(bytes[0..8].try_into().unwrap()),
+               i64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+               i64::from_le_bytes(bytes[16..24].try_into().unwrap()),
+           ));
        let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
        let receive = u64::from_le_bytes(data[8..16].try_into().unwrap());
        let amount = u64::from_le_bytes(data[16..24].try_into().unwrap());
@@ -113,10 +175,22 @@ impl<'info> TryFrom<&'info [u8]> for MakeInstructionData {
            return Err(ProgramError::InvalidInstructionData);
        }
 
+       if referral_bps > BPS_DENOMINATOR {
+           return Err(EscrowError::InvalidFee.into());
+       }
+
        Ok(Self{
            seed,
            receive,
            amount,
+           expires_at,
+           taker,
+           allowlist_root,
+           referral_bps,
+           native,
+           end_receive,
+           auction_start,
+           auction_end,
        })
     }
 }
@@ -172,7 +246,29 @@ impl<'info> TryFrom<(&'info [u8],&'info [AccountView])> for Make<'info> {
     fn try_from((data, accounts):(&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
         let accounts = MakeAccounts::try_from(accounts)?; 
 
-        let instruction_data = MakeInstructionData::try_from(data)?;
+        let mut instruction_data = MakeInstructionData::try_from(data)?;
+
+        // seed 为 0 且传入了创建者的计数器：从计数器中分配 seed，没有传入时 0 就是自己指定的 seed
+        if instruction_data.seed == 0 {
+            if let Some(seed) = Self::allocate_seed(&accounts)? {
+                instruction_data.seed = seed;
+            }
+        }
+
+        // 原生 SOL 一侧必须传入 NATIVE_MINT，托管中记录的 mint 与实际资产一致
+        match instruction_data.native {
+            NativeSide::TokenA if accounts.mint_a.address() != &NATIVE_MINT => {
+                return Err(EscrowError::InvalidAddress.into());
+            }
+            NativeSide::TokenB if accounts.mint_b.address() != &NATIVE_MINT => {
+                return Err(EscrowError::InvalidAddress.into());
+            }
+            _ => {}
+        }
+
+        if instruction_data.native != NativeSide::TokenA {
+            AssociatedTokenAccount::check(accounts.maker_ata_a, accounts.maker, accounts.mint_a, accounts.token_program)?;
+        }
         
         /*
         根据指定的种子（seeds）和程序 ID（crate::ID），计算出合法的 PDA 地址及其对应的 bump 值。
@@ -199,7 +295,7 @@ impl<'info> TryFrom<(&'info [u8],&'info [AccountView])> for Make<'info> {
         */
         let escrow_seeds=[
             Seed::from(b"escrow"), // 将字符串字面量 "escrow" 转换为 Seed 类型，用于生成程序派生地址（PDA, Program Derived Address）的种子（seeds）。
-            Seed::from(accounts.maker.address().as_ref().as_ref()),
+            Seed::from(accounts.maker.address().as_ref()),
             Seed::from(&seed_binding), // 指令数据中的 seed 值（已转换为字节数组）。
             Seed::from(&bump_binding), // PDA 的 bump 值（用于唯一性校验）。
         ];
@@ -209,25 +305,33 @@ impl<'info> TryFrom<(&'info [u8],&'info [AccountView])> for Make<'info> {
         为Escrow类型创建一个新的账户，并将其绑定到指定的PDA地址。
         通过 ProgramAccount::init 方法，将 escrow 账户初始化为 Escrow 类型。
         */
+        // seed 已被使用过（托管仍然存在）时给出明确的错误，而不是 System Program 创建账户失败
+        if accounts.escrow.owned_by(&crate::ID) {
+            return Err(EscrowError::SeedInUse.into());
+        }
+
+        // 账户大小和租金由 Escrow::LEN 决定，创建后写入 Escrow 判别器
         ProgramAccount::init::<Escrow>(
-            accounts.maker, // 创建者账户（通常是交易的签名者）。
+            accounts.payer, // 租金支付者（未单独指定时为创建者）。
             accounts.escrow, // 目标 PDA 账户（由客户端预先计算并传入）。
             &escrow_seeds, // 用于生成 PDA 的种子数组。
-            Escrow::LEN, // Escrow 结构体所需的账户大小（以字节为单位）。
         )?;
         
         /*
         初始化 Vault ATA 账户
         初始化一个 ATA（关联代币账户），用于存储代币 A，并将其所有权赋予 escrow（PDA）。
+        代币 A 为原生 SOL 时托管 PDA 本身就是金库（见 NativeVault），不创建 ATA。
         */
-        AssociatedTokenAccount::init(
-            accounts.vault, //目标 ATA 账户（将被初始化）。
-            accounts.mint_a, // 代币 A 的 Mint 账户（定义代币类型）。
-            accounts.maker, // 创建者账户（通常是交易的签名者）。
-            accounts.escrow, // 托管账户（PDA），将成为 ATA 的所有者。
-            accounts.system_program, // System Program，用于创建账户。
-            accounts.token_program, // Token Program，用于初始化 ATA。
-        )?;
+        if instruction_data.native != NativeSide::TokenA {
+            AssociatedTokenAccount::init(
+                accounts.vault, //目标 ATA 账户（将被初始化）。
+                accounts.mint_a, // 代币 A 的 Mint 账户（定义代币类型）。
+                accounts.payer, // 租金支付者（未单独指定时为创建者）。
+                accounts.escrow, // 托管账户（PDA），将成为 ATA 的所有者。
+                accounts.system_program, // System Program，用于创建账户。
+                accounts.token_program, // Token Program，用于初始化 ATA。
+            )?;
+        }
 
         Ok(Self{
             accounts,
@@ -242,12 +346,77 @@ impl<'info> Make<'info> {
     // 可取地址：&0 是对值 0 的引用（指向常量/只读内存），满足需要借用的 API/签名。
     pub const DISCRIMINATOR: &'info u8 = &0;
 
+    // 计数器一次最多跳过的 seed 数量（每跳过一个需要多派生一次 PDA）
+    pub const MAX_SEED_SKIP: usize = 8;
+
+    // 从 MakerState 中取出下一个 seed，账户不存在时先由 payer 创建
+    // 剩余账户中没有创建者的 MakerState 时返回 None
+    fn allocate_seed(accounts: &MakeAccounts) -> Result<Option<u64>, ProgramError> {
+        let (maker_state_key, maker_state_bump) = Address::find_program_address(
+            &[
+                b"maker",
+                accounts.maker.address().as_ref(),
+            ],
+            &crate::ID,
+        );
+        let Some(maker_state) = accounts
+            .remaining_accounts
+            .iter()
+            .find(|account| account.address() == &maker_state_key)
+        else {
+            return Ok(None);
+        };
+
+        if maker_state.owned_by(&crate::ID) {
+            ProgramAccount::check::<MakerState>(maker_state)?;
+        } else {
+            let bump_binding = [maker_state_bump];
+            let maker_state_seeds = [
+                Seed::from(b"maker"),
+                Seed::from(accounts.maker.address().as_ref()),
+                Seed::from(&bump_binding),
+            ];
+            ProgramAccount::init::<MakerState>(
+                accounts.payer,
+                maker_state,
+                &maker_state_seeds,
+            )?.set_inner(*accounts.maker.address(), bump_binding);
+        }
+
+        // 计数器分配的 seed 可能已经被自己指定 seed 的托管占用：客户端传入从 next_seed 开始第一个空闲 seed 的托管，
+        // 计数器依次跳过前面的 seed，跳过的 seed 不会再被分配
+        let mut data = maker_state.try_borrow_mut()?;
+        let maker_state = MakerState::load_mut(&mut data)?;
+        for _ in 0..=Self::MAX_SEED_SKIP {
+            let seed = maker_state.allocate_seed()?;
+            let (escrow_key, _) = Address::find_program_address(
+                &[
+                    b"escrow",
+                    accounts.maker.address().as_ref(),
+                    &seed.to_le_bytes(),
+                ],
+                &crate::ID,
+            );
+            if &escrow_key == accounts.escrow.address() {
+                return Ok(Some(seed));
+            }
+        }
+        Err(EscrowError::InvalidAddress.into())
+    }
+
     pub fn process(&mut self) -> ProgramResult {
 
+        // 过期时间必须在未来，否则创建出来就已经无法被接受
+        if self.instruction_data.expires_at != 0
+            && self.instruction_data.expires_at <= Clock::get()?.unix_timestamp
+        {
+            return Err(EscrowError::EscrowExpired.into());
+        }
+
         // 获取托管账户的可变借用
         let mut data = self.accounts.escrow.try_borrow_mut()?;
 
-        // 将字节数组解析为 Escrow 结构体
+        // 将字节数组解析为 Escrow 结构体（判别器已在 ProgramAccount::init 中写入）
         // unsafe transmute 将字节指针转换为结构体指针
         let escrow = Escrow::load_mut(data.as_mut())?; // 关键：这是直接内存映射，不是数据复制！
 
@@ -268,20 +437,58 @@ impl<'info> Make<'info> {
         // 设置托管账户的所有字段
         escrow.set_inner(
             self.instruction_data.seed,                   // seed：PDA 派生种子
-            self.accounts.maker.address().clone(),        // maker：创建者地址
-            self.accounts.mint_a.address().clone(),       // mint_a：代币 A mint
-            self.accounts.mint_b.address().clone(),       // mint_b：代币 B mint
-            self.instruction_data.receive.clone(),        // receive：期望数量
+            *self.accounts.maker.address(),               // maker：创建者地址
+            *self.accounts.mint_a.address(),              // mint_a：代币 A mint
+            *self.accounts.mint_b.address(),              // mint_b：代币 B mint
+            self.instruction_data.receive,                // receive：期望数量
             [self.bump],                                 // bump：PDA bump 种子
+            self.instruction_data.expires_at,             // expires_at：过期时间（0 表示永不过期）
+            self.instruction_data.taker,                  // taker：指定的接受者（全 0 表示任何人）
+            self.instruction_data.allowlist_root,         // allowlist_root：接受者白名单 Merkle 根（全 0 表示不启用）
+            self.instruction_data.referral_bps,           // referral_bps：推荐费（基点，0 表示不支付）
+            self.instruction_data.native,                 // native：原生 SOL 标记
         );
-        // Pinocchio 版本使用 Transfer 指令（不需要 decimals）
-        // 因为 Transfer 指令使用 Token Program 的基本转账功能
+        // 荷兰式拍卖：要求 auction_start < auction_end 且 end_receive <= receive
+        escrow.set_auction(
+            self.instruction_data.end_receive,
+            self.instruction_data.auction_start,
+            self.instruction_data.auction_end,
+        )?;
+        // 关闭托管和金库时租金退还给支付者
+        escrow.set_rent_recipient(*self.accounts.payer.address());
+
+        // 传入 Market 时挂单，之后的重新挂单和撤单都必须传入 Market
+        if MarketIndex::list(
+            self.accounts.remaining_accounts,
+            self.accounts.payer,
+            self.accounts.mint_a,
+            self.accounts.mint_b,
+            self.accounts.escrow.address(),
+            self.instruction_data.receive,
+            self.instruction_data.amount,
+        )? {
+            escrow.set_listed();
+        }
+        // 释放托管账户的借用：原生 SOL 存入时托管账户会参与 System Program 的 CPI
+        drop(data);
+
+        // 代币 A 为原生 SOL：lamports 直接转入托管 PDA
+        if self.instruction_data.native == NativeSide::TokenA {
+            return NativeVault::deposit(self.accounts.maker, self.accounts.escrow, self.instruction_data.amount);
+        }
+
+        // 使用 TransferChecked 指令（需要 decimals），与 Anchor 版本一致
+        // Token-2022 的转账钩子只在 TransferChecked 中触发，钩子账户随 CPI 一起转发
         // 转账代币 A 从创建者 ATA 到金库
-        Transfer{
+        TransferCheckedInterface{
             from: self.accounts.maker_ata_a,
+            mint: self.accounts.mint_a,
             to: self.accounts.vault,
             authority: self.accounts.maker, // 权限：创建者必须签名
-            amount: self.instruction_data.amount
+            token_program: self.accounts.token_program,
+            extra_accounts: TransferHook::resolve(self.accounts.mint_a, self.accounts.remaining_accounts)?,
+            amount: self.instruction_data.amount,
+            decimals: MintInterface::decimals(self.accounts.mint_a)?,
         }.invoke()?; // 调用 Token Program 执行转账
 
         Ok(())
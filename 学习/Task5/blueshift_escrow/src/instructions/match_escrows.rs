@@ -0,0 +1,345 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use solana_address::Address;
+use crate::{AccountCheck, AccountClose, Config, FeeSide, TakeAccounts, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, CloseAccountInterface, Escrow, EscrowError, MarketIndex, MintInterface, NativeSide, ProgramAccount, ProgramAccountCheck, ProgramAccountType, ProgramLog, RentRecipient, SignerAccount, TokenProgramInterface, TransferCheckedInterface, TransferHook};
+
+/*
+Match：撮合两个价格交叉的托管，任何人都可以调用，撮合者不需要自己成为接受者
+
+托管 1 存入 mint_a、要求换回 mint_b；托管 2 存入 mint_b、要求换回 mint_a
+两者交叉的条件：托管 2 的金库余额 >= 托管 1 要求的数量，且托管 1 的金库余额 >= 托管 2 要求的数量
+（要求的数量按当前时间计算，荷兰式拍卖取当前价格）
+
+结算规则：
+- 每个创建者收到自己要求的数量（与被普通接受者 Take 时相同）
+- 两个金库中超出对方要求的部分（价差）归撮合者，作为运行撮合程序的激励
+- 两个托管都被完全成交，金库和托管账户关闭，租金退还给各自的 rent_recipient（不是创建者时放在剩余账户中）
+- 协议费与两个托管分别被 Take 时相同，撮合不能绕过协议费（撮合者相当于两个托管的接受者）：
+  FeeSide::TokenB 从两个创建者收到的数量中扣除；
+  FeeSide::TokenA 从两个金库释放的数量中扣除，由撮合者的价差承担，价差不足以支付协议费时视为不交叉
+- 撮合没有推荐人，不支付推荐费
+
+指定了接受者的托管只能与该接受者创建的托管撮合；启用了白名单的托管不能被撮合
+原生 SOL 托管不支持撮合
+*/
+pub struct MatchAccounts<'info> {
+    // 撮合者：支付按需创建 ATA 的费用，接收价差
+    pub matcher: &'info AccountView,
+    pub maker_1: &'info AccountView,
+    pub escrow_1: &'info AccountView,
+    pub maker_2: &'info AccountView,
+    pub escrow_2: &'info AccountView,
+    pub mint_a: &'info AccountView,
+    pub mint_b: &'info AccountView,
+    // 托管 1 的金库（代币 A）
+    pub vault_1: &'info AccountView,
+    // 托管 2 的金库（代币 B）
+    pub vault_2: &'info AccountView,
+    pub maker_1_ata_b: &'info AccountView,
+    pub maker_2_ata_a: &'info AccountView,
+    // 撮合者的 ATA：只有对应一侧有价差时才需要（按需创建）
+    pub matcher_ata_a: &'info AccountView,
+    pub matcher_ata_b: &'info AccountView,
+    // 可选的全局配置（协议费、国库地址）：与 Take 相同，
+    // config、treasury、treasury_ata_a、treasury_ata_b 都传入本程序 ID 作为占位时不收协议费
+    pub config: Option<&'info AccountView>,
+    // 国库：传入 Config 时必须与其中记录的地址一致
+    pub treasury: &'info AccountView,
+    // 国库在两个 mint 上的 ATA（协议费不为 0 时按需创建，由撮合者支付）
+    pub treasury_ata_a: &'info AccountView,
+    pub treasury_ata_b: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    // 剩余账户：Token-2022 转账钩子需要的账户（mint_a 与 mint_b 的钩子账户都放在这里）
+    // 以及与创建者不同的租金退还对象（rent_recipient）
+    // 可选：两个方向的挂单索引（Market），托管关闭时从中移除
+    pub remaining_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for MatchAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [matcher, maker_1, escrow_1, maker_2, escrow_2, mint_a, mint_b, vault_1, vault_2, maker_1_ata_b, maker_2_ata_a, matcher_ata_a, matcher_ata_b, config, treasury, treasury_ata_a, treasury_ata_b, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(matcher)?;
+
+        ProgramAccount::check::<Escrow>(escrow_1)?;
+
+        ProgramAccount::check::<Escrow>(escrow_2)?;
+
+        if escrow_1.address() == escrow_2.address() {
+            return Err(EscrowError::InvalidAddress.into());
+        }
+
+        MintInterface::check(mint_a)?;
+
+        MintInterface::check(mint_b)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        AssociatedTokenAccount::check(vault_1, escrow_1, mint_a, token_program)?;
+
+        AssociatedTokenAccount::check(vault_2, escrow_2, mint_b, token_program)?;
+
+        let config = TakeAccounts::check_config(config, treasury)?;
+
+        Ok(Self{
+            matcher,
+            maker_1,
+            escrow_1,
+            maker_2,
+            escrow_2,
+            mint_a,
+            mint_b,
+            vault_1,
+            vault_2,
+            maker_1_ata_b,
+            maker_2_ata_a,
+            matcher_ata_a,
+            matcher_ata_b,
+            config,
+            treasury,
+            treasury_ata_a,
+            treasury_ata_b,
+            system_program,
+            token_program,
+            remaining_accounts,
+        })
+    }
+}
+
+// 撮合中的一方：托管的 PDA 签名信息和要求换回的数量
+struct MatchSide {
+    seed: [u8; 8],
+    bump: [u8; 1],
+    receive: u64,
+    rent_recipient: Address,
+}
+
+impl MatchSide {
+    // 验证托管属于 maker、代币方向正确、可以与 counterparty 成交，返回当前要求换回的数量
+    fn load(escrow: &AccountView, maker: &AccountView, mint_a: &AccountView, mint_b: &AccountView, counterparty: &AccountView, now: i64) -> Result<Self, ProgramError> {
+        let data = escrow.try_borrow()?;
+        let escrow_data = Escrow::load(&data)?;
+        let escrow_key = Address::create_program_address(
+            &[
+                b"escrow",
+                maker.address().as_ref(),
+                &escrow_data.seed().to_le_bytes(),
+                &escrow_data.bump(),
+            ],
+            &crate::ID
+        )?;
+        if &escrow_key != escrow.address() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if escrow_data.mint_a() != mint_a.address() || escrow_data.mint_b() != mint_b.address() {
+            return Err(EscrowError::InvalidAddress.into());
+        }
+        if escrow_data.native()? != NativeSide::None {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if !escrow_data.can_take(counterparty.address()) || escrow_data.allowlist_root().is_some() {
+            return Err(EscrowError::InvalidTaker.into());
+        }
+        if escrow_data.is_expired(now) {
+            return Err(EscrowError::EscrowExpired.into());
+        }
+
+        Ok(Self{
+            seed: escrow_data.seed().to_le_bytes(),
+            bump: escrow_data.bump(),
+            receive: escrow_data.receive_at(now),
+            rent_recipient: *escrow_data.rent_recipient(),
+        })
+    }
+}
+
+pub struct Match<'info> {
+    pub accounts: MatchAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for Match<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = MatchAccounts::try_from(accounts)?;
+
+        // 两个创建者的 ATA 可能还不存在，由撮合者支付创建费用
+        AssociatedTokenAccount::init_if_needed(
+            accounts.maker_1_ata_b,
+            accounts.mint_b,
+            accounts.matcher,
+            accounts.maker_1,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.maker_2_ata_a,
+            accounts.mint_a,
+            accounts.matcher,
+            accounts.maker_2,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self{
+            accounts,
+        })
+    }
+}
+
+impl<'info> Match<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &25;
+
+    // 从金库转出代币，由托管 PDA 签名
+    fn transfer(&self, vault: &AccountView, mint: &AccountView, to: &AccountView, authority: &AccountView, amount: u64, signers: &[Signer]) -> ProgramResult {
+        TransferCheckedInterface {
+            from: vault,
+            mint,
+            to,
+            authority,
+            token_program: self.accounts.token_program,
+            extra_accounts: TransferHook::resolve(mint, self.accounts.remaining_accounts)?,
+            amount,
+            decimals: MintInterface::decimals(mint)?,
+        }.invoke_signed(signers)
+    }
+
+    // 按需创建收款人的 ATA，由撮合者支付
+    fn init_ata(&self, ata: &AccountView, mint: &AccountView, owner: &AccountView) -> ProgramResult {
+        AssociatedTokenAccount::init_if_needed(
+            ata,
+            mint,
+            self.accounts.matcher,
+            owner,
+            self.accounts.system_program,
+            self.accounts.token_program,
+        )
+    }
+
+    fn vault_amount(vault: &AccountView) -> Result<u64, ProgramError> {
+        let vault_data = vault.try_borrow()?;
+        Ok(u64::from_le_bytes(vault_data[64..72].try_into().unwrap()))
+    }
+
+    pub fn process(&mut self) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        let side_1 = MatchSide::load(self.accounts.escrow_1, self.accounts.maker_1, self.accounts.mint_a, self.accounts.mint_b, self.accounts.maker_2, now)?;
+        let side_2 = MatchSide::load(self.accounts.escrow_2, self.accounts.maker_2, self.accounts.mint_b, self.accounts.mint_a, self.accounts.maker_1, now)?;
+
+        // 价差：金库余额超出对方要求的部分，任何一侧不足即不交叉
+        let vault_1 = Self::vault_amount(self.accounts.vault_1)?;
+        let vault_2 = Self::vault_amount(self.accounts.vault_2)?;
+        let (Some(surplus_a), Some(surplus_b)) = (vault_1.checked_sub(side_2.receive), vault_2.checked_sub(side_1.receive)) else {
+            return Err(EscrowError::OffersDoNotCross.into());
+        };
+
+        let escrow_1_seeds = [
+            Seed::from(b"escrow"),
+            Seed::from(self.accounts.maker_1.address().as_ref()),
+            Seed::from(&side_1.seed),
+            Seed::from(&side_1.bump),
+        ];
+        let signers_1 = [Signer::from(&escrow_1_seeds)];
+        let escrow_2_seeds = [
+            Seed::from(b"escrow"),
+            Seed::from(self.accounts.maker_2.address().as_ref()),
+            Seed::from(&side_2.seed),
+            Seed::from(&side_2.bump),
+        ];
+        let signers_2 = [Signer::from(&escrow_2_seeds)];
+        let rent_recipient_1 = RentRecipient::find(&side_1.rent_recipient, self.accounts.maker_1, self.accounts.remaining_accounts)?;
+        let rent_recipient_2 = RentRecipient::find(&side_2.rent_recipient, self.accounts.maker_2, self.accounts.remaining_accounts)?;
+
+        // 协议费：每个代币只在收费一侧收取一次，没有传入 Config 时不收取
+        let (fee_side, fee_a, fee_b) = match self.accounts.config {
+            Some(config) => {
+                let data = config.try_borrow()?;
+                let config = Config::load(&data)?;
+                let fee_side = config.fee_side()?;
+                match fee_side {
+                    // 两个托管各自的代币 B：从创建者收到的数量中扣除
+                    FeeSide::TokenB => (fee_side, config.fee(side_2.receive)?, config.fee(side_1.receive)?),
+                    // 两个托管各自的代币 A：从金库释放的全部数量中扣除
+                    FeeSide::TokenA => (fee_side, config.fee(vault_1)?, config.fee(vault_2)?),
+                }
+            }
+            None => (FeeSide::TokenB, 0, 0),
+        };
+        // 协议费不超过计费的数量，减法不会溢出
+        let (maker_2_amount, matcher_amount_a, maker_1_amount, matcher_amount_b) = match fee_side {
+            FeeSide::TokenB => (side_2.receive - fee_a, surplus_a, side_1.receive - fee_b, surplus_b),
+            FeeSide::TokenA => (
+                side_2.receive,
+                surplus_a.checked_sub(fee_a).ok_or(EscrowError::OffersDoNotCross)?,
+                side_1.receive,
+                surplus_b.checked_sub(fee_b).ok_or(EscrowError::OffersDoNotCross)?,
+            ),
+        };
+
+        // 代币 A：托管 1 的金库 -> 创建者 2 / 国库 / 撮合者
+        self.transfer(self.accounts.vault_1, self.accounts.mint_a, self.accounts.maker_2_ata_a, self.accounts.escrow_1, maker_2_amount, &signers_1)?;
+        if fee_a > 0 {
+            self.init_ata(self.accounts.treasury_ata_a, self.accounts.mint_a, self.accounts.treasury)?;
+            self.transfer(self.accounts.vault_1, self.accounts.mint_a, self.accounts.treasury_ata_a, self.accounts.escrow_1, fee_a, &signers_1)?;
+        }
+        if matcher_amount_a > 0 {
+            self.init_ata(self.accounts.matcher_ata_a, self.accounts.mint_a, self.accounts.matcher)?;
+            self.transfer(self.accounts.vault_1, self.accounts.mint_a, self.accounts.matcher_ata_a, self.accounts.escrow_1, matcher_amount_a, &signers_1)?;
+        }
+
+        // 代币 B：托管 2 的金库 -> 创建者 1 / 国库 / 撮合者
+        self.transfer(self.accounts.vault_2, self.accounts.mint_b, self.accounts.maker_1_ata_b, self.accounts.escrow_2, maker_1_amount, &signers_2)?;
+        if fee_b > 0 {
+            self.init_ata(self.accounts.treasury_ata_b, self.accounts.mint_b, self.accounts.treasury)?;
+            self.transfer(self.accounts.vault_2, self.accounts.mint_b, self.accounts.treasury_ata_b, self.accounts.escrow_2, fee_b, &signers_2)?;
+        }
+        if matcher_amount_b > 0 {
+            self.init_ata(self.accounts.matcher_ata_b, self.accounts.mint_b, self.accounts.matcher)?;
+            self.transfer(self.accounts.vault_2, self.accounts.mint_b, self.accounts.matcher_ata_b, self.accounts.escrow_2, matcher_amount_b, &signers_2)?;
+        }
+
+        CloseAccountInterface {
+            account: self.accounts.vault_1,
+            destination: rent_recipient_1,
+            authority: self.accounts.escrow_1,
+            token_program: self.accounts.token_program,
+        }.invoke_signed(&signers_1)?;
+
+        CloseAccountInterface {
+            account: self.accounts.vault_2,
+            destination: rent_recipient_2,
+            authority: self.accounts.escrow_2,
+            token_program: self.accounts.token_program,
+        }.invoke_signed(&signers_2)?;
+
+        // 日志：["match", 托管 1, 托管 2, 撮合者获得的代币 A 价差, 撮合者获得的代币 B 价差]
+        ProgramLog::data(&[
+            b"match",
+            self.accounts.escrow_1.address().as_ref(),
+            self.accounts.escrow_2.address().as_ref(),
+            &matcher_amount_a.to_le_bytes(),
+            &matcher_amount_b.to_le_bytes(),
+        ]);
+
+        MarketIndex::unlist(self.accounts.remaining_accounts, self.accounts.mint_a.address(), self.accounts.mint_b.address(), self.accounts.escrow_1)?;
+        MarketIndex::unlist(self.accounts.remaining_accounts, self.accounts.mint_b.address(), self.accounts.mint_a.address(), self.accounts.escrow_2)?;
+
+        ProgramAccount::close(
+            self.accounts.escrow_1,
+            rent_recipient_1
+        )?;
+        ProgramAccount::close(
+            self.accounts.escrow_2,
+            rent_recipient_2
+        )
+    }
+}
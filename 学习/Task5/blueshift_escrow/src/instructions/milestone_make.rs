@@ -0,0 +1,201 @@
+use pinocchio::{Address, AccountView, ProgramResult};
+use pinocchio::cpi::Seed;
+use pinocchio::error::ProgramError;
+use crate::{MAX_MILESTONES, EscrowError, AccountCheck, SignerAccount, MintInterface, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, ProgramAccount, ProgramAccountInit, ProgramAccountType, MilestoneEscrow, TokenProgramInterface, TransferHook, TransferCheckedInterface};
+
+// MilestoneMake：付款方按里程碑计划一次性存入全部代币
+pub struct MilestoneMakeAccounts<'info> {
+    pub payer: &'info AccountView,
+    pub escrow: &'info AccountView,
+    pub mint: &'info AccountView,
+    pub payer_ata: &'info AccountView,
+    pub vault: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    // 剩余账户：Token-2022 转账钩子需要的账户
+    pub remaining_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for MilestoneMakeAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [payer, escrow, mint, payer_ata, vault, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(payer)?;
+
+        MintInterface::check(mint)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        AssociatedTokenAccount::check(payer_ata, payer, mint, token_program)?;
+
+        Ok(Self{
+            payer,
+            escrow,
+            mint,
+            payer_ata,
+            vault,
+            system_program,
+            token_program,
+            remaining_accounts,
+        })
+    }
+}
+
+pub struct MilestoneMakeInstructionData {
+    pub seed: u64,
+    pub payee: Address,
+    // 全 0 地址表示没有仲裁人
+    pub arbiter: Address,
+    // 各里程碑的数量，只有前 count 个有效
+    pub amounts: [u64; MAX_MILESTONES],
+    pub count: usize,
+    // 各里程碑数量之和，即存入金库的总量
+    pub total: u64,
+}
+
+impl MilestoneMakeInstructionData {
+    #[inline(always)]
+    pub fn amounts(&self) -> &[u64] {
+        &self.amounts[..self.count]
+    }
+}
+
+impl<'info> TryFrom<&'info [u8]> for MilestoneMakeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        // 数据布局：seed(8) + payee(32) + arbiter(32) + amounts(8 * count)，count 为 1..=MAX_MILESTONES
+        if data.len() < 72 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let payee = Address::new_from_array(data[8..40].try_into().unwrap());
+        let arbiter = Address::new_from_array(data[40..72].try_into().unwrap());
+
+        let chunks = data[72..].chunks_exact(8);
+        if !chunks.remainder().is_empty() || chunks.len() == 0 || chunks.len() > MAX_MILESTONES {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let count = chunks.len();
+
+        let mut amounts = [0u64; MAX_MILESTONES];
+        let mut total: u64 = 0;
+        for (slot, chunk) in amounts.iter_mut().zip(chunks) {
+            *slot = u64::from_le_bytes(chunk.try_into().unwrap());
+            // 每个里程碑都必须释放一些代币，否则批准没有意义
+            if *slot == 0 {
+                return Err(EscrowError::InvalidAmount.into());
+            }
+            total = total.checked_add(*slot).ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+
+        Ok(Self{
+            seed,
+            payee,
+            arbiter,
+            amounts,
+            count,
+            total,
+        })
+    }
+}
+
+pub struct MilestoneMake<'info> {
+    pub accounts: MilestoneMakeAccounts<'info>,
+    pub instruction_data: MilestoneMakeInstructionData,
+    pub bump: u8,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for MilestoneMake<'info> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = MilestoneMakeAccounts::try_from(accounts)?;
+        let instruction_data = MilestoneMakeInstructionData::try_from(data)?;
+
+        // 收款方和仲裁人都不能是付款方：付款方兼任仲裁人就可以随时撤回剩余代币
+        let payer = accounts.payer.address();
+        if &instruction_data.payee == payer
+            || &instruction_data.arbiter == payer
+            || instruction_data.arbiter == instruction_data.payee
+        {
+            return Err(EscrowError::InvalidAddress.into());
+        }
+
+        let (_, bump) = Address::find_program_address(
+            &[
+                b"milestone",
+                payer.as_ref(),
+                &instruction_data.seed.to_le_bytes(),
+            ],
+            &crate::ID,
+        );
+
+        let seed_binding = instruction_data.seed.to_le_bytes();
+        let bump_binding = [bump];
+        let escrow_seeds = [
+            Seed::from(b"milestone"),
+            Seed::from(payer.as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+
+        ProgramAccount::init::<MilestoneEscrow>(
+            accounts.payer,
+            accounts.escrow,
+            &escrow_seeds,
+        )?;
+
+        AssociatedTokenAccount::init(
+            accounts.vault,
+            accounts.mint,
+            accounts.payer,
+            accounts.escrow,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self{
+            accounts,
+            instruction_data,
+            bump,
+        })
+    }
+}
+
+impl<'info> MilestoneMake<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &15;
+
+    pub fn process(&mut self) -> ProgramResult {
+        {
+            let mut data = self.accounts.escrow.try_borrow_mut()?;
+            let escrow = MilestoneEscrow::load_mut(data.as_mut())?;
+            escrow.set_inner(
+                self.instruction_data.seed,
+                *self.accounts.payer.address(),
+                self.instruction_data.payee,
+                self.instruction_data.arbiter,
+                *self.accounts.mint.address(),
+                self.instruction_data.amounts(),
+                [self.bump],
+            )?;
+        }
+
+        TransferCheckedInterface{
+            from: self.accounts.payer_ata,
+            mint: self.accounts.mint,
+            to: self.accounts.vault,
+            authority: self.accounts.payer,
+            token_program: self.accounts.token_program,
+            extra_accounts: TransferHook::resolve(self.accounts.mint, self.accounts.remaining_accounts)?,
+            amount: self.instruction_data.total,
+            decimals: MintInterface::decimals(self.accounts.mint)?,
+        }.invoke()?;
+
+        Ok(())
+    }
+}
@@ -2,7 +2,59 @@ mod make;
 mod helpers;
 mod take;
 mod refund;
+mod expire;
+mod take_partial;
+mod init_config;
+mod update_config;
+mod update;
+mod htlc_lock;
+mod htlc_claim;
+mod htlc_refund;
+mod arbiter_make;
+mod release;
+mod cancel;
+mod dispute;
+mod milestone_make;
+mod approve_milestone;
+mod refund_milestones;
+mod fill_signed_order;
+mod auction_make;
+mod place_bid;
+mod settle_auction;
+mod bundle_make;
+mod bundle_take;
+mod bundle_refund;
+mod match_escrows;
+mod take_many;
+mod settle_flash_take;
+mod prune_market;
 pub use refund::*;
 pub use helpers::*;
 pub use take::*;
-pub use make::*;
\ No newline at end of file
+pub use make::*;
+pub use expire::*;
+pub use take_partial::*;
+pub use init_config::*;
+pub use update_config::*;
+pub use update::*;
+pub use htlc_lock::*;
+pub use htlc_claim::*;
+pub use htlc_refund::*;
+pub use arbiter_make::*;
+pub use release::*;
+pub use cancel::*;
+pub use dispute::*;
+pub use milestone_make::*;
+pub use approve_milestone::*;
+pub use refund_milestones::*;
+pub use fill_signed_order::*;
+pub use auction_make::*;
+pub use place_bid::*;
+pub use settle_auction::*;
+pub use bundle_make::*;
+pub use bundle_take::*;
+pub use bundle_refund::*;
+pub use match_escrows::*;
+pub use take_many::*;
+pub use settle_flash_take::*;
+pub use prune_market::*;
\ No newline at end of file
@@ -0,0 +1,253 @@
+use pinocchio::{Address, AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use crate::{AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, Auction, CloseAccountInterface, EscrowError, MintInterface, ProgramAccount, ProgramAccountCheck, ProgramAccountType, SignerAccount, TokenProgramInterface, TransferCheckedInterface, TransferHook};
+
+// 出价金库：出价 PDA ["bid", auction, bidder, bump] 的代币 B ATA
+// 出价 PDA 只作为金库的所有者，本身不存储数据；金库的租金由出价者支付，关闭时退还给出价者
+pub struct BidVault<'a> {
+    pub bid: &'a AccountView,
+    pub vault: &'a AccountView,
+    pub mint: &'a AccountView,
+    pub auction: &'a AccountView,
+    pub bidder: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub remaining_accounts: &'a [AccountView],
+}
+
+impl BidVault<'_> {
+    // 验证出价 PDA 属于 (auction, bidder)，返回 bump
+    pub fn verify(&self) -> Result<u8, ProgramError> {
+        let (bid, bump) = Address::find_program_address(
+            &[
+                b"bid",
+                self.auction.address().as_ref(),
+                self.bidder.address().as_ref(),
+            ],
+            &crate::ID,
+        );
+        if &bid != self.bid.address() {
+            return Err(EscrowError::InvalidAddress.into());
+        }
+        Ok(bump)
+    }
+
+    // 把金库中的全部代币 B 转给 to，然后关闭金库，租金退还给出价者
+    pub fn release(&self, to: &AccountView) -> ProgramResult {
+        let bump = self.verify()?;
+        AssociatedTokenAccount::check(self.vault, self.bid, self.mint, self.token_program)?;
+
+        let bump_binding = [bump];
+        let bid_seeds = [
+            Seed::from(b"bid"),
+            Seed::from(self.auction.address().as_ref()),
+            Seed::from(self.bidder.address().as_ref()),
+            Seed::from(&bump_binding),
+        ];
+        let signers = [Signer::from(&bid_seeds)];
+
+        let amount = {
+            let vault_data = self.vault.try_borrow()?;
+            u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
+        };
+        TransferCheckedInterface {
+            from: self.vault,
+            mint: self.mint,
+            to,
+            authority: self.bid,
+            token_program: self.token_program,
+            extra_accounts: TransferHook::resolve(self.mint, self.remaining_accounts)?,
+            amount,
+            decimals: MintInterface::decimals(self.mint)?,
+        }.invoke_signed(&signers)?;
+
+        CloseAccountInterface {
+            account: self.vault,
+            destination: self.bidder,
+            authority: self.bid,
+            token_program: self.token_program,
+        }.invoke_signed(&signers)
+    }
+}
+
+// PlaceBid：出价（代币 B 的总数量）
+// 新出价者：创建自己的出价金库并锁定全部出价，上一位最高出价者的代币 B 退还到其 ATA
+// 当前最高出价者再次出价：只把差额补进已有的出价金库
+// 没有上一位出价者或补足差额时，previous_* 四个位置传入本程序 ID 作为占位
+pub struct PlaceBidAccounts<'info> {
+    pub bidder: &'info AccountView,
+    pub auction: &'info AccountView,
+    pub mint_b: &'info AccountView,
+    pub bidder_ata_b: &'info AccountView,
+    pub bid: &'info AccountView,
+    pub bid_vault: &'info AccountView,
+    // 上一位最高出价者：接收退款和出价金库的租金
+    pub previous_bidder: &'info AccountView,
+    pub previous_bid: &'info AccountView,
+    pub previous_bid_vault: &'info AccountView,
+    // 上一位最高出价者的代币 B ATA（按需创建，由新出价者支付）
+    pub previous_bidder_ata_b: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    // 剩余账户：Token-2022 转账钩子需要的账户
+    pub remaining_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for PlaceBidAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [bidder, auction, mint_b, bidder_ata_b, bid, bid_vault, previous_bidder, previous_bid, previous_bid_vault, previous_bidder_ata_b, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(bidder)?;
+
+        ProgramAccount::check::<Auction>(auction)?;
+
+        MintInterface::check(mint_b)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        AssociatedTokenAccount::check(bidder_ata_b, bidder, mint_b, token_program)?;
+
+        Ok(Self{
+            bidder,
+            auction,
+            mint_b,
+            bidder_ata_b,
+            bid,
+            bid_vault,
+            previous_bidder,
+            previous_bid,
+            previous_bid_vault,
+            previous_bidder_ata_b,
+            system_program,
+            token_program,
+            remaining_accounts,
+        })
+    }
+}
+
+pub struct PlaceBidInstructionData {
+    pub amount: u64,
+}
+
+impl<'info> TryFrom<&'info [u8]> for PlaceBidInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        // 数据布局：amount(8)，出价的总数量（不是增量）
+        let Ok(amount) = <[u8; 8]>::try_from(data) else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        Ok(Self{
+            amount: u64::from_le_bytes(amount),
+        })
+    }
+}
+
+pub struct PlaceBid<'info> {
+    pub accounts: PlaceBidAccounts<'info>,
+    pub instruction_data: PlaceBidInstructionData,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for PlaceBid<'info> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        Ok(Self{
+            accounts: PlaceBidAccounts::try_from(accounts)?,
+            instruction_data: PlaceBidInstructionData::try_from(data)?,
+        })
+    }
+}
+
+impl<'info> PlaceBid<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &20;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let amount = self.instruction_data.amount;
+
+        // 托管账户由本程序拥有（ProgramAccount::check），出价规则由 Auction::place_bid 检查
+        let (previous, previous_amount) = {
+            let mut data = self.accounts.auction.try_borrow_mut()?;
+            let auction = Auction::load_mut(&mut data)?;
+            if auction.mint_b() != self.accounts.mint_b.address() {
+                return Err(EscrowError::InvalidAddress.into());
+            }
+            if auction.is_ended(Clock::get()?.unix_timestamp) {
+                return Err(EscrowError::EscrowExpired.into());
+            }
+            let previous = auction.highest_bidder().copied();
+            let previous_amount = auction.highest_bid();
+            auction.place_bid(*self.accounts.bidder.address(), amount)?;
+            (previous, previous_amount)
+        };
+
+        let own = BidVault {
+            bid: self.accounts.bid,
+            vault: self.accounts.bid_vault,
+            mint: self.accounts.mint_b,
+            auction: self.accounts.auction,
+            bidder: self.accounts.bidder,
+            token_program: self.accounts.token_program,
+            remaining_accounts: self.accounts.remaining_accounts,
+        };
+        own.verify()?;
+
+        // 当前最高出价者加价：只补足差额
+        let raising = previous.as_ref() == Some(self.accounts.bidder.address());
+        let deposit = if raising { amount - previous_amount } else { amount };
+
+        // 出价金库可能已被他人抢先创建（ATA 可以由任何人创建），使用 init_if_needed
+        AssociatedTokenAccount::init_if_needed(
+            self.accounts.bid_vault,
+            self.accounts.mint_b,
+            self.accounts.bidder,
+            self.accounts.bid,
+            self.accounts.system_program,
+            self.accounts.token_program,
+        )?;
+
+        TransferCheckedInterface {
+            from: self.accounts.bidder_ata_b,
+            mint: self.accounts.mint_b,
+            to: self.accounts.bid_vault,
+            authority: self.accounts.bidder,
+            token_program: self.accounts.token_program,
+            extra_accounts: TransferHook::resolve(self.accounts.mint_b, self.accounts.remaining_accounts)?,
+            amount: deposit,
+            decimals: MintInterface::decimals(self.accounts.mint_b)?,
+        }.invoke()?;
+
+        let Some(previous) = previous.filter(|_| !raising) else {
+            return Ok(());
+        };
+
+        // 退还上一位最高出价者
+        if &previous != self.accounts.previous_bidder.address() {
+            return Err(EscrowError::InvalidAddress.into());
+        }
+        AssociatedTokenAccount::init_if_needed(
+            self.accounts.previous_bidder_ata_b,
+            self.accounts.mint_b,
+            self.accounts.bidder,
+            self.accounts.previous_bidder,
+            self.accounts.system_program,
+            self.accounts.token_program,
+        )?;
+        BidVault {
+            bid: self.accounts.previous_bid,
+            vault: self.accounts.previous_bid_vault,
+            mint: self.accounts.mint_b,
+            auction: self.accounts.auction,
+            bidder: self.accounts.previous_bidder,
+            token_program: self.accounts.token_program,
+            remaining_accounts: self.accounts.remaining_accounts,
+        }.release(self.accounts.previous_bidder_ata_b)
+    }
+}
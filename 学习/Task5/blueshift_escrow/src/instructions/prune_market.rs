@@ -0,0 +1,149 @@
+use pinocchio::{Address, AccountView, ProgramResult};
+use pinocchio::cpi::Seed;
+use pinocchio::error::ProgramError;
+use crate::{Escrow, EscrowError, Market, ProgramAccount, ProgramAccountCheck, ProgramAccountInit, ProgramAccountType};
+
+// Market 挂单索引的账户操作：Make 挂单，Update / TakePartial 重新挂单，关闭托管的指令撤单，PruneMarket 清理
+// Market 是可选账户，由调用者放在剩余账户中传入，不改变各指令的固定账户列表
+// Make 时没有传入 Market 的托管不挂单，之后什么也不做；挂过单的托管（Escrow::is_listed）之后必须传入 Market
+pub struct MarketIndex;
+
+impl MarketIndex {
+    // 在剩余账户中查找交易对已经存在的 Market
+    // Market 只能由本程序在正确的 PDA 上创建，检查账户类型和交易对即可，不需要重新派生地址
+    pub fn find<'a>(remaining_accounts: &'a [AccountView], mint_a: &Address, mint_b: &Address) -> Option<&'a AccountView> {
+        remaining_accounts.iter().find(|account| {
+            ProgramAccount::check::<Market>(account).is_ok()
+                && account.try_borrow().is_ok_and(|data| {
+                    Market::load(&data).is_ok_and(|market| market.mint_a() == mint_a && market.mint_b() == mint_b)
+                })
+        })
+    }
+
+    // 挂单：Market 不存在时由 payer 创建，返回是否传入了 Market（托管的挂单标记）
+    pub fn list(remaining_accounts: &[AccountView], payer: &AccountView, mint_a: &AccountView, mint_b: &AccountView, escrow: &Address, receive: u64, amount: u64) -> Result<bool, ProgramError> {
+        let market = match Self::find(remaining_accounts, mint_a.address(), mint_b.address()) {
+            Some(market) => market,
+            None => {
+                // 只有剩余账户中有可写的空账户时才可能是待创建的 Market，不挂单的 Make 不必派生 PDA
+                if !remaining_accounts.iter().any(|account| account.is_writable() && !account.is_signer() && account.is_data_empty()) {
+                    return Ok(false);
+                }
+                let (market_key, market_bump) = Address::find_program_address(
+                    &[
+                        b"market",
+                        mint_a.address().as_ref(),
+                        mint_b.address().as_ref(),
+                    ],
+                    &crate::ID,
+                );
+                let Some(market) = remaining_accounts.iter().find(|account| account.address() == &market_key) else {
+                    return Ok(false);
+                };
+                let bump_binding = [market_bump];
+                let market_seeds = [
+                    Seed::from(b"market"),
+                    Seed::from(mint_a.address().as_ref()),
+                    Seed::from(mint_b.address().as_ref()),
+                    Seed::from(&bump_binding),
+                ];
+                ProgramAccount::init::<Market>(
+                    payer,
+                    market,
+                    &market_seeds,
+                )?.set_inner(*mint_a.address(), *mint_b.address(), bump_binding);
+                market
+            }
+        };
+
+        // 已满且不比最差的挂单便宜时不加入索引，托管本身不受影响
+        Market::load_mut(&mut market.try_borrow_mut()?)?.insert(*escrow, receive, amount);
+        Ok(true)
+    }
+
+    // 重新挂单：托管的期望数量或剩余数量变化后按新的价格重新排序
+    // 托管之前被挤出索引时，价格足够便宜也会重新加入
+    pub fn relist(remaining_accounts: &[AccountView], mint_a: &Address, mint_b: &Address, escrow: &AccountView, receive: u64, amount: u64) -> ProgramResult {
+        if let Some(market) = Self::listed_market(remaining_accounts, mint_a, mint_b, escrow)? {
+            Market::load_mut(&mut market.try_borrow_mut()?)?.insert(*escrow.address(), receive, amount);
+        }
+        Ok(())
+    }
+
+    // 撤单：托管不在索引中（已被挤掉或已被清理）时什么也不做
+    pub fn unlist(remaining_accounts: &[AccountView], mint_a: &Address, mint_b: &Address, escrow: &AccountView) -> ProgramResult {
+        if let Some(market) = Self::listed_market(remaining_accounts, mint_a, mint_b, escrow)? {
+            Market::load_mut(&mut market.try_borrow_mut()?)?.remove(escrow.address());
+        }
+        Ok(())
+    }
+
+    // 挂过单的托管返回交易对的 Market，剩余账户中没有 Market 时失败；没有挂过单的托管返回 None
+    fn listed_market<'a>(remaining_accounts: &'a [AccountView], mint_a: &Address, mint_b: &Address, escrow: &AccountView) -> Result<Option<&'a AccountView>, ProgramError> {
+        if !Escrow::load(&escrow.try_borrow()?)?.is_listed() {
+            return Ok(None);
+        }
+        Self::find(remaining_accounts, mint_a, mint_b)
+            .map(Some)
+            .ok_or(EscrowError::MissingMarket.into())
+    }
+}
+
+// PruneMarket：从索引中移除已经关闭的托管，任何人都可以调用
+// 挂过单的托管关闭时必须传入 Market；没有挂单标记的旧托管关闭后不会撤单，会一直占用挂单位置，直到被清理
+// 账户：[market, 要检查的托管...]，仍然存在的托管和不在索引中的账户会被忽略
+pub struct PruneMarketAccounts<'info> {
+    pub market: &'info AccountView,
+    pub escrows: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for PruneMarketAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [market, escrows @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        ProgramAccount::check::<Market>(market)?;
+
+        Ok(Self{
+            market,
+            escrows,
+        })
+    }
+}
+
+pub struct PruneMarket<'info> {
+    pub accounts: PruneMarketAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for PruneMarket<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        Ok(Self{
+            accounts: PruneMarketAccounts::try_from(accounts)?,
+        })
+    }
+}
+
+impl<'info> PruneMarket<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &28;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut data = self.accounts.market.try_borrow_mut()?;
+        let market = Market::load_mut(&mut data)?;
+
+        for escrow in self.accounts.escrows {
+            if !market.contains(escrow.address()) {
+                continue;
+            }
+            // 托管已关闭（账户被回收或只剩 Closed 标记）
+            if ProgramAccount::check::<Escrow>(escrow).is_err() {
+                market.remove(escrow.address());
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,9 +1,8 @@
 use pinocchio::{AccountView, ProgramResult};
 use pinocchio::cpi::{Seed, Signer};
 use pinocchio::error::ProgramError;
-use pinocchio_token::instructions::{CloseAccount, Transfer};
 use solana_address::Address;
-use crate::{AccountCheck, AccountClose, AssociatedTokenAccount, AssociatedTokenAccountInit, Escrow, MintInterface, ProgramAccount, SignerAccount};
+use crate::{NativeSide, AccountCheck, AccountClose, AssociatedTokenAccount, AssociatedTokenAccountInit, CloseAccountInterface, Escrow, MarketIndex, MintInterface, NativeVault, RentRecipient, ProgramAccount, ProgramAccountCheck, ProgramAccountType, SignerAccount, TokenProgramInterface, TransferCheckedInterface, TransferHook};
 pub struct RefundAccount<'info> {
     pub maker: &'info AccountView,
     pub escrow: &'info AccountView,
@@ -12,19 +11,27 @@ pub struct RefundAccount<'info> {
     pub maker_ata_a: &'info AccountView,
     pub system_program: &'info AccountView,
     pub token_program: &'info AccountView,
+    // 剩余账户：Token-2022 转账钩子需要的账户
+    // 可选：交易对的挂单索引（Market），托管关闭时从中移除
+    pub remaining_accounts: &'info [AccountView],
+    // 托管中记录的原生 SOL 标记
+    pub native: NativeSide,
 }
 impl<'info> TryFrom<&'info [AccountView]> for RefundAccount<'info> { 
     type Error = ProgramError;
     fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
-        let [maker, escrow, mint_a, vault, maker_ata_a, system_program, token_program,_] = accounts else {
+        let [maker, escrow, mint_a, vault, maker_ata_a, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
         SignerAccount::check(maker)?;
 
-        ProgramAccount::check(escrow)?;
+        ProgramAccount::check::<Escrow>(escrow)?;
+
+        let native = Escrow::load(&escrow.try_borrow()?)?.native()?;
 
         MintInterface::check(mint_a)?;
 
+        TokenProgramInterface::check(token_program)?;
 
         Ok(Self{
             maker,
@@ -34,6 +41,8 @@ impl<'info> TryFrom<&'info [AccountView]> for RefundAccount<'info> {
             maker_ata_a,
             system_program,
             token_program,
+            remaining_accounts,
+            native,
         })
     }
 }
@@ -46,14 +55,17 @@ impl<'info> TryFrom<&'info [AccountView]> for crate::Refund<'info> {
     type Error = ProgramError;
     fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
         let accounts = RefundAccount::try_from(accounts)?;
-        AssociatedTokenAccount::init_if_needed(
-            accounts.maker_ata_a, 
-            accounts.mint_a,
-            accounts.maker,
-            accounts.maker, 
-	    accounts.system_program,
-            accounts.token_program, 
+        // 代币 A 为原生 SOL 时关闭托管账户即可退还，不需要 ATA
+        if accounts.native != NativeSide::TokenA {
+            AssociatedTokenAccount::init_if_needed(
+                accounts.maker_ata_a, 
+                accounts.mint_a,
+                accounts.maker,
+                accounts.maker, 
+                accounts.system_program,
+                accounts.token_program, 
             )?;
+        }
         Ok(Self{
             accounts,
         })
@@ -64,22 +76,22 @@ impl<'info> Refund<'info> {
     pub const DISCRIMINATOR: &'info u8 = &2;
 
     pub fn process(&mut self) -> ProgramResult {
-        let (seed,bump) = {
+        let (seed, bump, mint_a, mint_b, rent_recipient) = {
             let data = self.accounts.escrow.try_borrow()?;
             let escrow = Escrow::load(&data)?;
             let escrow_key = Address::create_program_address(
                 &[
                     b"escrow",
                     self.accounts.maker.address().as_ref(),
-                    &escrow.seed.to_le_bytes(),
-                    &escrow.bump,
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump(),
                 ],
                 &crate::ID
             )?;
             if &escrow_key != self.accounts.escrow.address() {
                 return Err(ProgramError::InvalidAccountOwner);
             }
-            (escrow.seed, escrow.bump)
+            (escrow.seed(), escrow.bump(), *escrow.mint_a(), *escrow.mint_b(), *escrow.rent_recipient())
         };
         let seed_binding = seed.to_le_bytes();
         let bump_binding = bump;
@@ -89,27 +101,41 @@ impl<'info> Refund<'info> {
             Seed::from(&seed_binding),
             Seed::from(&bump_binding),
         ];
-        let signer = Signer::from(&escrow_seeds);
-        let amount = {
-            let vault_data = self.accounts.vault.try_borrow()?;
-            u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
-        };
-        Transfer {
-            from: self.accounts.vault,
-            to: self.accounts.maker_ata_a,
-            authority: self.accounts.escrow,
-            amount,
-         }.invoke_signed(&[signer.clone()])?; 
+        let signers = [Signer::from(&escrow_seeds)];
+        // 金库和托管账户的租金退还给 Make 时的支付者
+        let rent_recipient = RentRecipient::find(&rent_recipient, self.accounts.maker, self.accounts.remaining_accounts)?;
+        if self.accounts.native != NativeSide::TokenA {
+            let amount = {
+                let vault_data = self.accounts.vault.try_borrow()?;
+                u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
+            };
+            TransferCheckedInterface {
+                from: self.accounts.vault,
+                mint: self.accounts.mint_a,
+                to: self.accounts.maker_ata_a,
+                authority: self.accounts.escrow,
+                token_program: self.accounts.token_program,
+                extra_accounts: TransferHook::resolve(self.accounts.mint_a, self.accounts.remaining_accounts)?,
+                amount,
+                decimals: MintInterface::decimals(self.accounts.mint_a)?,
+             }.invoke_signed(&signers)?; 
+
+            CloseAccountInterface {
+                account: self.accounts.vault,
+                destination: rent_recipient,
+                authority: self.accounts.escrow,
+                token_program: self.accounts.token_program,
+            }.invoke_signed(&signers)?;
+        } else {
+            // 原生 SOL 的存款在托管账户中：先退还给创建者，剩余的租金在关闭时退还给 rent_recipient
+            NativeVault::withdraw(self.accounts.escrow, self.accounts.maker, NativeVault::amount(self.accounts.escrow)?)?;
+        }
 
-        CloseAccount {
-            account: self.accounts.vault,
-            destination: self.accounts.maker,
-            authority: self.accounts.escrow,
-        }.invoke_signed(&[signer.clone()])?;
+        MarketIndex::unlist(self.accounts.remaining_accounts, &mint_a, &mint_b, self.accounts.escrow)?;
 
         ProgramAccount::close(
             self.accounts.escrow,
-            self.accounts.maker
+            rent_recipient
         )?;
         Ok(())
     }
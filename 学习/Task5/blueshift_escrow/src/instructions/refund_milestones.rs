@@ -0,0 +1,147 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use solana_address::Address;
+use crate::{AccountCheck, AccountClose, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, CloseAccountInterface, EscrowError, MilestoneEscrow, MintInterface, ProgramAccount, ProgramAccountCheck, ProgramAccountType, SignerAccount, TokenProgramInterface, TransferCheckedInterface, TransferHook};
+
+// RefundMilestones：把尚未释放的剩余代币退还给付款方并关闭托管
+// 没有仲裁人时由付款方签名，设置了仲裁人时只有仲裁人可以签名
+pub struct RefundMilestonesAccounts<'info> {
+    // 授权者：付款方或仲裁人（见 MilestoneEscrow::can_refund）
+    pub authority: &'info AccountView,
+    pub payer: &'info AccountView,
+    pub escrow: &'info AccountView,
+    pub mint: &'info AccountView,
+    pub vault: &'info AccountView,
+    pub payer_ata: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    // 剩余账户：Token-2022 转账钩子需要的账户
+    pub remaining_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for RefundMilestonesAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, payer, escrow, mint, vault, payer_ata, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        ProgramAccount::check::<MilestoneEscrow>(escrow)?;
+
+        MintInterface::check(mint)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        AssociatedTokenAccount::check(vault, escrow, mint, token_program)?;
+
+        Ok(Self{
+            authority,
+            payer,
+            escrow,
+            mint,
+            vault,
+            payer_ata,
+            system_program,
+            token_program,
+            remaining_accounts,
+        })
+    }
+}
+
+pub struct RefundMilestones<'info> {
+    pub accounts: RefundMilestonesAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for RefundMilestones<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = RefundMilestonesAccounts::try_from(accounts)?;
+
+        // 付款方的 ATA 可能已经被关闭，由授权者支付创建费用
+        AssociatedTokenAccount::init_if_needed(
+            accounts.payer_ata,
+            accounts.mint,
+            accounts.authority,
+            accounts.payer,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self{
+            accounts,
+        })
+    }
+}
+
+impl<'info> RefundMilestones<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &17;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump) = {
+            let data = self.accounts.escrow.try_borrow()?;
+            let escrow = MilestoneEscrow::load(&data)?;
+            let escrow_key = Address::create_program_address(
+                &[
+                    b"milestone",
+                    self.accounts.payer.address().as_ref(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump(),
+                ],
+                &crate::ID
+            )?;
+            if &escrow_key != self.accounts.escrow.address() {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+            if escrow.mint() != self.accounts.mint.address() {
+                return Err(EscrowError::InvalidAddress.into());
+            }
+            if !escrow.can_refund(self.accounts.authority.address()) {
+                return Err(EscrowError::InvalidAuthority.into());
+            }
+            (escrow.seed(), escrow.bump())
+        };
+
+        let seed_binding = seed.to_le_bytes();
+        let bump_binding = bump;
+        let escrow_seeds = [
+            Seed::from(b"milestone"),
+            Seed::from(self.accounts.payer.address().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+        let signers = [Signer::from(&escrow_seeds)];
+
+        // 金库余额就是尚未释放的里程碑之和
+        let amount = {
+            let vault_data = self.accounts.vault.try_borrow()?;
+            u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
+        };
+        TransferCheckedInterface {
+            from: self.accounts.vault,
+            mint: self.accounts.mint,
+            to: self.accounts.payer_ata,
+            authority: self.accounts.escrow,
+            token_program: self.accounts.token_program,
+            extra_accounts: TransferHook::resolve(self.accounts.mint, self.accounts.remaining_accounts)?,
+            amount,
+            decimals: MintInterface::decimals(self.accounts.mint)?,
+        }.invoke_signed(&signers)?;
+
+        CloseAccountInterface {
+            account: self.accounts.vault,
+            destination: self.accounts.payer,
+            authority: self.accounts.escrow,
+            token_program: self.accounts.token_program,
+        }.invoke_signed(&signers)?;
+
+        ProgramAccount::close(
+            self.accounts.escrow,
+            self.accounts.payer
+        )
+    }
+}
@@ -0,0 +1,159 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use solana_address::Address;
+use crate::{AccountCheck, AccountClose, ArbiterEscrow, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, CloseAccountInterface, EscrowError, MintInterface, ProgramAccount, ProgramAccountCheck, ProgramAccountType, SignerAccount, TokenProgramInterface, TransferCheckedInterface, TransferHook};
+
+// Release / Cancel 共用的账户：授权者签名，把金库全部余额转给收款方并关闭托管
+// Release 的收款方是卖方，Cancel 的收款方是买方；租金始终退还给买方
+pub struct ArbiterSettleAccounts<'info> {
+    // 授权者：买方、卖方或仲裁人（见 ArbiterEscrow::can_release / can_cancel）
+    pub authority: &'info AccountView,
+    pub buyer: &'info AccountView,
+    pub seller: &'info AccountView,
+    pub escrow: &'info AccountView,
+    pub mint: &'info AccountView,
+    pub vault: &'info AccountView,
+    // 收款方的 ATA：Release 为卖方，Cancel 为买方
+    pub recipient_ata: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    // 剩余账户：Token-2022 转账钩子需要的账户
+    pub remaining_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for ArbiterSettleAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, buyer, seller, escrow, mint, vault, recipient_ata, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        ProgramAccount::check::<ArbiterEscrow>(escrow)?;
+
+        MintInterface::check(mint)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        AssociatedTokenAccount::check(vault, escrow, mint, token_program)?;
+
+        Ok(Self{
+            authority,
+            buyer,
+            seller,
+            escrow,
+            mint,
+            vault,
+            recipient_ata,
+            system_program,
+            token_program,
+            remaining_accounts,
+        })
+    }
+}
+
+impl<'info> ArbiterSettleAccounts<'info> {
+    // 校验托管 PDA 与记录的卖方、mint，并用 authorized 判断授权者是否有权结算
+    // 返回 PDA 的 seed 和 bump
+    pub fn verify(&self, authorized: fn(&ArbiterEscrow, &Address) -> bool) -> Result<(u64, [u8; 1]), ProgramError> {
+        let data = self.escrow.try_borrow()?;
+        let escrow = ArbiterEscrow::load(&data)?;
+        // PDA 由买方地址派生，验证通过即说明传入的 buyer 就是托管的买方
+        let escrow_key = Address::create_program_address(
+            &[
+                b"arbiter_escrow",
+                self.buyer.address().as_ref(),
+                &escrow.seed().to_le_bytes(),
+                &escrow.bump(),
+            ],
+            &crate::ID
+        )?;
+        if &escrow_key != self.escrow.address() {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        if escrow.seller() != self.seller.address() || escrow.mint() != self.mint.address() {
+            return Err(EscrowError::InvalidAddress.into());
+        }
+        if !authorized(escrow, self.authority.address()) {
+            return Err(EscrowError::InvalidAuthority.into());
+        }
+        Ok((escrow.seed(), escrow.bump()))
+    }
+
+    // 把金库全部余额转给 recipient（ATA 不存在时由授权者支付创建费用），然后关闭金库和托管
+    pub fn pay_out(&self, recipient: &AccountView, seed: u64, bump: [u8; 1]) -> ProgramResult {
+        AssociatedTokenAccount::init_if_needed(
+            self.recipient_ata,
+            self.mint,
+            self.authority,
+            recipient,
+            self.system_program,
+            self.token_program,
+        )?;
+
+        let seed_binding = seed.to_le_bytes();
+        let bump_binding = bump;
+        let escrow_seeds = [
+            Seed::from(b"arbiter_escrow"),
+            Seed::from(self.buyer.address().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+        let signers = [Signer::from(&escrow_seeds)];
+
+        let amount = {
+            let vault_data = self.vault.try_borrow()?;
+            u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
+        };
+        TransferCheckedInterface {
+            from: self.vault,
+            mint: self.mint,
+            to: self.recipient_ata,
+            authority: self.escrow,
+            token_program: self.token_program,
+            extra_accounts: TransferHook::resolve(self.mint, self.remaining_accounts)?,
+            amount,
+            decimals: MintInterface::decimals(self.mint)?,
+        }.invoke_signed(&signers)?;
+
+        CloseAccountInterface {
+            account: self.vault,
+            destination: self.buyer,
+            authority: self.escrow,
+            token_program: self.token_program,
+        }.invoke_signed(&signers)?;
+
+        ProgramAccount::close(
+            self.escrow,
+            self.buyer
+        )
+    }
+}
+
+// Release：把代币释放给卖方
+// 买方确认交付后调用；发起争议后仲裁人也可以调用
+pub struct Release<'info> {
+    pub accounts: ArbiterSettleAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for Release<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        Ok(Self{
+            accounts: ArbiterSettleAccounts::try_from(accounts)?,
+        })
+    }
+}
+
+impl<'info> Release<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &12;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump) = self.accounts.verify(ArbiterEscrow::can_release)?;
+        self.accounts.pay_out(self.accounts.seller, seed, bump)
+    }
+}
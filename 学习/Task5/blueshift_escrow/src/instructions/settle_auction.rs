@@ -0,0 +1,190 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use solana_address::Address;
+use crate::{AccountCheck, AccountClose, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, Auction, BidVault, CloseAccountInterface, EscrowError, MintInterface, ProgramAccount, ProgramAccountCheck, ProgramAccountType, SignerAccount, TokenProgramInterface, TransferCheckedInterface, TransferHook};
+
+// SettleAuction：拍卖结束后任何人都可以结算
+// 代币 A 给最高出价者，出价金库中的代币 B 给创建者；没有人出价时代币 A 退还给创建者
+// 金库和拍卖账户的租金退还给创建者，出价金库的租金退还给最高出价者
+pub struct SettleAuctionAccounts<'info> {
+    // 结算者：支付按需创建 ATA 的费用
+    pub authority: &'info AccountView,
+    pub maker: &'info AccountView,
+    pub auction: &'info AccountView,
+    pub mint_a: &'info AccountView,
+    pub mint_b: &'info AccountView,
+    pub vault: &'info AccountView,
+    // 最高出价者；没有人出价时传入创建者
+    pub winner: &'info AccountView,
+    pub winner_ata_a: &'info AccountView,
+    // 最高出价者的出价 PDA 和出价金库；没有人出价时传入本程序 ID 作为占位
+    pub bid: &'info AccountView,
+    pub bid_vault: &'info AccountView,
+    pub maker_ata_b: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    // 剩余账户：Token-2022 转账钩子需要的账户（mint_a 与 mint_b 的钩子账户都放在这里）
+    pub remaining_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for SettleAuctionAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, maker, auction, mint_a, mint_b, vault, winner, winner_ata_a, bid, bid_vault, maker_ata_b, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        ProgramAccount::check::<Auction>(auction)?;
+
+        MintInterface::check(mint_a)?;
+
+        MintInterface::check(mint_b)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        AssociatedTokenAccount::check(vault, auction, mint_a, token_program)?;
+
+        Ok(Self{
+            authority,
+            maker,
+            auction,
+            mint_a,
+            mint_b,
+            vault,
+            winner,
+            winner_ata_a,
+            bid,
+            bid_vault,
+            maker_ata_b,
+            system_program,
+            token_program,
+            remaining_accounts,
+        })
+    }
+}
+
+pub struct SettleAuction<'info> {
+    pub accounts: SettleAuctionAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for SettleAuction<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = SettleAuctionAccounts::try_from(accounts)?;
+
+        // 获胜者的代币 A ATA 可能还不存在，由结算者支付创建费用
+        AssociatedTokenAccount::init_if_needed(
+            accounts.winner_ata_a,
+            accounts.mint_a,
+            accounts.authority,
+            accounts.winner,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self{
+            accounts,
+        })
+    }
+}
+
+impl<'info> SettleAuction<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &21;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump, has_bids) = {
+            let data = self.accounts.auction.try_borrow()?;
+            let auction = Auction::load(&data)?;
+            // PDA 由创建者地址派生，验证通过即说明传入的 maker 就是拍卖的创建者
+            let auction_key = Address::create_program_address(
+                &[
+                    b"auction",
+                    self.accounts.maker.address().as_ref(),
+                    &auction.seed().to_le_bytes(),
+                    &auction.bump(),
+                ],
+                &crate::ID
+            )?;
+            if &auction_key != self.accounts.auction.address() {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+            if auction.mint_a() != self.accounts.mint_a.address() || auction.mint_b() != self.accounts.mint_b.address() {
+                return Err(EscrowError::InvalidAddress.into());
+            }
+            if !auction.is_ended(Clock::get()?.unix_timestamp) {
+                return Err(EscrowError::EscrowNotExpired.into());
+            }
+            let winner = auction.highest_bidder().unwrap_or(auction.maker());
+            if winner != self.accounts.winner.address() {
+                return Err(EscrowError::InvalidAddress.into());
+            }
+            (auction.seed(), auction.bump(), auction.highest_bidder().is_some())
+        };
+
+        let seed_binding = seed.to_le_bytes();
+        let bump_binding = bump;
+        let auction_seeds = [
+            Seed::from(b"auction"),
+            Seed::from(self.accounts.maker.address().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+        let signers = [Signer::from(&auction_seeds)];
+
+        // 代币 A：拍卖金库 -> 获胜者
+        let amount = {
+            let vault_data = self.accounts.vault.try_borrow()?;
+            u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
+        };
+        TransferCheckedInterface {
+            from: self.accounts.vault,
+            mint: self.accounts.mint_a,
+            to: self.accounts.winner_ata_a,
+            authority: self.accounts.auction,
+            token_program: self.accounts.token_program,
+            extra_accounts: TransferHook::resolve(self.accounts.mint_a, self.accounts.remaining_accounts)?,
+            amount,
+            decimals: MintInterface::decimals(self.accounts.mint_a)?,
+        }.invoke_signed(&signers)?;
+
+        CloseAccountInterface {
+            account: self.accounts.vault,
+            destination: self.accounts.maker,
+            authority: self.accounts.auction,
+            token_program: self.accounts.token_program,
+        }.invoke_signed(&signers)?;
+
+        // 代币 B：出价金库 -> 创建者
+        if has_bids {
+            AssociatedTokenAccount::init_if_needed(
+                self.accounts.maker_ata_b,
+                self.accounts.mint_b,
+                self.accounts.authority,
+                self.accounts.maker,
+                self.accounts.system_program,
+                self.accounts.token_program,
+            )?;
+            BidVault {
+                bid: self.accounts.bid,
+                vault: self.accounts.bid_vault,
+                mint: self.accounts.mint_b,
+                auction: self.accounts.auction,
+                bidder: self.accounts.winner,
+                token_program: self.accounts.token_program,
+                remaining_accounts: self.accounts.remaining_accounts,
+            }.release(self.accounts.maker_ata_b)?;
+        }
+
+        ProgramAccount::close(
+            self.accounts.auction,
+            self.accounts.maker
+        )
+    }
+}
@@ -0,0 +1,96 @@
+use pinocchio::{Address, AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::instructions::{Instructions, INSTRUCTIONS_ID};
+use pinocchio::sysvars::Sysvar;
+use crate::{AccountClose, EscrowError, ProgramAccount, TakeAccounts, TakeInstructionData};
+
+// SettleFlashTake：闪电成交的第二步，支付代币 B 并关闭托管
+// 账户列表与 Take 完全相同；指令数据为可选的 Merkle 证明（格式与 Take 相同）
+//
+// 闪电成交的流程（同一笔交易中）：
+//   1. Take（flash = 1）：代币 A 从金库转给接受者，托管保持打开，金库余额为 0
+//   2. 接受者在其他地方卖出代币 A，换到代币 B
+//   3. SettleFlashTake：接受者把 receive 个代币 B 支付给创建者，关闭金库和托管
+// Take 通过 Instructions sysvar 检查交易中随后有第 3 步，缺少第 3 步或第 3 步失败时整笔交易回滚
+// 托管仍然存在而金库余额为 0 只会出现在第 1 步与第 3 步之间，因此第 3 步以此判断第 1 步已执行
+pub struct SettleFlashTake<'info> {
+    pub accounts: TakeAccounts<'info>,
+    pub proof: &'info [u8],
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for SettleFlashTake<'info> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = TakeAccounts::try_from(accounts)?;
+        let (proof, None, false) = TakeInstructionData::split_proof(data)? else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+        accounts.init_atas()?;
+        Ok(Self {
+            accounts,
+            proof,
+        })
+    }
+}
+
+impl<'info> SettleFlashTake<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &27;
+
+    // 检查当前指令之后有一条本程序的 SettleFlashTake，且结算的是同一个托管（账户列表第 3 个）
+    // Instructions sysvar 从剩余账户中查找
+    pub fn check_follows(remaining_accounts: &[AccountView], escrow: &Address) -> ProgramResult {
+        let instructions = remaining_accounts
+            .iter()
+            .find(|account| account.address() == &INSTRUCTIONS_ID)
+            .ok_or(EscrowError::InvalidFlashTake)?;
+        let instructions = Instructions::try_from(instructions)?;
+
+        let current = instructions.load_current_index() as usize;
+        let settled = (current + 1..instructions.num_instructions()).any(|index| {
+            instructions.load_instruction_at(index).is_ok_and(|instruction| {
+                instruction.get_program_id() == &crate::ID
+                    && instruction.get_instruction_data().first() == Some(Self::DISCRIMINATOR)
+                    && instruction.get_instruction_account_at(2).is_ok_and(|account| &account.key == escrow)
+            })
+        });
+        if !settled {
+            return Err(EscrowError::InvalidFlashTake.into());
+        }
+        Ok(())
+    }
+
+    pub fn process(&mut self) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        let (seed, receive, bump, referral_bps) = self.accounts.load_escrow(self.proof, now)?;
+
+        // 金库余额不为 0 说明没有执行过闪电成交的第 1 步
+        if self.accounts.vault_amount()? != 0 {
+            return Err(EscrowError::InvalidFlashTake.into());
+        }
+
+        let seed_binding = seed.to_le_bytes();
+        let bump_binding = bump;
+        let escrow_seeds = [
+            Seed::from(b"escrow"),
+            Seed::from(self.accounts.maker.address().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+        let signers = [Signer::from(&escrow_seeds)];
+
+        // 同一交易中时钟不变，receive 与第 1 步检查 max_pay 时的数量相同
+        self.accounts.settle_b(receive, referral_bps)?;
+
+        self.accounts.close_vault(&signers)?;
+
+        self.accounts.unlist()?;
+
+        ProgramAccount::close(
+            self.accounts.escrow,
+            self.accounts.rent_recipient()?
+        )
+    }
+}
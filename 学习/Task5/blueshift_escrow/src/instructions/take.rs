@@ -1,8 +1,10 @@
 use pinocchio::{Address, AccountView, ProgramResult};
 use pinocchio::cpi::{Seed, Signer};
 use pinocchio::error::ProgramError;
-use pinocchio_token::instructions::{CloseAccount, Transfer};
-use crate::{AccountCheck, SignerAccount, MintInterface, AssociatedTokenAccount, AssociatedTokenAccountCheck, ProgramAccount, AssociatedTokenAccountInit, Escrow, AccountClose};
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use pinocchio_system::instructions::Transfer;
+use crate::{apply_bps, split_fees, Config, SettleFlashTake, NativeSide, NativeVault, EscrowError, FeeSide, MerkleProof, MAX_MERKLE_PROOF_DEPTH, AccountCheck, SignerAccount, MintInterface, AssociatedTokenAccount, AssociatedTokenAccountCheck, ProgramAccount, ProgramAccountCheck, ProgramAccountType, AssociatedTokenAccountInit, Escrow, AccountClose, MarketIndex, RentRecipient, TokenProgramInterface, TransferHook, TransferCheckedInterface, CloseAccountInterface};
 pub struct TakeAccounts<'info> {
     pub taker: &'info AccountView,
 
@@ -22,29 +24,61 @@ pub struct TakeAccounts<'info> {
 
     pub maker_ata_b:&'info AccountView,
 
+    // 可选的全局配置（协议费、国库地址）：未初始化 Config 或不收协议费时，
+    // config、treasury、treasury_ata 三个位置都传入本程序 ID 作为占位，协议费按 0 计算
+    pub config: Option<&'info AccountView>,
+
+    // 国库：传入 Config 时必须与其中记录的地址一致
+    pub treasury: &'info AccountView,
+
+    // 国库在收费一侧 mint 上的 ATA（协议费不为 0 时按需创建）
+    pub treasury_ata: &'info AccountView,
+
+    // 可选的推荐人（前端、聚合器）：收取托管中设定的推荐费
+    // 推荐费由创建者承担，接受者不能把自己作为推荐人（否则推荐费就变成了接受者的折扣）
+    // 不使用推荐人时，referrer 和 referrer_ata 两个位置都传入本程序 ID 作为占位（与 Anchor 可选账户的约定相同）
+    pub referrer: Option<&'info AccountView>,
+
+    // 推荐人在 mint_b 上的 ATA（推荐费不为 0 时按需创建）
+    pub referrer_ata: Option<&'info AccountView>,
+
     pub system_program: &'info AccountView,
 
     pub token_program: &'info AccountView,
 
+    // 剩余账户：Token-2022 转账钩子需要的账户（mint_a 与 mint_b 的钩子账户都放在这里）
+    // 可选：交易对的挂单索引（Market），托管关闭时从中移除
+    pub remaining_accounts: &'info [AccountView],
+
+    // 托管中记录的原生 SOL 标记：原生一侧不使用金库 / ATA
+    pub native: NativeSide,
 }
 
 impl<'info> TryFrom<&'info [AccountView]> for TakeAccounts<'info> {
     type Error = ProgramError;
     fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error>{
-         let [taker, maker, escrow, mint_a, mint_b, vault, taker_ata_a, taker_ata_b, maker_ata_b, system_program, token_program, _] = accounts else {
+         let [taker, maker, escrow, mint_a, mint_b, vault, taker_ata_a, taker_ata_b, maker_ata_b, config, treasury, treasury_ata, referrer, referrer_ata, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
         SignerAccount::check(taker)?;
 
-        ProgramAccount::check(escrow)?;
-
         MintInterface::check(mint_a)?;
 
         MintInterface::check(mint_b)?;
 
-        AssociatedTokenAccount::check(taker_ata_b,taker,mint_b,token_program)?;
+        TokenProgramInterface::check(token_program)?;
 
-        AssociatedTokenAccount::check(vault,escrow,mint_a,token_program)?;
+        let native = Self::check_escrow(taker, escrow, mint_a, mint_b, vault, taker_ata_b, token_program)?;
+
+        let config = Self::check_config(config, treasury)?;
+
+        let (referrer, referrer_ata) = if referrer.address() == &crate::ID {
+            (None, None)
+        } else if referrer.address() == taker.address() {
+            return Err(EscrowError::InvalidReferrer.into());
+        } else {
+            (Some(referrer), Some(referrer_ata))
+        };
         Ok(Self{
             taker,
             maker,
@@ -55,40 +89,377 @@ impl<'info> TryFrom<&'info [AccountView]> for TakeAccounts<'info> {
             taker_ata_a,
             taker_ata_b,
             maker_ata_b,
+            config,
+            treasury,
+            treasury_ata,
+            referrer,
+            referrer_ata,
             system_program,
             token_program,
+            remaining_accounts,
+            native,
+        })
+    }
+}
+
+impl<'info> TakeAccounts<'info> {
+    // 单个托管的账户校验，返回托管中记录的原生 SOL 标记
+    // Take / TakePartial 在 try_from 中调用，TakeMany 对每个托管分别调用
+    pub fn check_escrow(
+        taker: &AccountView,
+        escrow: &AccountView,
+        mint_a: &AccountView,
+        mint_b: &AccountView,
+        vault: &AccountView,
+        taker_ata_b: &AccountView,
+        token_program: &AccountView,
+    ) -> Result<NativeSide, ProgramError> {
+        ProgramAccount::check::<Escrow>(escrow)?;
+
+        let native = Escrow::load(&escrow.try_borrow()?)?.native()?;
+
+        if native != NativeSide::TokenB {
+            AssociatedTokenAccount::check(taker_ata_b,taker,mint_b,token_program)?;
+        }
+
+        if native != NativeSide::TokenA {
+            AssociatedTokenAccount::check(vault,escrow,mint_a,token_program)?;
+        }
+
+        Ok(native)
+    }
+
+    // 可选的全局配置：传入本程序 ID 作为占位时返回 None（不收协议费）
+    // 否则必须是 Config PDA，国库必须与其中记录的地址一致
+    pub fn check_config<'a>(config: &'a AccountView, treasury: &AccountView) -> Result<Option<&'a AccountView>, ProgramError> {
+        if config.address() == &crate::ID {
+            return Ok(None);
+        }
+
+        ProgramAccount::check::<Config>(config)?;
+
+        let data = config.try_borrow()?;
+        let config_data = Config::load(&data)?;
+        let config_key = Address::create_program_address(
+            &[b"config", &config_data.bump()],
+            &crate::ID
+        )?;
+        if &config_key != config.address() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if config_data.treasury() != treasury.address() {
+            return Err(EscrowError::InvalidAddress.into());
+        }
+        Ok(Some(config))
+    }
+
+    // 读取并校验托管：PDA、mint、指定接受者、白名单、是否过期
+    // 返回 (seed, 当前期望的代币 B 数量, bump, 推荐费)，荷兰式拍卖按 now 计算期望数量
+    pub fn load_escrow(&self, proof: &[u8], now: i64) -> Result<(u64, u64, [u8; 1], u16), ProgramError> {
+        let data = self.escrow.try_borrow()?;
+        let escrow = Escrow::load(&data)?;
+        let escrow_key = Address::create_program_address(
+            &[
+                b"escrow",
+                self.maker.address().as_ref(),
+                &escrow.seed().to_le_bytes(),
+                &escrow.bump(),
+            ],
+            &crate::ID
+        )?;
+
+        if &escrow_key != self.escrow.address() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // 传入的 mint 必须与托管记录的一致，否则接受者可以用其他代币支付
+        if escrow.mint_a() != self.mint_a.address() || escrow.mint_b() != self.mint_b.address() {
+            return Err(EscrowError::InvalidAddress.into());
+        }
+
+        // 指定了接受者的托管只允许该地址接受，防止报价被抢跑
+        if !escrow.can_take(self.taker.address()) {
+            return Err(EscrowError::InvalidTaker.into());
+        }
+
+        // 启用了白名单的托管需要证明接受者地址在白名单中
+        if let Some(root) = escrow.allowlist_root() {
+            MerkleProof::verify(root, self.taker.address(), proof)?;
+        }
+
+        // 过期的托管不能再被接受，只能由 Expire / Refund 退还
+        if escrow.is_expired(now) {
+            return Err(EscrowError::EscrowExpired.into());
+        }
+
+        Ok((escrow.seed(), escrow.receive_at(now), escrow.bump(), escrow.referral_bps()))
+    }
+
+    // 金库中代币 A 的余额：原生 SOL 时为托管 PDA 中超出租金豁免的 lamports
+    pub fn vault_amount(&self) -> Result<u64, ProgramError> {
+        if self.native == NativeSide::TokenA {
+            return NativeVault::amount(self.escrow);
+        }
+        let vault_data = self.vault.try_borrow()?;
+        Ok(u64::from_le_bytes(vault_data[64..72].try_into().unwrap()))
+    }
+
+    // 创建接受者的代币 A ATA 和创建者的代币 B ATA（由接受者支付），原生 SOL 一侧不需要
+    pub fn init_atas(&self) -> ProgramResult {
+        if self.native != NativeSide::TokenA {
+            AssociatedTokenAccount::init_if_needed(
+                self.taker_ata_a,
+                self.mint_a,
+                self.taker,
+                self.taker,
+                self.system_program,
+                self.token_program,
+            )?;
+        }
+
+        if self.native != NativeSide::TokenB {
+            AssociatedTokenAccount::init_if_needed(
+                self.maker_ata_b,
+                self.mint_b,
+                self.taker,
+                self.maker,
+                self.system_program,
+                self.token_program,
+            )?;
+        }
+        Ok(())
+    }
+
+    // 托管中记录的租金退还对象：创建者，或者放在剩余账户中的 Make payer
+    pub fn rent_recipient(&self) -> Result<&'info AccountView, ProgramError> {
+        let data = self.escrow.try_borrow()?;
+        RentRecipient::find(Escrow::load(&data)?.rent_recipient(), self.maker, self.remaining_accounts)
+    }
+
+    // 金库收尾：SPL 金库在清空后关闭，租金退还给 rent_recipient；原生 SOL 金库就是托管账户本身
+    pub fn close_vault(&self, signers: &[Signer]) -> ProgramResult {
+        if self.native == NativeSide::TokenA {
+            return Ok(());
+        }
+        CloseAccountInterface{
+            account: self.vault,
+            destination: self.rent_recipient()?,
+            authority: self.escrow,
+            token_program: self.token_program,
+        }.invoke_signed(signers)
+    }
+
+    // 代币 B：接受者 -> 收款人
+    // 原生 SOL 直接转账到收款人的钱包（System Program），否则转到收款人的 ATA
+    fn pay_b(&self, wallet: &AccountView, ata: &AccountView, amount: u64) -> ProgramResult {
+        if self.native == NativeSide::TokenB {
+            return Transfer {
+                from: self.taker,
+                to: wallet,
+                lamports: amount,
+            }.invoke();
+        }
+        TransferCheckedInterface{
+            from: self.taker_ata_b,
+            mint: self.mint_b,
+            to: ata,
+            authority: self.taker,
+            token_program: self.token_program,
+            extra_accounts: TransferHook::resolve(self.mint_b, self.remaining_accounts)?,
+            amount,
+            decimals: MintInterface::decimals(self.mint_b)?,
+        }.invoke()
+    }
+
+    // 代币 A：金库 -> 收款人（SPL 金库由托管 PDA 签名）
+    fn pay_a(&self, wallet: &AccountView, ata: &AccountView, amount: u64, signers: &[Signer]) -> ProgramResult {
+        if self.native == NativeSide::TokenA {
+            return NativeVault::withdraw(self.escrow, wallet, amount);
+        }
+        TransferCheckedInterface{
+            from: self.vault,
+            mint: self.mint_a,
+            to: ata,
+            authority: self.escrow,
+            token_program: self.token_program,
+            extra_accounts: TransferHook::resolve(self.mint_a, self.remaining_accounts)?,
+            amount,
+            decimals: MintInterface::decimals(self.mint_a)?,
+        }.invoke_signed(signers)
+    }
+
+    // 成交结算：接受者支付 amount_b 个代币 B，金库释放 amount_a 个代币 A
+    // 传入 Config 时按其从收费一侧的成交数量中扣除协议费，转入国库的 ATA
+    // - FeeSide::TokenB：创建者收到 amount_b - fee
+    // - FeeSide::TokenA：接受者收到 amount_a - fee
+    // 传入推荐人时，再按托管的 referral_bps 从代币 B 中支付推荐费（由创建者承担）：
+    // 创建者收到 amount_b - fee_b - referral，协议费与推荐费之和超过成交数量时返回 InvalidFee
+    // 原生 SOL 一侧的款项（包括协议费和推荐费）直接以 lamports 支付给各方的钱包
+    // Take 与 TakePartial 共用，部分成交也无法绕过协议费
+    pub fn settle(&self, amount_a: u64, amount_b: u64, referral_bps: u16, signers: &[Signer]) -> ProgramResult {
+        self.settle_b(amount_b, referral_bps)?;
+        self.settle_a(amount_a, signers)
+    }
+
+    // 协议费（基点）：传入了 Config 且收费一侧的 side 与 Config 一致时才收取，否则为 0
+    fn fee_bps(&self, side: FeeSide) -> Result<u16, ProgramError> {
+        let Some(config) = self.config else {
+            return Ok(0);
+        };
+        let data = config.try_borrow()?;
+        let config = Config::load(&data)?;
+        if config.fee_side()? != side {
+            return Ok(0);
+        }
+        Ok(config.fee_bps())
+    }
+
+    // 协议费不为 0 且收费一侧不是原生 SOL 时，按需创建国库的 ATA（由接受者支付）
+    fn init_treasury_ata(&self, fee: u64, mint: &AccountView, native: NativeSide) -> ProgramResult {
+        if fee == 0 || self.native == native {
+            return Ok(());
+        }
+        AssociatedTokenAccount::init_if_needed(
+            self.treasury_ata,
+            mint,
+            self.taker,
+            self.treasury,
+            self.system_program,
+            self.token_program,
+        )
+    }
+
+    // 托管关闭前从挂单索引中移除（托管挂过单时剩余账户中必须传入 Market）
+    pub fn unlist(&self) -> ProgramResult {
+        MarketIndex::unlist(self.remaining_accounts, self.mint_a.address(), self.mint_b.address(), self.escrow)
+    }
+
+    // 部分成交后按缩放后的期望数量和金库剩余数量重新挂单（托管挂过单时剩余账户中必须传入 Market）
+    pub fn relist(&self, receive: u64, amount: u64) -> ProgramResult {
+        MarketIndex::relist(self.remaining_accounts, self.mint_a.address(), self.mint_b.address(), self.escrow, receive, amount)
+    }
+
+    // 结算代币 B 一侧：接受者 -> 创建者 / 国库 / 推荐人
+    // 闪电成交（SettleFlashTake）在取走代币 A 之后单独调用
+    pub fn settle_b(&self, amount_b: u64, referral_bps: u16) -> ProgramResult {
+        let referral_bps = match self.referrer {
+            Some(_) => referral_bps,
+            None => 0,
+        };
+        let (maker_amount_b, fee_b, referral) = split_fees(amount_b, self.fee_bps(FeeSide::TokenB)?, referral_bps)?;
+
+        self.init_treasury_ata(fee_b, self.mint_b, NativeSide::TokenB)?;
+
+        // 代币 B：接受者 -> 创建者 / 国库
+        self.pay_b(self.maker, self.maker_ata_b, maker_amount_b)?;
+
+        if fee_b > 0 {
+            self.pay_b(self.treasury, self.treasury_ata, fee_b)?;
+        }
+
+        // 推荐费：代币 B 接受者 -> 推荐人（推荐人的 ATA 按需创建，由接受者支付租金）
+        if let (Some(referrer), Some(referrer_ata)) = (self.referrer, self.referrer_ata) {
+            if referral > 0 {
+                if self.native != NativeSide::TokenB {
+                    AssociatedTokenAccount::init_if_needed(
+                        referrer_ata,
+                        self.mint_b,
+                        self.taker,
+                        referrer,
+                        self.system_program,
+                        self.token_program,
+                    )?;
+                }
+                self.pay_b(referrer, referrer_ata, referral)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // 结算代币 A 一侧：金库 -> 接受者 / 国库
+    pub fn settle_a(&self, amount_a: u64, signers: &[Signer]) -> ProgramResult {
+        let fee_a = apply_bps(amount_a, self.fee_bps(FeeSide::TokenA)?)?;
+
+        self.init_treasury_ata(fee_a, self.mint_a, NativeSide::TokenA)?;
+
+        self.pay_a(self.taker, self.taker_ata_a, amount_a - fee_a, signers)?;
+
+        if fee_a > 0 {
+            self.pay_a(self.treasury, self.treasury_ata, fee_a, signers)?;
+        }
+
+        Ok(())
+    }
+}
+
+// 指令数据：min_amount_a(8) + 可选的 Merkle 证明（托管启用了接受者白名单时必须提供）+ 可选的 max_pay(8) + 可选的 flash(1)
+// min_amount_a：接受者至少要从金库得到的代币 A 数量（扣除协议费之前），必须提供
+//   创建者可以抢在 Take 之前用 Update 取走金库中的代币，少于该数量时返回 SlippageExceeded
+// 证明格式见 MerkleProof：依次拼接的 32 字节兄弟节点哈希
+// max_pay：接受者愿意支付的代币 B 上限，防止荷兰式拍卖中交易提前落块、或者创建者用 Update 提高 receive 时按更高的价格成交
+// flash = 1：闪电成交，先取走代币 A，代币 B 由同一交易中随后的 SettleFlashTake 支付
+//   只能跟在 max_pay 之后（不限制价格时 max_pay 传 u64::MAX）
+pub struct TakeInstructionData<'info> {
+    pub min_amount_a: u64,
+    pub proof: &'info [u8],
+    pub max_pay: Option<u64>,
+    pub flash: bool,
+}
+
+impl<'info> TryFrom<&'info [u8]> for TakeInstructionData<'info> {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        let Some((min_amount_a, data)) = data.split_first_chunk::<8>() else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+        let (proof, max_pay, flash) = Self::split_proof(data)?;
+
+        Ok(Self{
+            min_amount_a: u64::from_le_bytes(*min_amount_a),
+            proof,
+            max_pay,
+            flash,
         })
     }
 }
 
+impl<'info> TakeInstructionData<'info> {
+    // 拆分 Merkle 证明与其后可选的 max_pay / flash，返回 (proof, max_pay, flash)
+    // 证明长度是 32 的倍数，余下的 8 字节就是 max_pay，9 字节则再加上 flash
+    // SettleFlashTake 的指令数据只有证明，直接使用这个函数
+    pub fn split_proof(data: &'info [u8]) -> Result<(&'info [u8], Option<u64>, bool), ProgramError> {
+        let siblings = data.chunks_exact(32);
+        let (max_pay, flash) = match siblings.remainder() {
+            [] => (None, false),
+            bytes if bytes.len() == 8 => (Some(u64::from_le_bytes(bytes.try_into().unwrap())), false),
+            [max_pay @ .., 1] if max_pay.len() == 8 => (Some(u64::from_le_bytes(max_pay.try_into().unwrap())), true),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        if siblings.len() > MAX_MERKLE_PROOF_DEPTH {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok((&data[..siblings.len() * 32], max_pay, flash))
+    }
+}
+
 pub struct Take<'info> {
     pub accounts: TakeAccounts<'info>,
+    pub instruction_data: TakeInstructionData<'info>,
 }
 
-impl<'info> TryFrom<&'info [AccountView]> for Take<'info> {
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for Take<'info> {
     type Error = ProgramError;
     
-    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
         let accounts = TakeAccounts::try_from(accounts)?;
-        AssociatedTokenAccount::init_if_needed(
-            accounts.taker_ata_a,
-            accounts.mint_a,
-            accounts.taker,
-            accounts.taker,
-            accounts.system_program,
-            accounts.token_program,
-        )?;
-
-        AssociatedTokenAccount::init_if_needed(
-            accounts.maker_ata_b,
-            accounts.mint_b,
-            accounts.taker,
-            accounts.maker,
-            accounts.system_program,
-            accounts.token_program,
-        )?;
+        let instruction_data = TakeInstructionData::try_from(data)?;
+        accounts.init_atas()?;
         Ok(Self {
             accounts,
+            instruction_data,
         })
     } 
 }
@@ -96,25 +467,13 @@ impl<'info> TryFrom<&'info [AccountView]> for Take<'info> {
 impl<'info> Take<'info> {
     pub const DISCRIMINATOR: &'info u8 = &1;
     pub fn process(&mut self) -> ProgramResult { 
-        let (seed, receive,bump) = {
-           let data = self.accounts.escrow.try_borrow()?;
-           let escrow  = Escrow::load(&data)?;
-           let escrow_key = Address::create_program_address(
-            &[
-                b"escrow",
-                self.accounts.maker.address().as_ref(),
-                &escrow.seed.to_le_bytes(),
-                &escrow.bump,
-            ],
-            &crate::ID
-           )?;
+        let now = Clock::get()?.unix_timestamp;
+        let (seed, receive, bump, referral_bps) = self.accounts.load_escrow(self.instruction_data.proof, now)?;
 
-            
-           if &escrow_key != self.accounts.escrow.address() {
-               return Err(ProgramError::InvalidArgument);
-           }
-           (escrow.seed, escrow.receive, escrow.bump)
-        };
+        // 荷兰式拍卖按当前时间计算期望数量，超过接受者的 max_pay 时拒绝成交
+        if self.instruction_data.max_pay.is_some_and(|max_pay| receive > max_pay) {
+            return Err(EscrowError::SlippageExceeded.into());
+        }
         let seed_binding = seed.to_le_bytes();
         let bump_binding = bump;
         let escrow_seeds=[
@@ -123,36 +482,34 @@ impl<'info> Take<'info> {
             Seed::from(&seed_binding),
             Seed::from(&bump_binding),
         ];
-        let signer = Signer::from(&escrow_seeds);
+        let signers = [Signer::from(&escrow_seeds)];
 
-        let amount = {
-            let vault_data = self.accounts.vault.try_borrow()?;
-            u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
-        };
+        let amount = self.accounts.vault_amount()?;
 
-        Transfer{
-            from: self.accounts.vault,
-            to: self.accounts.taker_ata_a,
-            authority: self.accounts.escrow,
-            amount,
-        }.invoke_signed(&[signer.clone()])?;
+        // 金库余额少于接受者签名时看到的数量（创建者抢先 Update 取走了代币）时拒绝成交
+        if amount < self.instruction_data.min_amount_a {
+            return Err(EscrowError::SlippageExceeded.into());
+        }
+
+        // 闪电成交：先把代币 A 交给接受者，托管保持打开（金库余额为 0）
+        // 同一交易中随后必须有针对该托管的 SettleFlashTake 支付代币 B，否则整笔交易失败
+        if self.instruction_data.flash {
+            if self.accounts.native != NativeSide::None || amount == 0 {
+                return Err(EscrowError::InvalidFlashTake.into());
+            }
+            SettleFlashTake::check_follows(self.accounts.remaining_accounts, self.accounts.escrow.address())?;
+            return self.accounts.settle_a(amount, &signers);
+        }
+
+        self.accounts.settle(amount, receive, referral_bps, &signers)?;
 
-        CloseAccount{
-            account: self.accounts.vault,
-            destination: self.accounts.maker,
-            authority: self.accounts.escrow,
-        }.invoke_signed(&[signer.clone()])?;
+        self.accounts.close_vault(&signers)?;
 
-        Transfer{
-            from: self.accounts.taker_ata_b,
-            to: self.accounts.maker_ata_b,
-            authority: self.accounts.taker,
-            amount:receive,
-        }.invoke()?;
+        self.accounts.unlist()?;
 
         ProgramAccount::close(
             self.accounts.escrow,
-            self.accounts.maker
+            self.accounts.rent_recipient()?
         )?;
         Ok(())
     }
@@ -0,0 +1,231 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use crate::{AccountCheck, AccountClose, EscrowError, MintInterface, ProgramAccount, ProgramLog, SignerAccount, TakeAccounts, TokenProgramInterface};
+
+// TakeMany：在一条指令中接受同一交易对的多个托管（聚合器一次扫掉多个更便宜的报价）
+// 固定账户之后，剩余账户按顺序传入：
+//   每个托管 [maker, escrow, vault, maker_ata_b] × count
+//   其余为 Token-2022 转账钩子需要的账户，以及可选的交易对挂单索引（Market）
+// 每个托管的校验与 Take 相同（见 TakeAccounts::check_escrow / load_escrow），不支持推荐人和白名单证明
+// 传入 Market 时成交的托管从索引中移除
+pub struct TakeManyAccounts<'info> {
+    pub taker: &'info AccountView,
+    pub mint_a: &'info AccountView,
+    pub mint_b: &'info AccountView,
+    pub taker_ata_a: &'info AccountView,
+    pub taker_ata_b: &'info AccountView,
+    // 可选的全局配置：与 Take 相同，传入本程序 ID 作为占位时不收协议费
+    pub config: Option<&'info AccountView>,
+    pub treasury: &'info AccountView,
+    pub treasury_ata: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    pub remaining_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for TakeManyAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [taker, mint_a, mint_b, taker_ata_a, taker_ata_b, config, treasury, treasury_ata, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(taker)?;
+
+        MintInterface::check(mint_a)?;
+
+        MintInterface::check(mint_b)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        let config = TakeAccounts::check_config(config, treasury)?;
+
+        Ok(Self{
+            taker,
+            mint_a,
+            mint_b,
+            taker_ata_a,
+            taker_ata_b,
+            config,
+            treasury,
+            treasury_ata,
+            system_program,
+            token_program,
+            remaining_accounts,
+        })
+    }
+}
+
+// 指令数据：max_pay(8) + min_amount_a(8) + all_or_nothing(1) + count(1)
+// - max_pay：所有托管合计愿意支付的代币 B 上限
+// - min_amount_a：所有成交的托管合计至少要释放的代币 A 数量（扣除协议费之前）
+//   防止创建者抢先 Update 取走金库中的代币，不满足时整条指令失败（与 all_or_nothing 无关）
+// - all_or_nothing = 1：任何一个托管无法成交（已被别人接受、过期、超出 max_pay 等）时整条指令失败
+//   all_or_nothing = 0：跳过无法成交的托管，只成交其余的
+// - count：剩余账户中托管的数量
+pub struct TakeManyInstructionData {
+    pub max_pay: u64,
+    pub min_amount_a: u64,
+    pub all_or_nothing: bool,
+    pub count: usize,
+}
+
+impl<'info> TryFrom<&'info [u8]> for TakeManyInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        if data.len() != 18 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let all_or_nothing = match data[16] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        let count = data[17] as usize;
+        if count == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self{
+            max_pay: u64::from_le_bytes(data[0..8].try_into().unwrap()),
+            min_amount_a: u64::from_le_bytes(data[8..16].try_into().unwrap()),
+            all_or_nothing,
+            count,
+        })
+    }
+}
+
+pub struct TakeMany<'info> {
+    pub accounts: TakeManyAccounts<'info>,
+    pub instruction_data: TakeManyInstructionData,
+    // 每个托管 [maker, escrow, vault, maker_ata_b]
+    pub groups: &'info [AccountView],
+    // Token-2022 转账钩子需要的账户，以及可选的 Market
+    pub hook_accounts: &'info [AccountView],
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for TakeMany<'info> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = TakeManyAccounts::try_from(accounts)?;
+        let instruction_data = TakeManyInstructionData::try_from(data)?;
+
+        let (groups, hook_accounts) = accounts.remaining_accounts
+            .split_at_checked(instruction_data.count * 4)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+        Ok(Self{
+            accounts,
+            instruction_data,
+            groups,
+            hook_accounts,
+        })
+    }
+}
+
+impl<'info> TakeMany<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &26;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        let mut paid = 0u64;
+        let mut released = 0u64;
+        let mut filled = 0u8;
+
+        for group in self.groups.chunks_exact(4) {
+            let [maker, escrow, vault, maker_ata_b] = group else {
+                unreachable!()
+            };
+
+            // 成交前的校验失败时，按 all_or_nothing 决定整条指令失败还是跳过该托管
+            // 一旦开始转账，任何失败都会让整条指令回滚
+            let checked = TakeAccounts::check_escrow(
+                self.accounts.taker,
+                escrow,
+                self.accounts.mint_a,
+                self.accounts.mint_b,
+                vault,
+                self.accounts.taker_ata_b,
+                self.accounts.token_program,
+            ).and_then(|native| {
+                let take = TakeAccounts {
+                    taker: self.accounts.taker,
+                    maker,
+                    escrow,
+                    mint_a: self.accounts.mint_a,
+                    mint_b: self.accounts.mint_b,
+                    vault,
+                    taker_ata_a: self.accounts.taker_ata_a,
+                    taker_ata_b: self.accounts.taker_ata_b,
+                    maker_ata_b,
+                    config: self.accounts.config,
+                    treasury: self.accounts.treasury,
+                    treasury_ata: self.accounts.treasury_ata,
+                    referrer: None,
+                    referrer_ata: None,
+                    system_program: self.accounts.system_program,
+                    token_program: self.accounts.token_program,
+                    remaining_accounts: self.hook_accounts,
+                    native,
+                };
+                let escrow_data = take.load_escrow(&[], now)?;
+                let total = paid
+                    .checked_add(escrow_data.1)
+                    .filter(|total| *total <= self.instruction_data.max_pay)
+                    .ok_or(EscrowError::SlippageExceeded)?;
+                Ok((take, escrow_data, total))
+            });
+            let (take, (seed, receive, bump, _), total) = match checked {
+                Ok(checked) => checked,
+                Err(error) if self.instruction_data.all_or_nothing => return Err(error),
+                Err(_) => continue,
+            };
+            paid = total;
+
+            let seed_binding = seed.to_le_bytes();
+            let bump_binding = bump;
+            let escrow_seeds = [
+                Seed::from(b"escrow"),
+                Seed::from(maker.address().as_ref()),
+                Seed::from(&seed_binding),
+                Seed::from(&bump_binding),
+            ];
+            let signers = [Signer::from(&escrow_seeds)];
+
+            take.init_atas()?;
+
+            let amount = take.vault_amount()?;
+
+            released += amount;
+
+            take.settle(amount, receive, 0, &signers)?;
+
+            take.close_vault(&signers)?;
+
+            take.unlist()?;
+
+            ProgramAccount::close(escrow, take.rent_recipient()?)?;
+
+            filled += 1;
+        }
+
+        if released < self.instruction_data.min_amount_a {
+            return Err(EscrowError::SlippageExceeded.into());
+        }
+
+        // 日志：["take_many", 成交的托管数量, 合计支付的代币 B]
+        ProgramLog::data(&[
+            b"take_many",
+            &[filled],
+            &paid.to_le_bytes(),
+        ]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use crate::{EscrowError, TakeInstructionData, Escrow, AccountClose, ProgramAccount, ProgramAccountType, TakeAccounts};
+
+// TakePartial：部分成交
+// 接受者只支付 amount_b 个代币 B，按比例获得金库中的代币 A（向下取整，有利于创建者）
+// 托管中剩余的 receive 相应减少；只有全部成交（receive 归零）时才关闭金库和托管账户
+// 荷兰式拍卖按成交时的期望数量（Escrow::receive_at）计算比例
+// 账户列表与 Take 完全相同
+// 指令数据：amount_b(8) + min_amount_a(8) + 可选的 Merkle 证明（格式与 Take 相同）
+// min_amount_a：这次部分成交至少要释放的代币 A 数量，防止创建者抢先 Update 取走金库中的代币
+pub struct TakePartialInstructionData<'info> {
+    pub amount_b: u64,
+    pub min_amount_a: u64,
+    pub proof: &'info [u8],
+}
+
+impl<'info> TryFrom<&'info [u8]> for TakePartialInstructionData<'info> {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        let Some((amount_b, proof)) = data.split_first_chunk::<8>() else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+        let amount_b = u64::from_le_bytes(*amount_b);
+
+        if amount_b == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // 支付数量由 amount_b 指定，不接受 max_pay 和闪电成交
+        let TakeInstructionData { min_amount_a, proof, max_pay: None, flash: false } = TakeInstructionData::try_from(proof)? else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        Ok(Self{
+            amount_b,
+            min_amount_a,
+            proof,
+        })
+    }
+}
+
+pub struct TakePartial<'info> {
+    pub accounts: TakeAccounts<'info>,
+    pub instruction_data: TakePartialInstructionData<'info>,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for TakePartial<'info> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = TakeAccounts::try_from(accounts)?;
+        let instruction_data = TakePartialInstructionData::try_from(data)?;
+        accounts.init_atas()?;
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'info> TakePartial<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &4;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let amount_b = self.instruction_data.amount_b;
+        let vault_amount = self.accounts.vault_amount()?;
+
+        let now = Clock::get()?.unix_timestamp;
+
+        let (seed, receive, bump, referral_bps) = self.accounts.load_escrow(self.instruction_data.proof, now)?;
+
+        // amount_b 超过剩余数量，或者按比例换算后得不到任何代币 A
+        let amount_a = {
+            let data = self.accounts.escrow.try_borrow()?;
+            Escrow::load(&data)?
+                .fill_amount_a(vault_amount, amount_b, now)
+                .ok_or(EscrowError::InvalidAmount)?
+        };
+
+        // 创建者在这之前取走了金库中的代币或者提高了 receive，按比例得到的代币 A 变少
+        if amount_a < self.instruction_data.min_amount_a {
+            return Err(EscrowError::SlippageExceeded.into());
+        }
+
+        let seed_binding = seed.to_le_bytes();
+        let bump_binding = bump;
+        let escrow_seeds=[
+            Seed::from(b"escrow"),
+            Seed::from(self.accounts.maker.address().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+        let signers = [Signer::from(&escrow_seeds)];
+
+        self.accounts.settle(amount_a, amount_b, referral_bps, &signers)?;
+
+        // 剩余数量：fill_amount_a 已保证 amount_b <= receive（当前期望数量）
+        // 荷兰式拍卖的整条价格曲线按剩余比例缩放，固定价格时即 receive - amount_b
+        let remaining = receive - amount_b;
+        if remaining > 0 {
+            let receive = {
+                let mut data = self.accounts.escrow.try_borrow_mut()?;
+                let escrow = Escrow::load_mut(&mut data)?;
+                escrow.scale_receive(receive, amount_b);
+                escrow.receive()
+            };
+            // 剩余数量变化后重新挂单：按比例取整后单价可能略有变化
+            return self.accounts.relist(receive, self.accounts.vault_amount()?);
+        }
+
+        // 全部成交：金库此时为空，关闭金库和托管账户
+        self.accounts.close_vault(&signers)?;
+
+        self.accounts.unlist()?;
+
+        ProgramAccount::close(
+            self.accounts.escrow,
+            self.accounts.rent_recipient()?
+        )?;
+        Ok(())
+    }
+}
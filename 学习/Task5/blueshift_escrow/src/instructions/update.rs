@@ -0,0 +1,252 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use solana_address::Address;
+use crate::{NativeSide, NativeVault, AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, Escrow, EscrowError, MarketIndex, MintInterface, ProgramAccount, ProgramAccountCheck, ProgramAccountType, SignerAccount, TokenProgramInterface, TransferCheckedInterface, TransferHook};
+
+// Update：创建者修改未成交的托管，不需要 Refund 后重新 Make
+// 可以修改期望数量 receive、追加或部分取回金库中的代币 A、修改过期时间
+// 托管地址、金库和 seed 都保持不变，接受者关注的报价不会消失
+pub struct UpdateAccounts<'info> {
+    pub maker: &'info AccountView,
+    pub escrow: &'info AccountView,
+    pub mint_a: &'info AccountView,
+    pub vault: &'info AccountView,
+    pub maker_ata_a: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub token_program: &'info AccountView,
+    // 剩余账户：Token-2022 转账钩子需要的账户
+    // 可选：交易对的挂单索引（Market），条款修改后按新的价格重新挂单
+    pub remaining_accounts: &'info [AccountView],
+    // 托管中记录的原生 SOL 标记
+    pub native: NativeSide,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for UpdateAccounts<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [maker, escrow, mint_a, vault, maker_ata_a, system_program, token_program, _, remaining_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        SignerAccount::check(maker)?;
+
+        ProgramAccount::check::<Escrow>(escrow)?;
+
+        let native = Escrow::load(&escrow.try_borrow()?)?.native()?;
+
+        MintInterface::check(mint_a)?;
+
+        TokenProgramInterface::check(token_program)?;
+
+        // 原生 SOL 的金库就是托管账户本身
+        if native != NativeSide::TokenA {
+            AssociatedTokenAccount::check(vault, escrow, mint_a, token_program)?;
+        }
+
+        Ok(Self{
+            maker,
+            escrow,
+            mint_a,
+            vault,
+            maker_ata_a,
+            system_program,
+            token_program,
+            remaining_accounts,
+            native,
+        })
+    }
+}
+
+pub struct UpdateInstructionData {
+    // 新的期望数量，0 表示不修改
+    pub receive: u64,
+    // 追加存入金库的代币 A 数量
+    pub deposit: u64,
+    // 从金库取回的代币 A 数量（必须保留余额，全部取回请使用 Refund）
+    pub withdraw: u64,
+    // 可选：新的过期时间，不传表示不修改，0 表示永不过期
+    pub expires_at: Option<i64>,
+}
+
+impl<'info> TryFrom<&'info [u8]> for UpdateInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        // 数据布局：receive(8) + deposit(8) + withdraw(8) + [可选] expires_at(8)
+        if !matches!(data.len(), 24 | 32) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let receive = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let deposit = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let withdraw = u64::from_le_bytes(data[16..24].try_into().unwrap());
+        let expires_at = data
+            .get(24..32)
+            .map(|bytes| i64::from_le_bytes(bytes.try_into().unwrap()));
+
+        // 同一次调用中只能追加或取回其中一种
+        if deposit > 0 && withdraw > 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self{
+            receive,
+            deposit,
+            withdraw,
+            expires_at,
+        })
+    }
+}
+
+pub struct Update<'info> {
+    pub accounts: UpdateAccounts<'info>,
+    pub instruction_data: UpdateInstructionData,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for Update<'info> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = UpdateAccounts::try_from(accounts)?;
+        let instruction_data = UpdateInstructionData::try_from(data)?;
+
+        let spl = accounts.native != NativeSide::TokenA;
+
+        if spl && instruction_data.deposit > 0 {
+            AssociatedTokenAccount::check(accounts.maker_ata_a, accounts.maker, accounts.mint_a, accounts.token_program)?;
+        }
+
+        // 取回时创建者的 ATA 可能已经被关闭
+        if spl && instruction_data.withdraw > 0 {
+            AssociatedTokenAccount::init_if_needed(
+                accounts.maker_ata_a,
+                accounts.mint_a,
+                accounts.maker,
+                accounts.maker,
+                accounts.system_program,
+                accounts.token_program,
+            )?;
+        }
+
+        Ok(Self{
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'info> Update<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &7;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 新的过期时间必须在未来，与 Make 相同
+        if let Some(expires_at) = self.instruction_data.expires_at {
+            if expires_at != 0 && expires_at <= Clock::get()?.unix_timestamp {
+                return Err(EscrowError::EscrowExpired.into());
+            }
+        }
+
+        let (seed, bump, mint_b) = {
+            let mut data = self.accounts.escrow.try_borrow_mut()?;
+            let escrow = Escrow::load_mut(&mut data)?;
+            // PDA 由 maker 地址派生，验证通过即说明签名者就是托管的创建者
+            let escrow_key = Address::create_program_address(
+                &[
+                    b"escrow",
+                    self.accounts.maker.address().as_ref(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump(),
+                ],
+                &crate::ID
+            )?;
+            if &escrow_key != self.accounts.escrow.address() {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+            if escrow.mint_a() != self.accounts.mint_a.address() {
+                return Err(EscrowError::InvalidAddress.into());
+            }
+
+            if self.instruction_data.receive > 0 {
+                escrow.set_receive(self.instruction_data.receive);
+                // 荷兰式拍卖的起始价格不能低于结束价格
+                escrow.set_auction(escrow.end_receive(), escrow.auction_start(), escrow.auction_end())?;
+            }
+            if let Some(expires_at) = self.instruction_data.expires_at {
+                escrow.set_expires_at(expires_at);
+            }
+            (escrow.seed(), escrow.bump(), *escrow.mint_b())
+        };
+
+        // 原生 SOL：直接在创建者钱包与托管 PDA 之间转移 lamports
+        if self.accounts.native == NativeSide::TokenA {
+            if self.instruction_data.deposit > 0 {
+                NativeVault::deposit(self.accounts.maker, self.accounts.escrow, self.instruction_data.deposit)?;
+            }
+            if self.instruction_data.withdraw > 0 {
+                if self.instruction_data.withdraw >= NativeVault::amount(self.accounts.escrow)? {
+                    return Err(EscrowError::InvalidAmount.into());
+                }
+                NativeVault::withdraw(self.accounts.escrow, self.accounts.maker, self.instruction_data.withdraw)?;
+            }
+            return self.relist(&mint_b, NativeVault::amount(self.accounts.escrow)?);
+        }
+
+        if self.instruction_data.deposit > 0 {
+            TransferCheckedInterface {
+                from: self.accounts.maker_ata_a,
+                mint: self.accounts.mint_a,
+                to: self.accounts.vault,
+                authority: self.accounts.maker,
+                token_program: self.accounts.token_program,
+                extra_accounts: TransferHook::resolve(self.accounts.mint_a, self.accounts.remaining_accounts)?,
+                amount: self.instruction_data.deposit,
+                decimals: MintInterface::decimals(self.accounts.mint_a)?,
+            }.invoke()?;
+        }
+
+        if self.instruction_data.withdraw > 0 {
+            let vault_amount = {
+                let vault_data = self.accounts.vault.try_borrow()?;
+                u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
+            };
+            // 金库必须保留余额，否则托管变成一个没有代币 A 的空报价
+            if self.instruction_data.withdraw >= vault_amount {
+                return Err(EscrowError::InvalidAmount.into());
+            }
+
+            let seed_binding = seed.to_le_bytes();
+            let bump_binding = bump;
+            let escrow_seeds = [
+                Seed::from(b"escrow"),
+                Seed::from(self.accounts.maker.address().as_ref()),
+                Seed::from(&seed_binding),
+                Seed::from(&bump_binding),
+            ];
+            let signers = [Signer::from(&escrow_seeds)];
+
+            TransferCheckedInterface {
+                from: self.accounts.vault,
+                mint: self.accounts.mint_a,
+                to: self.accounts.maker_ata_a,
+                authority: self.accounts.escrow,
+                token_program: self.accounts.token_program,
+                extra_accounts: TransferHook::resolve(self.accounts.mint_a, self.accounts.remaining_accounts)?,
+                amount: self.instruction_data.withdraw,
+                decimals: MintInterface::decimals(self.accounts.mint_a)?,
+            }.invoke_signed(&signers)?;
+        }
+
+        let vault_amount = {
+            let vault_data = self.accounts.vault.try_borrow()?;
+            u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
+        };
+        self.relist(&mint_b, vault_amount)
+    }
+
+    // 期望数量或金库余额变化后重新挂单（托管挂过单时剩余账户中必须传入 Market）
+    fn relist(&self, mint_b: &Address, amount: u64) -> ProgramResult {
+        let receive = Escrow::load(&self.accounts.escrow.try_borrow()?)?.receive();
+        MarketIndex::relist(self.accounts.remaining_accounts, self.accounts.mint_a.address(), mint_b, self.accounts.escrow, receive, amount)
+    }
+}
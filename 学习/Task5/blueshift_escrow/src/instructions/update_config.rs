@@ -0,0 +1,88 @@
+use pinocchio::{Address, AccountView, ProgramResult};
+use pinocchio::error::ProgramError;
+use crate::{AccountCheck, Config, ConfigInstructionData, EscrowError, ProgramAccount, ProgramAccountCheck, ProgramAccountType, SignerAccount};
+
+// UpdateConfig：修改协议费、国库地址，或把管理员权限转移给新地址
+// 只有当前管理员可以调用
+pub struct UpdateConfigAccounts<'info> {
+    pub admin: &'info AccountView,
+    pub config: &'info AccountView,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for UpdateConfigAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [admin, config, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(admin)?;
+
+        ProgramAccount::check::<Config>(config)?;
+
+        Ok(Self{
+            admin,
+            config,
+        })
+    }
+}
+
+// 指令数据：fee_bps(2) + fee_side(1) + treasury(32) + admin(32)
+pub struct UpdateConfigInstructionData {
+    pub config: ConfigInstructionData,
+    pub admin: Address,
+}
+
+impl<'info> TryFrom<&'info [u8]> for UpdateConfigInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        let Some((config, admin)) = data.split_last_chunk::<32>() else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        Ok(Self{
+            config: ConfigInstructionData::try_from(config)?,
+            admin: Address::new_from_array(*admin),
+        })
+    }
+}
+
+pub struct UpdateConfig<'info> {
+    pub accounts: UpdateConfigAccounts<'info>,
+    pub instruction_data: UpdateConfigInstructionData,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for UpdateConfig<'info> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        Ok(Self{
+            accounts: UpdateConfigAccounts::try_from(accounts)?,
+            instruction_data: UpdateConfigInstructionData::try_from(data)?,
+        })
+    }
+}
+
+impl<'info> UpdateConfig<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &6;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut data = self.accounts.config.try_borrow_mut()?;
+        let config = Config::load_mut(data.as_mut())?;
+
+        if config.admin() != self.accounts.admin.address() {
+            return Err(EscrowError::InvalidAdmin.into());
+        }
+
+        let bump = config.bump();
+        config.set_inner(
+            bump,
+            self.instruction_data.config.fee_bps,
+            self.instruction_data.config.fee_side,
+            self.instruction_data.admin,
+            self.instruction_data.config.treasury,
+        )
+    }
+}
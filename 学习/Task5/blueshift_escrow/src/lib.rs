@@ -82,8 +82,34 @@ fn process_instruction(
 
     match instruction_data.split_first() {
         Some((Make::DISCRIMINATOR, data)) => Make::try_from((data,accounts))?.process(),
-        Some((Take::DISCRIMINATOR,_)) => Take::try_from(accounts)?.process(),
+        Some((Take::DISCRIMINATOR, data)) => Take::try_from((data,accounts))?.process(),
         Some((Refund::DISCRIMINATOR,_)) => Refund::try_from(accounts)?.process(),
+        Some((Expire::DISCRIMINATOR,_)) => Expire::try_from(accounts)?.process(),
+        Some((TakePartial::DISCRIMINATOR, data)) => TakePartial::try_from((data,accounts))?.process(),
+        Some((InitConfig::DISCRIMINATOR, data)) => InitConfig::try_from((data,accounts))?.process(),
+        Some((UpdateConfig::DISCRIMINATOR, data)) => UpdateConfig::try_from((data,accounts))?.process(),
+        Some((Update::DISCRIMINATOR, data)) => Update::try_from((data,accounts))?.process(),
+        Some((HtlcLock::DISCRIMINATOR, data)) => HtlcLock::try_from((data,accounts))?.process(),
+        Some((HtlcClaim::DISCRIMINATOR, data)) => HtlcClaim::try_from((data,accounts))?.process(),
+        Some((HtlcRefund::DISCRIMINATOR,_)) => HtlcRefund::try_from(accounts)?.process(),
+        Some((ArbiterMake::DISCRIMINATOR, data)) => ArbiterMake::try_from((data,accounts))?.process(),
+        Some((Release::DISCRIMINATOR,_)) => Release::try_from(accounts)?.process(),
+        Some((Cancel::DISCRIMINATOR,_)) => Cancel::try_from(accounts)?.process(),
+        Some((Dispute::DISCRIMINATOR,_)) => Dispute::try_from(accounts)?.process(),
+        Some((MilestoneMake::DISCRIMINATOR, data)) => MilestoneMake::try_from((data,accounts))?.process(),
+        Some((ApproveMilestone::DISCRIMINATOR, data)) => ApproveMilestone::try_from((data,accounts))?.process(),
+        Some((RefundMilestones::DISCRIMINATOR,_)) => RefundMilestones::try_from(accounts)?.process(),
+        Some((FillSignedOrder::DISCRIMINATOR, data)) => FillSignedOrder::try_from((data,accounts))?.process(),
+        Some((AuctionMake::DISCRIMINATOR, data)) => AuctionMake::try_from((data,accounts))?.process(),
+        Some((PlaceBid::DISCRIMINATOR, data)) => PlaceBid::try_from((data,accounts))?.process(),
+        Some((SettleAuction::DISCRIMINATOR,_)) => SettleAuction::try_from(accounts)?.process(),
+        Some((BundleMake::DISCRIMINATOR, data)) => BundleMake::try_from((data,accounts))?.process(),
+        Some((BundleTake::DISCRIMINATOR,_)) => BundleTake::try_from(accounts)?.process(),
+        Some((BundleRefund::DISCRIMINATOR,_)) => BundleRefund::try_from(accounts)?.process(),
+        Some((Match::DISCRIMINATOR,_)) => Match::try_from(accounts)?.process(),
+        Some((TakeMany::DISCRIMINATOR, data)) => TakeMany::try_from((data,accounts))?.process(),
+        Some((SettleFlashTake::DISCRIMINATOR, data)) => SettleFlashTake::try_from((data,accounts))?.process(),
+        Some((PruneMarket::DISCRIMINATOR,_)) => PruneMarket::try_from(accounts)?.process(),
         _=> Err(ProgramError::InvalidInstructionData),
     }
 }
\ No newline at end of file
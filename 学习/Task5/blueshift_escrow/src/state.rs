@@ -1,117 +1,109 @@
 use pinocchio::Address;
 use pinocchio::error::ProgramError;
+use crate::errors::EscrowError;
 use core::mem::size_of; // 引入 Rust 核心库的内存大小计算函数
-/*
-#[repr(C)] 是一个 Rust 属性（attribute），用于控制结构体的内存布局：
 
-主要作用：
-1、指定内存表示：告诉编译器按照 C 语言的内存布局规则来排列结构体字段
-2、保证字段顺序：确保结构体字段在内存中的排列顺序与源代码中定义的顺序一致
-3、确定对齐方式：使用 C 语言的对齐规则而非 Rust 的默认优化对齐
+/*
+账户类型判别器（Account Discriminator）：
+每个由本程序拥有的账户，数据的第 1 个字节都是账户类型标记
 
-在 Solana 程序中的重要性：
-1、跨语言兼容性：确保 Rust 结构体与 C/C++ 或其他语言的结构体具有相同的内存布局
-2、ABI 兼容性：保证与其他组件（如客户端或系统调用）交互时的数据格式正确
-3、序列化一致性：在将结构体转换为字节数组时，保证字节顺序和布局的可预测性
+作用：
+1、类型混淆防护：Take/Refund 只接受标记为 Escrow 的账户，未来新增的账户类型即使长度相同也不会被当作 Escrow
+2、初始化保护：只有标记为 Uninitialized（全 0）的账户才能被初始化
+3、关闭标记：关闭的账户写入专用的 Closed 标记，而不是覆盖某个业务字段
 
+对应 Anchor：#[account] 自动生成的 8 字节判别器（Anchor Escrow 使用 discriminator = 1）
 */
-#[repr(C)]
-pub struct Escrow{
-    // 种子：用于派生 PDA 的随机数
-    // 确保每个托管账户都有唯一的地址
-    // 客户端和程序使用相同的种子 + maker + mint_a 可以派生出相同的 PDA
-    pub seed: u64,
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AccountDiscriminator {
+    // 刚由 System Program 创建、尚未写入数据的账户（数据全为 0）
+    Uninitialized = 0,
 
-    // 创建者：发起托管交易的用户地址
-    // 用于验证只有创建者才能执行退款操作
-    pub maker: Address,
-    /*
-    Mint 地址：Solana 上代币类型的唯一标识符，类似于 ERC-20 合约地址
-    作用：
-    1、标识代币类型：记录被托管的代币是什么（如 USDC、SOL 或其他 SPL 代币）
-    2、验证安全性：确保存入和提取的是正确的代币类型
-    3、PDA 派生依据：作为派生程序地址的重要参数之一
-    */
-    // 代币 A 的 mint 地址：被存入金库的代币类型
-    // 例如：如果是 SOL，则是 SOL 的 mint 地址
-    pub mint_a: Address,
+    Escrow = 1,
 
-    pub mint_b: Address,
-    
-    // 期望数量：创建者希望获得的代币 B 的数量
-    // 接受者必须发送至少这个数量的代币 B 才能接受交易
-    
-    pub receive: u64,
-    /*
-    虽然 [u8; 1] 和 u8 在逻辑上都表示单个字节，但在 内存布局和 ABI 兼容性 方面存在重要差异：
+    Config = 2,
 
-    关键区别：
-    1. 类型表示
-    u8 - 单个字节值
-    [u8; 1] - 包含一个 u8 元素的数组
-    2. 内存布局差异
-    u8 - 可能因对齐要求而占用更多空间（取决于编译器优化）
-    [u8; 1] - 明确表示一个固定大小的单字节数组，有更严格的内存布局
-    3. #[repr(C)] 的影响
-    从上下文可以看到，Escrow 结构体使用了 #[repr(C)] 属性，这要求：
+    Htlc = 3,
 
-    严格按照字段定义顺序排列
-    保证跨平台的内存布局一致性
-    [u8; 1] 提供了更明确的内存布局保证
-    在 Solana 程序中的实际意义：
-    序列化/反序列化 - 确保字节数据的准确转换
-    PDA 验证 - PDA 派生时需要精确的内存布局
-    跨语言互操作 - 与其他组件通信时保持数据格式一致性
-    正如代码注释所说："使用 [u8; 1] 而不是 u8 是为了确保内存布局"，这是为了保证结构体在序列化为字节流时，每个字段都占据预期内的字节位置，避免因编译器对齐优化导致的意外偏移。
-    */
-    // Bump 种子：PDA 派生时找到的有效 bump 值
-    // Solana 使用 "find_program_address" 查找 PDA，会返回一个 bump 值
-    // 验证签名时需要提供这个 bump 值（通常追加在 seeds 后面）
-    // 使用 [u8; 1] 而不是 u8 是为了确保内存布局
-    // [u8; 1] 是 Rust 中的数组类型语法，表示一个包含 1 个 u8 类型元素的固定大小数组。
-    // [T; N]：Rust 数组类型的通用语法
-    pub bump:[u8;1]
+    ArbiterEscrow = 4,
+
+    MilestoneEscrow = 5,
+
+    NonceBitmap = 6,
+
+    Auction = 7,
+
+    BundleEscrow = 8,
+
+    MakerState = 9,
+
+    Market = 10,
+
+    // 已关闭的账户
+    Closed = 0xff,
 }
 
-// Escrow 结构体的方法实现
-impl Escrow {
-    // ------------------------------------------------------------------------
-    // 常量：账户数据长度
-    // ------------------------------------------------------------------------
-    // 这是 Escrow 结构体在链上账户中占用的总字节数
-    // 计算方式：每个字段的大小之和
-    // - u64: 8 字节
-    // - Address: 32 字节
-    // - [u8; 1]: 1 字节
-    // 总计：8 + 32 + 32 + 32 + 8 + 1 = 113 字节
-    //
-    // 用途：创建账户时需要指定空间大小，客户端和程序都需要知道这个值
-    // <Address>() 泛型参数的显式指定语法。<Address> - 指定泛型类型参数为 Address 类型。() - 函数调用的参数列表（无参数）
-    pub const LEN: usize = size_of::<u64>()                     // seed: 8 字节
-        + size_of::<Address>()                                  // maker: 32 字节
-        + size_of::<Address>()                                  // mint_a: 32 字节
-        + size_of::<Address>()                                  // mint_b: 32 字节
-        + size_of::<u64>()                                      // receive: 8 字节
-        + size_of::<[u8;1]>();                                  // bump: 1 字节
+/*
+程序账户类型（Program Account Type）：
+每种由本程序拥有的账户类型都实现这个 trait，提供：
+1、LEN：账户数据长度（创建账户时用于计算空间和租金）
+2、DISCRIMINATOR：账户类型判别器（第 1 个字节）
+3、SEEDS：PDA 的固定种子前缀，动态部分（如 maker、seed、bump）由指令追加
+
+init/load/load_mut 的默认实现对所有类型通用，新增账户类型只需实现这三个常量，
+helpers.rs 中的 ProgramAccount::check / init / close 会自动支持
 
-    // 从字节数组（账户数据）中加载 Escrow 结构体的可变引用
+要求：实现类型必须是 #[repr(C)]、对齐为 1、没有填充、大小等于 LEN（默认方法中有编译期断言）
+
+对应 Anchor：#[account] + #[derive(InitSpace)] 自动生成的判别器和空间计算
+*/
+pub trait ProgramAccountType: Sized {
+    const LEN: usize;
+
+    const DISCRIMINATOR: AccountDiscriminator;
+
+    const SEEDS: &'static [&'static [u8]];
+
+    // 从字节数组中加载一个尚未初始化的账户（判别器必须为 Uninitialized）
+    // 并写入该类型的判别器，返回可变引用
+    // 只在创建账户之后调用（见 ProgramAccountInit::init）
+    #[inline(always)]
+    fn init(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        if bytes.len() != Self::LEN{
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if bytes[0] != AccountDiscriminator::Uninitialized as u8 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        bytes[0] = Self::DISCRIMINATOR as u8;
+
+        Self::load_mut(bytes)
+    }
+
+    // 从字节数组（账户数据）中加载账户结构体的可变引用
     // 参数：
     //   bytes: 账户数据的可变字节数组切片
     // 返回：
-    //   成功：返回 Escrow 的可变引用
+    //   成功：返回账户结构体的可变引用
     //   失败：返回 InvalidAccountData 错误
     //
     // 安全性：
     //   使用 unsafe 代码块和 transmute 将字节指针转换为结构体指针
     //   这是因为我们需要直接操作原始内存，避免复制开销
-    //   前提条件：字节数组必须足够大且内存布局正确
+    //   前提条件：字节数组长度等于 LEN；账户类型的对齐为 1，任意地址都满足对齐要求
     //
     // #[inline(always)]:
     //   强制编译器内联此函数，消除函数调用开销
     //   对于这种小型辅助函数，内联能提高性能
     #[inline(always)]
-    pub fn load_mut(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
-        if bytes.len() != Escrow::LEN{
+    fn load_mut(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        const { assert!(size_of::<Self>() == Self::LEN && core::mem::align_of::<Self>() == 1) };
+        if bytes.len() != Self::LEN{
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // 判别器必须与类型一致：拒绝未初始化、已关闭或其他类型的账户
+        if bytes[0] != Self::DISCRIMINATOR as u8 {
             return Err(ProgramError::InvalidAccountData);
         }
 
@@ -121,7 +113,7 @@ impl Escrow {
         原因：当编译器无法验证某些操作的安全性，但开发者可以保证其安全性时使用
         为什么要在这里使用 unsafe？
         原始内存操作：直接将字节数组转换为结构体指针
-        内存布局假设：假设输入的字节数组具有正确的 Escrow 结构体布局
+        内存布局假设：假设输入的字节数组具有正确的账户结构体布局
         性能优化：避免数据复制，直接操作内存
         as_mut_ptr() 解析：将 &mut [u8] 切片转换为原始可变指针 *mut u8。返回指向切片首字节的原始指针
         *mut u8 - 可变原始指针
@@ -148,58 +140,1720 @@ impl Escrow {
         Ok(unsafe {&mut *core::mem::transmute::<*mut u8, *mut Self>(bytes.as_mut_ptr()) })
     }
 
-     // 从字节数组（账户数据）中加载 Escrow 结构体的只读引用
+    // 从字节数组（账户数据）中加载账户结构体的只读引用
     #[inline(always)]
-    pub fn load(bytes:&[u8]) -> Result<&Self, ProgramError> {
-        if bytes.len() != Escrow::LEN{
+    fn load(bytes:&[u8]) -> Result<&Self, ProgramError> {
+        const { assert!(size_of::<Self>() == Self::LEN && core::mem::align_of::<Self>() == 1) };
+        if bytes.len() != Self::LEN{
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if bytes[0] != Self::DISCRIMINATOR as u8 {
             return Err(ProgramError::InvalidAccountData);
         }
 
         Ok(unsafe {&*core::mem::transmute::<*const u8, *const Self>(bytes.as_ptr())})
     }
-     // 为什么需要这些 setter 方法？
-    // - Pinocchio 不像 Anchor 那样自动实现序列化
-    // - 需要手动提供方法来修改结构体字段
-    // - 提供一致的 API 接口
+}
+
+// 原生 SOL 的 Mint 地址（wSOL）：So11111111111111111111111111111111111111112
+// 原生 SOL 一侧的 mint_a / mint_b 记录这个地址，客户端也传入这个 Mint 账户
+pub const NATIVE_MINT: Address = Address::new_from_array(
+    [
+    0x06, 0x9b, 0x88, 0x57, 0xfe, 0xab, 0x81, 0x84, 0xfb, 0x68, 0x7f, 0x63, 0x46, 0x18, 0xc0, 0x35,
+    0xda, 0xc4, 0x39, 0xdc, 0x1a, 0xeb, 0x3b, 0x55, 0x98, 0xa0, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x01,
+]);
+
+/*
+原生 SOL 托管：哪一侧使用 lamports 而不是 SPL 代币
+- TokenA：创建者存入的 lamports 直接保存在托管 PDA 中（超出租金豁免的部分），不创建金库 ATA
+  Take / Refund / Expire 直接从托管账户扣减存入的 lamports，关闭托管时剩余的租金退还给 rent_recipient
+- TokenB：接受者通过 System Program 直接向创建者（以及国库、推荐人）转账 lamports
+两侧不能同时为原生 SOL
+*/
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NativeSide {
+    // 两侧都是 SPL 代币
+    None = 0,
+
+    TokenA = 1,
+
+    TokenB = 2,
+}
+
+impl TryFrom<u8> for NativeSide {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(NativeSide::None),
+            1 => Ok(NativeSide::TokenA),
+            2 => Ok(NativeSide::TokenB),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+/*
+#[repr(C)] 是一个 Rust 属性（attribute），用于控制结构体的内存布局：
+
+主要作用：
+1、指定内存表示：告诉编译器按照 C 语言的内存布局规则来排列结构体字段
+2、保证字段顺序：确保结构体字段在内存中的排列顺序与源代码中定义的顺序一致
+3、确定对齐方式：使用 C 语言的对齐规则而非 Rust 的默认优化对齐
+
+在 Solana 程序中的重要性：
+1、跨语言兼容性：确保 Rust 结构体与 C/C++ 或其他语言的结构体具有相同的内存布局
+2、ABI 兼容性：保证与其他组件（如客户端或系统调用）交互时的数据格式正确
+3、序列化一致性：在将结构体转换为字节数组时，保证字节顺序和布局的可预测性
+
+零拷贝布局约定：
+1、所有字段都是字节数组（u8 / [u8; N] / Address），结构体对齐为 1，
+   因此可以安全地把任意地址开始的账户数据直接转换为 &Escrow，不存在未对齐读取
+2、数值字段（u64）以小端字节序存储在 [u8; 8] 中，通过 getter/setter 读写
+3、字段之间没有任何填充，LEN 与 size_of::<Escrow>() 在编译期断言一致
+
+*/
+#[repr(C)]
+pub struct Escrow{
+    // 账户类型判别器：固定为 AccountDiscriminator::Escrow
+    // 放在第 1 个字节，关闭账户时会被改写为 AccountDiscriminator::Closed
+    discriminator: u8,
+
+    /*
+    虽然 [u8; 1] 和 u8 在逻辑上都表示单个字节，但在 内存布局和 ABI 兼容性 方面存在重要差异：
+
+    关键区别：
+    1. 类型表示
+    u8 - 单个字节值
+    [u8; 1] - 包含一个 u8 元素的数组
+    2. 内存布局差异
+    u8 - 可能因对齐要求而占用更多空间（取决于编译器优化）
+    [u8; 1] - 明确表示一个固定大小的单字节数组，有更严格的内存布局
+    3. #[repr(C)] 的影响
+    从上下文可以看到，Escrow 结构体使用了 #[repr(C)] 属性，这要求：
+
+    严格按照字段定义顺序排列
+    保证跨平台的内存布局一致性
+    [u8; 1] 提供了更明确的内存布局保证
+    在 Solana 程序中的实际意义：
+    序列化/反序列化 - 确保字节数据的准确转换
+    PDA 验证 - PDA 派生时需要精确的内存布局
+    跨语言互操作 - 与其他组件通信时保持数据格式一致性
+    正如代码注释所说："使用 [u8; 1] 而不是 u8 是为了确保内存布局"，这是为了保证结构体在序列化为字节流时，每个字段都占据预期内的字节位置，避免因编译器对齐优化导致的意外偏移。
+    */
+    // Bump 种子：PDA 派生时找到的有效 bump 值
+    // Solana 使用 "find_program_address" 查找 PDA，会返回一个 bump 值
+    // 验证签名时需要提供这个 bump 值（通常追加在 seeds 后面）
+    // 使用 [u8; 1] 而不是 u8 是为了确保内存布局
+    // [u8; 1] 是 Rust 中的数组类型语法，表示一个包含 1 个 u8 类型元素的固定大小数组。
+    // [T; N]：Rust 数组类型的通用语法
+    bump:[u8;1],
+
+    // 种子：用于派生 PDA 的随机数
+    // 确保每个托管账户都有唯一的地址
+    // 客户端和程序使用相同的种子 + maker + mint_a 可以派生出相同的 PDA
+    // 以小端字节序保存的 u64，通过 seed() 读取
+    seed: [u8; 8],
+
+    // 创建者：发起托管交易的用户地址
+    // 用于验证只有创建者才能执行退款操作
+    maker: Address,
+    /*
+    Mint 地址：Solana 上代币类型的唯一标识符，类似于 ERC-20 合约地址
+    作用：
+    1、标识代币类型：记录被托管的代币是什么（如 USDC、SOL 或其他 SPL 代币）
+    2、验证安全性：确保存入和提取的是正确的代币类型
+    3、PDA 派生依据：作为派生程序地址的重要参数之一
+    */
+    // 代币 A 的 mint 地址：被存入金库的代币类型
+    // 例如：如果是 SOL，则是 SOL 的 mint 地址
+    mint_a: Address,
+
+    mint_b: Address,
+    
+    // 期望数量：创建者希望获得的代币 B 的数量
+    // 接受者必须发送至少这个数量的代币 B 才能接受交易
+    // 以小端字节序保存的 u64，通过 receive() 读取
+    receive: [u8; 8],
+
+    // 过期时间：Unix 时间戳（秒），以小端字节序保存的 i64
+    // 0 表示永不过期；到期后 Take 失败，任何人都可以调用 Expire 把代币退还给创建者、租金退还给 rent_recipient
+    expires_at: [u8; 8],
+
+    // 指定的接受者（私下协商的 OTC 交易）：设置后只有该地址可以 Take / TakePartial
+    // 全 0 地址表示任何人都可以接受
+    taker: Address,
+
+    // 接受者白名单的 Merkle 根：设置后 Take / TakePartial 需要在指令数据中提供 Merkle 证明
+    // 证明接受者地址在白名单中（叶子与节点的哈希规则见 helpers.rs 的 MerkleProof）
+    // 全 0 表示不启用白名单
+    allowlist_root: [u8; 32],
+
+    // 推荐费（基点）：创建者在 Make 时设定，以小端字节序保存的 u16
+    // Take / TakePartial 传入推荐人时，从创建者收到的代币 B 中按此比例支付给推荐人
+    // 0 表示不支付推荐费
+    referral_bps: [u8; 2],
+
+    // 原生 SOL 标记（NativeSide）：0 表示两侧都是 SPL 代币
+    native: u8,
+
+    // 荷兰式拍卖：期望数量在 [auction_start, auction_end] 之间从 receive 线性下降到 end_receive
+    // auction_end 为 0 表示固定价格，只使用 receive
+    // 三个字段都以小端字节序保存（u64 / i64 / i64）
+    end_receive: [u8; 8],
+
+    auction_start: [u8; 8],
+
+    auction_end: [u8; 8],
+
+    // 租金的退还对象：Make 时支付托管账户和金库 ATA 租金的账户（没有单独的 payer 时为创建者）
+    // 关闭金库和托管账户时租金退还给该地址（见 helpers.rs 的 RentRecipient）
+    rent_recipient: Address,
+
+    // 挂单标记：Make 时传入了交易对的 Market 为 1，否则为 0
+    // 已挂单的托管在重新挂单和撤单时必须传入 Market，避免漏传 Market 后索引中留下过期的价格
+    listed: u8,
+}
+
+// Escrow 结构体的方法实现
+impl Escrow {
+    // 字段读取：数值字段从小端字节数组解码，不依赖内存对齐
     #[inline(always)]
-    pub fn set_seed(&mut self, seed: u64) {
-        self.seed = seed;
+    pub fn discriminator(&self) -> u8 {
+        self.discriminator
     }
 
     #[inline(always)]
-    pub fn set_maker(&mut self, maker: Address) {
-        self.maker = maker;
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
     }
 
     #[inline(always)]
-    pub fn set_mint_a(&mut self, mint_a: Address) {
-        self.mint_a = mint_a;
+    pub fn maker(&self) -> &Address {
+        &self.maker
     }
 
     #[inline(always)]
-    pub fn set_mint_b(&mut self, mint_b: Address) {
-        self.mint_b = mint_b;
+    pub fn mint_a(&self) -> &Address {
+        &self.mint_a
     }
 
     #[inline(always)]
-    pub fn set_receive(&mut self, receive: u64) {
-        self.receive = receive;
+    pub fn mint_b(&self) -> &Address {
+        &self.mint_b
     }
 
     #[inline(always)]
-    pub fn set_bump(&mut self, bump: [u8;1]) {
-        self.bump = bump;
+    pub fn receive(&self) -> u64 {
+        u64::from_le_bytes(self.receive)
     }
 
-    // 一次性设置所有字段，避免多次函数调用
-     #[inline(always)]
-    pub fn set_inner(&mut self, seed: u64, maker: Address, mint_a: Address, mint_b: Address, receive: u64, bump: [u8;1]) {
-        self.seed = seed;
-        self.maker = maker;
-        self.mint_a = mint_a;
-        self.mint_b = mint_b;
-        self.receive = receive;
-        self.bump = bump;
+    #[inline(always)]
+    pub fn bump(&self) -> [u8;1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn expires_at(&self) -> i64 {
+        i64::from_le_bytes(self.expires_at)
+    }
+
+    // 当前时间（Clock::unix_timestamp）是否已经到达过期时间
+    // expires_at 为 0 的托管永不过期
+    #[inline(always)]
+    pub fn is_expired(&self, now: i64) -> bool {
+        let expires_at = self.expires_at();
+        expires_at != 0 && now >= expires_at
+    }
+
+    #[inline(always)]
+    pub fn taker(&self) -> &Address {
+        &self.taker
+    }
+
+    // 未指定接受者（全 0 地址）时任何人都可以接受，否则只允许指定的地址
+    #[inline(always)]
+    pub fn can_take(&self, taker: &Address) -> bool {
+        self.taker == Address::default() || &self.taker == taker
+    }
+
+    #[inline(always)]
+    pub fn allowlist_root(&self) -> Option<&[u8; 32]> {
+        if self.allowlist_root == [0; 32] {
+            return None;
+        }
+        Some(&self.allowlist_root)
+    }
+
+    #[inline(always)]
+    pub fn referral_bps(&self) -> u16 {
+        u16::from_le_bytes(self.referral_bps)
+    }
+
+    #[inline(always)]
+    pub fn native(&self) -> Result<NativeSide, ProgramError> {
+        NativeSide::try_from(self.native).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    #[inline(always)]
+    pub fn end_receive(&self) -> u64 {
+        u64::from_le_bytes(self.end_receive)
+    }
+
+    #[inline(always)]
+    pub fn auction_start(&self) -> i64 {
+        i64::from_le_bytes(self.auction_start)
+    }
+
+    #[inline(always)]
+    pub fn auction_end(&self) -> i64 {
+        i64::from_le_bytes(self.auction_end)
+    }
+
+    #[inline(always)]
+    pub fn rent_recipient(&self) -> &Address {
+        &self.rent_recipient
+    }
+
+    #[inline(always)]
+    pub fn is_listed(&self) -> bool {
+        self.listed != 0
+    }
+
+    #[inline(always)]
+    pub fn is_auction(&self) -> bool {
+        self.auction_end() != 0
+    }
+
+    // 当前期望数量：固定价格时为 receive
+    // 荷兰式拍卖时开始前为 receive，结束后为 end_receive，中间线性插值：
+    // receive - (receive - end_receive) * (now - auction_start) / (auction_end - auction_start)
+    // 下降量向下取整，即价格向上取整，有利于创建者
+    #[inline(always)]
+    pub fn receive_at(&self, now: i64) -> u64 {
+        let start = self.receive();
+        if !self.is_auction() || now <= self.auction_start() {
+            return start;
+        }
+        let end = self.end_receive();
+        if now >= self.auction_end() {
+            return end;
+        }
+        // Make 保证 auction_start < auction_end、end_receive <= receive，u128 中计算不会溢出
+        let elapsed = now.abs_diff(self.auction_start()) as u128;
+        let duration = self.auction_end().abs_diff(self.auction_start()) as u128;
+        start - ((start - end) as u128 * elapsed / duration) as u64
     }
 
-}
\ No newline at end of file
+    // 部分成交：接受者支付 amount_b 个代币 B 时，按比例释放的代币 A 数量
+    // amount_a = floor(vault_amount * amount_b / receive_at(now))
+    // 向下取整，舍入误差留在金库中，有利于创建者；amount_b 等于当前期望数量时释放金库全部余额
+    // 返回 None：amount_b 为 0、超过当前期望数量，或者释放数量被舍入为 0
+    #[inline(always)]
+    pub fn fill_amount_a(&self, vault_amount: u64, amount_b: u64, now: i64) -> Option<u64> {
+        let receive = self.receive_at(now);
+        if amount_b == 0 || amount_b > receive {
+            return None;
+        }
+        // u64 * u64 放在 u128 中计算不会溢出，结果不超过 vault_amount
+        let amount_a = (vault_amount as u128 * amount_b as u128 / receive as u128) as u64;
+        if amount_a == 0 {
+            return None;
+        }
+        Some(amount_a)
+    }
+
+     // 为什么需要这些 setter 方法？
+    // - Pinocchio 不像 Anchor 那样自动实现序列化
+    // - 需要手动提供方法来修改结构体字段
+    // - 提供一致的 API 接口
+    #[inline(always)]
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_maker(&mut self, maker: Address) {
+        self.maker = maker;
+    }
+
+    #[inline(always)]
+    pub fn set_mint_a(&mut self, mint_a: Address) {
+        self.mint_a = mint_a;
+    }
+
+    #[inline(always)]
+    pub fn set_mint_b(&mut self, mint_b: Address) {
+        self.mint_b = mint_b;
+    }
+
+    #[inline(always)]
+    pub fn set_receive(&mut self, receive: u64) {
+        self.receive = receive.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_bump(&mut self, bump: [u8;1]) {
+        self.bump = bump;
+    }
+
+    #[inline(always)]
+    pub fn set_expires_at(&mut self, expires_at: i64) {
+        self.expires_at = expires_at.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_taker(&mut self, taker: Address) {
+        self.taker = taker;
+    }
+
+    #[inline(always)]
+    pub fn set_allowlist_root(&mut self, allowlist_root: [u8; 32]) {
+        self.allowlist_root = allowlist_root;
+    }
+
+    #[inline(always)]
+    pub fn set_referral_bps(&mut self, referral_bps: u16) {
+        self.referral_bps = referral_bps.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_native(&mut self, native: NativeSide) {
+        self.native = native as u8;
+    }
+
+    #[inline(always)]
+    pub fn set_rent_recipient(&mut self, rent_recipient: Address) {
+        self.rent_recipient = rent_recipient;
+    }
+
+    #[inline(always)]
+    pub fn set_listed(&mut self) {
+        self.listed = 1;
+    }
+
+    // 设置荷兰式拍卖：auction_end 为 0 时关闭拍卖（其余两个字段也必须为 0）
+    // 否则要求 auction_start < auction_end 且 end_receive <= receive，价格只会下降
+    #[inline(always)]
+    pub fn set_auction(&mut self, end_receive: u64, auction_start: i64, auction_end: i64) -> Result<(), ProgramError> {
+        let valid = if auction_end == 0 {
+            end_receive == 0 && auction_start == 0
+        } else {
+            auction_start < auction_end && end_receive <= self.receive()
+        };
+        if !valid {
+            return Err(EscrowError::InvalidAmount.into());
+        }
+        self.end_receive = end_receive.to_le_bytes();
+        self.auction_start = auction_start.to_le_bytes();
+        self.auction_end = auction_end.to_le_bytes();
+        Ok(())
+    }
+
+    // 部分成交后按剩余比例缩放价格曲线：当前期望数量为 current，接受者支付了 amount_b
+    // receive 与 end_receive 都乘以 (current - amount_b) / current 并向上取整，有利于创建者
+    // 固定价格时 current == receive，结果正好是 receive - amount_b
+    #[inline(always)]
+    pub fn scale_receive(&mut self, current: u64, amount_b: u64) {
+        let remaining = (current - amount_b) as u128;
+        let scale = |value: u64| (value as u128 * remaining).div_ceil(current as u128) as u64;
+        self.receive = scale(self.receive()).to_le_bytes();
+        self.end_receive = scale(self.end_receive()).to_le_bytes();
+    }
+
+    // 一次性设置所有字段，避免多次函数调用
+     #[inline(always)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_inner(&mut self, seed: u64, maker: Address, mint_a: Address, mint_b: Address, receive: u64, bump: [u8;1], expires_at: i64, taker: Address, allowlist_root: [u8; 32], referral_bps: u16, native: NativeSide) {
+        self.set_seed(seed);
+        self.maker = maker;
+        self.mint_a = mint_a;
+        self.mint_b = mint_b;
+        self.set_receive(receive);
+        self.bump = bump;
+        self.set_expires_at(expires_at);
+        self.taker = taker;
+        self.allowlist_root = allowlist_root;
+        self.set_referral_bps(referral_bps);
+        self.set_native(native);
+    }
+
+}
+
+impl ProgramAccountType for Escrow {
+    // ------------------------------------------------------------------------
+    // 常量：账户数据长度
+    // ------------------------------------------------------------------------
+    // 这是 Escrow 结构体在链上账户中占用的总字节数
+    // 计算方式：每个字段的大小之和
+    // - u8 / [u8; 1]: 1 字节
+    // - [u8; 8]（小端 u64）: 8 字节
+    // - Address: 32 字节
+    // - [u8; 2]（小端 u16）: 2 字节
+    // 总计：1 + 1 + 8 + 32 + 32 + 32 + 8 + 8 + 32 + 32 + 2 + 1 + 8 + 8 + 8 + 32 + 1 = 246 字节
+    //
+    // 用途：创建账户时需要指定空间大小，客户端和程序都需要知道这个值
+    // <Address>() 泛型参数的显式指定语法。<Address> - 指定泛型类型参数为 Address 类型。() - 函数调用的参数列表（无参数）
+    const LEN: usize = size_of::<u8>()                          // discriminator: 1 字节
+        + size_of::<[u8;1]>()                                   // bump: 1 字节
+        + size_of::<[u8;8]>()                                   // seed: 8 字节
+        + size_of::<Address>()                                  // maker: 32 字节
+        + size_of::<Address>()                                  // mint_a: 32 字节
+        + size_of::<Address>()                                  // mint_b: 32 字节
+        + size_of::<[u8;8]>()                                   // receive: 8 字节
+        + size_of::<[u8;8]>()                                   // expires_at: 8 字节
+        + size_of::<Address>()                                  // taker: 32 字节
+        + size_of::<[u8;32]>()                                  // allowlist_root: 32 字节
+        + size_of::<[u8;2]>()                                   // referral_bps: 2 字节
+        + size_of::<u8>()                                       // native: 1 字节
+        + size_of::<[u8;8]>()                                   // end_receive: 8 字节
+        + size_of::<[u8;8]>()                                   // auction_start: 8 字节
+        + size_of::<[u8;8]>()                                   // auction_end: 8 字节
+        + size_of::<Address>()                                  // rent_recipient: 32 字节
+        + size_of::<u8>();                                      // listed: 1 字节
+
+    // 账户类型判别器
+    const DISCRIMINATOR: AccountDiscriminator = AccountDiscriminator::Escrow;
+
+    // PDA 种子：["escrow", maker, seed.to_le_bytes(), bump]
+    const SEEDS: &'static [&'static [u8]] = &[b"escrow"];
+}
+
+// 编译期检查：
+// 1、LEN 必须与结构体的实际大小一致（没有隐式填充），否则 load/load_mut 会越界
+// 2、对齐必须为 1，账户数据在任意地址开始都可以安全地转换为 &Escrow
+const _: () = assert!(Escrow::LEN == size_of::<Escrow>());
+const _: () = assert!(core::mem::align_of::<Escrow>() == 1);
+
+// ============================================================================
+// 协议费（Protocol Fee）
+// ============================================================================
+
+// 基点（basis points）分母：1 bps = 0.01%，10_000 bps = 100%
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+// 按基点计算数量：floor(amount * bps / 10_000)
+// 向下取整，舍入误差归付款方；bps 超过 10_000 返回 InvalidFee
+// 乘法在 u128 中进行并使用 checked 运算，任何溢出都返回 ArithmeticOverflow 而不是回绕
+pub fn apply_bps(amount: u64, bps: u16) -> Result<u64, ProgramError> {
+    if bps > BPS_DENOMINATOR {
+        return Err(EscrowError::InvalidFee.into());
+    }
+    let value = (amount as u128)
+        .checked_mul(bps as u128)
+        .and_then(|product| product.checked_div(BPS_DENOMINATOR as u128))
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    u64::try_from(value).map_err(|_| ProgramError::ArithmeticOverflow)
+}
+
+// 拆分接受者支付的代币 B：返回 (创建者, 国库, 推荐人) 各自收到的数量
+// 没有传入 Config 或协议费从代币 A 收取时 fee_bps 为 0，没有推荐人时 referral_bps 为 0
+// 协议费和推荐费都按成交数量计算，两者之和超过成交数量时返回 InvalidFee
+pub fn split_fees(amount: u64, fee_bps: u16, referral_bps: u16) -> Result<(u64, u64, u64), ProgramError> {
+    let fee = apply_bps(amount, fee_bps)?;
+    let referral = apply_bps(amount, referral_bps)?;
+    let maker = amount
+        .checked_sub(fee)
+        .and_then(|amount| amount.checked_sub(referral))
+        .ok_or(EscrowError::InvalidFee)?;
+    Ok((maker, fee, referral))
+}
+
+// 协议费从成交的哪一侧收取
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FeeSide {
+    // 从接受者支付的代币 B 中收取（创建者少收）
+    TokenB = 0,
+
+    // 从金库释放的代币 A 中收取（接受者少收）
+    TokenA = 1,
+}
+
+impl TryFrom<u8> for FeeSide {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(FeeSide::TokenB),
+            1 => Ok(FeeSide::TokenA),
+            _ => Err(EscrowError::InvalidFee.into()),
+        }
+    }
+}
+
+/*
+Config：程序级全局配置账户（单例 PDA，种子 ["config"]）
+由管理员通过 InitConfig 初始化一次，之后只有管理员可以通过 UpdateConfig 修改
+
+Take / TakePartial / TakeMany / Match 按 fee_bps 从成交数量中扣除协议费，转入 treasury 的 ATA
+Config 在这些指令中是可选账户：传入本程序 ID 作为占位时协议费按 0 计算，
+因此部署后在 InitConfig 之前、以及不传 Config 的旧客户端都可以正常成交
+*/
+#[repr(C)]
+pub struct Config {
+    // 账户类型判别器：固定为 AccountDiscriminator::Config
+    discriminator: u8,
+
+    // Config PDA 的 bump
+    bump: [u8; 1],
+
+    // 协议费（基点），以小端字节序保存的 u16
+    fee_bps: [u8; 2],
+
+    // 收费的一侧（FeeSide）
+    fee_side: u8,
+
+    // 管理员：唯一可以修改配置的地址
+    admin: Address,
+
+    // 国库：协议费接收者（代币转入其 ATA）
+    treasury: Address,
+}
+
+impl ProgramAccountType for Config {
+    // 1 + 1 + 2 + 1 + 32 + 32 = 69 字节
+    const LEN: usize = size_of::<u8>()                          // discriminator: 1 字节
+        + size_of::<[u8;1]>()                                   // bump: 1 字节
+        + size_of::<[u8;2]>()                                   // fee_bps: 2 字节
+        + size_of::<u8>()                                       // fee_side: 1 字节
+        + size_of::<Address>()                                  // admin: 32 字节
+        + size_of::<Address>();                                 // treasury: 32 字节
+
+    const DISCRIMINATOR: AccountDiscriminator = AccountDiscriminator::Config;
+
+    // PDA 种子：["config", bump]
+    const SEEDS: &'static [&'static [u8]] = &[b"config"];
+}
+
+impl Config {
+    #[inline(always)]
+    pub fn bump(&self) -> [u8;1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn fee_bps(&self) -> u16 {
+        u16::from_le_bytes(self.fee_bps)
+    }
+
+    #[inline(always)]
+    pub fn fee_side(&self) -> Result<FeeSide, ProgramError> {
+        FeeSide::try_from(self.fee_side)
+    }
+
+    #[inline(always)]
+    pub fn admin(&self) -> &Address {
+        &self.admin
+    }
+
+    #[inline(always)]
+    pub fn treasury(&self) -> &Address {
+        &self.treasury
+    }
+
+    // 协议费：floor(amount * fee_bps / 10_000)
+    #[inline(always)]
+    pub fn fee(&self, amount: u64) -> Result<u64, ProgramError> {
+        apply_bps(amount, self.fee_bps())
+    }
+
+    // 一次性设置所有字段，fee_bps 不能超过 10_000
+    #[inline(always)]
+    pub fn set_inner(&mut self, bump: [u8;1], fee_bps: u16, fee_side: FeeSide, admin: Address, treasury: Address) -> Result<(), ProgramError> {
+        if fee_bps > BPS_DENOMINATOR {
+            return Err(EscrowError::InvalidFee.into());
+        }
+        self.bump = bump;
+        self.fee_bps = fee_bps.to_le_bytes();
+        self.fee_side = fee_side as u8;
+        self.admin = admin;
+        self.treasury = treasury;
+        Ok(())
+    }
+}
+
+const _: () = assert!(Config::LEN == size_of::<Config>());
+const _: () = assert!(core::mem::align_of::<Config>() == 1);
+
+/*
+Htlc：哈希时间锁托管（Hash Time-Locked Contract），用于跨链原子交换
+PDA 种子 ["htlc", maker, seed, bump]，金库是 Htlc PDA 的代币 A ATA
+
+流程：
+1、HtlcLock：创建者锁定代币 A，记录指定的接受者、hashlock = sha256(preimage) 和超时时间
+2、HtlcClaim：超时前，指定的接受者提交 preimage，哈希匹配即取走全部代币 A
+   preimage 通过 sol_log_data 写入交易日志，另一条链上的对手方据此完成对应的领取
+3、HtlcRefund：超时后，创建者取回代币 A
+
+与 Escrow 不同，本链上没有代币 B 一侧：对价在另一条链上通过相同的 hashlock 锁定
+*/
+#[repr(C)]
+pub struct Htlc {
+    // 账户类型判别器：固定为 AccountDiscriminator::Htlc
+    discriminator: u8,
+
+    bump: [u8; 1],
+
+    // 以小端字节序保存的 u64，允许同一创建者同时锁定多笔
+    seed: [u8; 8],
+
+    maker: Address,
+
+    // 唯一可以领取的地址
+    taker: Address,
+
+    mint_a: Address,
+
+    // sha256(preimage)
+    hashlock: [u8; 32],
+
+    // 超时时间：Unix 时间戳（秒），以小端字节序保存的 i64
+    // 早于超时时间只能 Claim，到达超时时间后只能 Refund
+    timeout: [u8; 8],
+}
+
+impl ProgramAccountType for Htlc {
+    // 1 + 1 + 8 + 32 + 32 + 32 + 32 + 8 = 146 字节
+    const LEN: usize = size_of::<u8>()                          // discriminator: 1 字节
+        + size_of::<[u8;1]>()                                   // bump: 1 字节
+        + size_of::<[u8;8]>()                                   // seed: 8 字节
+        + size_of::<Address>()                                  // maker: 32 字节
+        + size_of::<Address>()                                  // taker: 32 字节
+        + size_of::<Address>()                                  // mint_a: 32 字节
+        + size_of::<[u8;32]>()                                  // hashlock: 32 字节
+        + size_of::<[u8;8]>();                                  // timeout: 8 字节
+
+    const DISCRIMINATOR: AccountDiscriminator = AccountDiscriminator::Htlc;
+
+    // PDA 种子：["htlc", maker, seed.to_le_bytes(), bump]
+    const SEEDS: &'static [&'static [u8]] = &[b"htlc"];
+}
+
+impl Htlc {
+    #[inline(always)]
+    pub fn bump(&self) -> [u8;1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn maker(&self) -> &Address {
+        &self.maker
+    }
+
+    #[inline(always)]
+    pub fn taker(&self) -> &Address {
+        &self.taker
+    }
+
+    #[inline(always)]
+    pub fn mint_a(&self) -> &Address {
+        &self.mint_a
+    }
+
+    #[inline(always)]
+    pub fn hashlock(&self) -> &[u8; 32] {
+        &self.hashlock
+    }
+
+    #[inline(always)]
+    pub fn timeout(&self) -> i64 {
+        i64::from_le_bytes(self.timeout)
+    }
+
+    // 当前时间是否已经到达超时时间（含等于）
+    #[inline(always)]
+    pub fn is_timed_out(&self, now: i64) -> bool {
+        now >= self.timeout()
+    }
+
+    // 一次性设置所有字段
+    #[inline(always)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_inner(&mut self, seed: u64, maker: Address, taker: Address, mint_a: Address, hashlock: [u8; 32], timeout: i64, bump: [u8;1]) {
+        self.bump = bump;
+        self.seed = seed.to_le_bytes();
+        self.maker = maker;
+        self.taker = taker;
+        self.mint_a = mint_a;
+        self.hashlock = hashlock;
+        self.timeout = timeout.to_le_bytes();
+    }
+}
+
+const _: () = assert!(Htlc::LEN == size_of::<Htlc>());
+const _: () = assert!(core::mem::align_of::<Htlc>() == 1);
+
+/*
+ArbiterEscrow：三方托管（买方、卖方、仲裁人），用于自由职业付款
+PDA 种子 ["arbiter_escrow", buyer, seed, bump]，金库是该 PDA 的代币 ATA
+
+- 买方通过 ArbiterMake 存入代币
+- Release：买方确认后把代币释放给卖方
+- Cancel：卖方取消后把代币退还给买方
+- Dispute：买方或卖方发起争议，之后仲裁人也可以执行 Release 或 Cancel
+
+任何时候双方都可以让步（买方 Release、卖方 Cancel），仲裁人只在争议后才有权限
+金库与托管账户的租金始终退还给买方
+*/
+#[repr(C)]
+pub struct ArbiterEscrow {
+    // 账户类型判别器：固定为 AccountDiscriminator::ArbiterEscrow
+    discriminator: u8,
+
+    bump: [u8; 1],
+
+    // 以小端字节序保存的 u64
+    seed: [u8; 8],
+
+    buyer: Address,
+
+    seller: Address,
+
+    arbiter: Address,
+
+    mint: Address,
+
+    // 争议标记：0 表示没有争议，1 表示已发起争议
+    disputed: u8,
+}
+
+impl ProgramAccountType for ArbiterEscrow {
+    // 1 + 1 + 8 + 32 + 32 + 32 + 32 + 1 = 139 字节
+    const LEN: usize = size_of::<u8>()                          // discriminator: 1 字节
+        + size_of::<[u8;1]>()                                   // bump: 1 字节
+        + size_of::<[u8;8]>()                                   // seed: 8 字节
+        + size_of::<Address>()                                  // buyer: 32 字节
+        + size_of::<Address>()                                  // seller: 32 字节
+        + size_of::<Address>()                                  // arbiter: 32 字节
+        + size_of::<Address>()                                  // mint: 32 字节
+        + size_of::<u8>();                                      // disputed: 1 字节
+
+    const DISCRIMINATOR: AccountDiscriminator = AccountDiscriminator::ArbiterEscrow;
+
+    // PDA 种子：["arbiter_escrow", buyer, seed.to_le_bytes(), bump]
+    const SEEDS: &'static [&'static [u8]] = &[b"arbiter_escrow"];
+}
+
+impl ArbiterEscrow {
+    #[inline(always)]
+    pub fn bump(&self) -> [u8;1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn buyer(&self) -> &Address {
+        &self.buyer
+    }
+
+    #[inline(always)]
+    pub fn seller(&self) -> &Address {
+        &self.seller
+    }
+
+    #[inline(always)]
+    pub fn arbiter(&self) -> &Address {
+        &self.arbiter
+    }
+
+    #[inline(always)]
+    pub fn mint(&self) -> &Address {
+        &self.mint
+    }
+
+    #[inline(always)]
+    pub fn is_disputed(&self) -> bool {
+        self.disputed != 0
+    }
+
+    // 释放给卖方：买方随时可以，仲裁人只在争议后可以
+    #[inline(always)]
+    pub fn can_release(&self, authority: &Address) -> bool {
+        authority == &self.buyer || (self.is_disputed() && authority == &self.arbiter)
+    }
+
+    // 退还给买方：卖方随时可以，仲裁人只在争议后可以
+    #[inline(always)]
+    pub fn can_cancel(&self, authority: &Address) -> bool {
+        authority == &self.seller || (self.is_disputed() && authority == &self.arbiter)
+    }
+
+    #[inline(always)]
+    pub fn set_disputed(&mut self) {
+        self.disputed = 1;
+    }
+
+    // 一次性设置所有字段，新托管没有争议
+    #[inline(always)]
+    pub fn set_inner(&mut self, seed: u64, buyer: Address, seller: Address, arbiter: Address, mint: Address, bump: [u8;1]) {
+        self.bump = bump;
+        self.seed = seed.to_le_bytes();
+        self.buyer = buyer;
+        self.seller = seller;
+        self.arbiter = arbiter;
+        self.mint = mint;
+        self.disputed = 0;
+    }
+}
+
+const _: () = assert!(ArbiterEscrow::LEN == size_of::<ArbiterEscrow>());
+const _: () = assert!(core::mem::align_of::<ArbiterEscrow>() == 1);
+
+// 每个分阶段托管最多的里程碑数量，与 released 位图（u16）的位数一致
+pub const MAX_MILESTONES: usize = 16;
+
+/*
+MilestoneEscrow：分阶段释放的托管
+PDA 种子 ["milestone", payer, seed, bump]，金库是该 PDA 的代币 ATA
+
+- MilestoneMake：付款方按里程碑计划存入全部代币（各阶段数量之和）
+- ApproveMilestone：付款方（或仲裁人）逐个批准里程碑，把该阶段的数量释放给收款方
+  released 位图记录已释放的阶段，每个阶段只能释放一次；全部释放后关闭托管
+- RefundMilestones：把尚未释放的剩余代币退还给付款方
+  设置了仲裁人时只有仲裁人可以退款，收款方开工后付款方不能单方面撤回
+*/
+#[repr(C)]
+pub struct MilestoneEscrow {
+    // 账户类型判别器：固定为 AccountDiscriminator::MilestoneEscrow
+    discriminator: u8,
+
+    bump: [u8; 1],
+
+    // 以小端字节序保存的 u64
+    seed: [u8; 8],
+
+    payer: Address,
+
+    payee: Address,
+
+    // 仲裁人：全 0 地址表示没有仲裁人
+    arbiter: Address,
+
+    mint: Address,
+
+    // 里程碑数量：1..=MAX_MILESTONES
+    count: u8,
+
+    // 已释放的里程碑位图（小端 u16），第 i 位对应 amounts[i]
+    released: [u8; 2],
+
+    // 各里程碑的代币数量（小端 u64），只有前 count 个有效
+    amounts: [[u8; 8]; MAX_MILESTONES],
+}
+
+impl ProgramAccountType for MilestoneEscrow {
+    // 1 + 1 + 8 + 32 * 4 + 1 + 2 + 8 * 16 = 269 字节
+    const LEN: usize = size_of::<u8>()                          // discriminator: 1 字节
+        + size_of::<[u8;1]>()                                   // bump: 1 字节
+        + size_of::<[u8;8]>()                                   // seed: 8 字节
+        + size_of::<Address>()                                  // payer: 32 字节
+        + size_of::<Address>()                                  // payee: 32 字节
+        + size_of::<Address>()                                  // arbiter: 32 字节
+        + size_of::<Address>()                                  // mint: 32 字节
+        + size_of::<u8>()                                       // count: 1 字节
+        + size_of::<[u8;2]>()                                   // released: 2 字节
+        + size_of::<[[u8;8]; MAX_MILESTONES]>();                // amounts: 128 字节
+
+    const DISCRIMINATOR: AccountDiscriminator = AccountDiscriminator::MilestoneEscrow;
+
+    // PDA 种子：["milestone", payer, seed.to_le_bytes(), bump]
+    const SEEDS: &'static [&'static [u8]] = &[b"milestone"];
+}
+
+impl MilestoneEscrow {
+    #[inline(always)]
+    pub fn bump(&self) -> [u8;1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn payer(&self) -> &Address {
+        &self.payer
+    }
+
+    #[inline(always)]
+    pub fn payee(&self) -> &Address {
+        &self.payee
+    }
+
+    #[inline(always)]
+    pub fn arbiter(&self) -> Option<&Address> {
+        if self.arbiter == Address::default() {
+            return None;
+        }
+        Some(&self.arbiter)
+    }
+
+    #[inline(always)]
+    pub fn mint(&self) -> &Address {
+        &self.mint
+    }
+
+    #[inline(always)]
+    pub fn count(&self) -> usize {
+        self.count as usize
+    }
+
+    #[inline(always)]
+    pub fn released(&self) -> u16 {
+        u16::from_le_bytes(self.released)
+    }
+
+    // 第 index 个里程碑的数量，超出 count 返回 None
+    #[inline(always)]
+    pub fn amount(&self, index: usize) -> Option<u64> {
+        if index >= self.count() {
+            return None;
+        }
+        Some(u64::from_le_bytes(self.amounts[index]))
+    }
+
+    #[inline(always)]
+    pub fn is_released(&self, index: usize) -> bool {
+        index < MAX_MILESTONES && self.released() & (1 << index) != 0
+    }
+
+    // 前 count 个里程碑是否都已释放
+    #[inline(always)]
+    pub fn is_complete(&self) -> bool {
+        let all = ((1u32 << self.count()) - 1) as u16;
+        self.released() & all == all
+    }
+
+    // 批准里程碑：付款方随时可以，设置了仲裁人时仲裁人也可以
+    #[inline(always)]
+    pub fn can_approve(&self, authority: &Address) -> bool {
+        authority == &self.payer || self.arbiter() == Some(authority)
+    }
+
+    // 退还剩余代币：没有仲裁人时由付款方决定，否则只有仲裁人可以
+    #[inline(always)]
+    pub fn can_refund(&self, authority: &Address) -> bool {
+        match self.arbiter() {
+            Some(arbiter) => authority == arbiter,
+            None => authority == &self.payer,
+        }
+    }
+
+    // 标记第 index 个里程碑已释放，返回该阶段的数量
+    // 索引超出 count 返回 InvalidInstructionData，重复释放返回 MilestoneAlreadyReleased
+    #[inline(always)]
+    pub fn release(&mut self, index: usize) -> Result<u64, ProgramError> {
+        let amount = self.amount(index).ok_or(ProgramError::InvalidInstructionData)?;
+        if self.is_released(index) {
+            return Err(EscrowError::MilestoneAlreadyReleased.into());
+        }
+        self.released = (self.released() | (1 << index)).to_le_bytes();
+        Ok(amount)
+    }
+
+    // 一次性设置所有字段，amounts 的长度即里程碑数量（1..=MAX_MILESTONES）
+    #[inline(always)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_inner(&mut self, seed: u64, payer: Address, payee: Address, arbiter: Address, mint: Address, amounts: &[u64], bump: [u8;1]) -> Result<(), ProgramError> {
+        if amounts.is_empty() || amounts.len() > MAX_MILESTONES {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        self.bump = bump;
+        self.seed = seed.to_le_bytes();
+        self.payer = payer;
+        self.payee = payee;
+        self.arbiter = arbiter;
+        self.mint = mint;
+        self.count = amounts.len() as u8;
+        self.released = [0; 2];
+        self.amounts = [[0; 8]; MAX_MILESTONES];
+        for (slot, amount) in self.amounts.iter_mut().zip(amounts) {
+            *slot = amount.to_le_bytes();
+        }
+        Ok(())
+    }
+}
+
+const _: () = assert!(MilestoneEscrow::LEN == size_of::<MilestoneEscrow>());
+const _: () = assert!(core::mem::align_of::<MilestoneEscrow>() == 1);
+
+// 每个 NonceBitmap 账户记录的 nonce 数量（256 字节位图）
+pub const NONCES_PER_PAGE: u64 = 2048;
+
+/*
+NonceBitmap：链下签名订单（FillSignedOrder）的防重放位图
+PDA 种子 ["nonces", maker, page, bump]，page = nonce / NONCES_PER_PAGE
+
+- 每个 nonce 对应一位，成交后置 1，同一个签名订单不能成交两次
+- 账户在该页第一次成交时由接受者创建，创建者不需要发送任何交易
+- 创建者想作废一个尚未成交的订单时，撤销对 delegate PDA 的授权即可
+*/
+#[repr(C)]
+pub struct NonceBitmap {
+    // 账户类型判别器：固定为 AccountDiscriminator::NonceBitmap
+    discriminator: u8,
+
+    bump: [u8; 1],
+
+    maker: Address,
+
+    // 以小端字节序保存的 u64
+    page: [u8; 8],
+
+    // 第 i 位对应 nonce = page * NONCES_PER_PAGE + i
+    bits: [u8; (NONCES_PER_PAGE / 8) as usize],
+}
+
+impl ProgramAccountType for NonceBitmap {
+    // 1 + 1 + 32 + 8 + 256 = 298 字节
+    const LEN: usize = size_of::<u8>()                          // discriminator: 1 字节
+        + size_of::<[u8;1]>()                                   // bump: 1 字节
+        + size_of::<Address>()                                  // maker: 32 字节
+        + size_of::<[u8;8]>()                                   // page: 8 字节
+        + (NONCES_PER_PAGE / 8) as usize;                       // bits: 256 字节
+
+    const DISCRIMINATOR: AccountDiscriminator = AccountDiscriminator::NonceBitmap;
+
+    // PDA 种子：["nonces", maker, page.to_le_bytes(), bump]
+    const SEEDS: &'static [&'static [u8]] = &[b"nonces"];
+}
+
+impl NonceBitmap {
+    // nonce 所在的页
+    #[inline(always)]
+    pub fn page_of(nonce: u64) -> u64 {
+        nonce / NONCES_PER_PAGE
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8;1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn maker(&self) -> &Address {
+        &self.maker
+    }
+
+    #[inline(always)]
+    pub fn page(&self) -> u64 {
+        u64::from_le_bytes(self.page)
+    }
+
+    // nonce 不属于本页时返回 InvalidArgument
+    #[inline(always)]
+    fn position(&self, nonce: u64) -> Result<(usize, u8), ProgramError> {
+        if Self::page_of(nonce) != self.page() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let offset = nonce % NONCES_PER_PAGE;
+        Ok(((offset / 8) as usize, 1 << (offset % 8)))
+    }
+
+    #[inline(always)]
+    pub fn is_used(&self, nonce: u64) -> Result<bool, ProgramError> {
+        let (byte, mask) = self.position(nonce)?;
+        Ok(self.bits[byte] & mask != 0)
+    }
+
+    // 标记 nonce 已使用；已使用过返回 NonceAlreadyUsed
+    #[inline(always)]
+    pub fn use_nonce(&mut self, nonce: u64) -> Result<(), ProgramError> {
+        let (byte, mask) = self.position(nonce)?;
+        if self.bits[byte] & mask != 0 {
+            return Err(EscrowError::NonceAlreadyUsed.into());
+        }
+        self.bits[byte] |= mask;
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, maker: Address, page: u64, bump: [u8;1]) {
+        self.bump = bump;
+        self.maker = maker;
+        self.page = page.to_le_bytes();
+        self.bits = [0; (NONCES_PER_PAGE / 8) as usize];
+    }
+}
+
+const _: () = assert!(NonceBitmap::LEN == size_of::<NonceBitmap>());
+const _: () = assert!(core::mem::align_of::<NonceBitmap>() == 1);
+
+/*
+Auction：英式拍卖（公开递增出价）
+PDA 种子 ["auction", maker, seed, bump]，金库是该 PDA 的代币 A ATA
+
+- AuctionMake：创建者存入代币 A，设定最低出价和结束时间
+- PlaceBid：出价者把代币 B 锁定在自己的出价 PDA ["bid", auction, bidder] 的 ATA 中
+  新的最高出价必须高于当前最高出价，上一位最高出价者的代币 B 在同一条指令中退还
+  当前最高出价者再次出价时只补足差额
+- SettleAuction：结束后任何人都可以结算，代币 A 给最高出价者，代币 B 给创建者
+  没有人出价时代币 A 退还给创建者
+*/
+#[repr(C)]
+pub struct Auction {
+    // 账户类型判别器：固定为 AccountDiscriminator::Auction
+    discriminator: u8,
+
+    bump: [u8; 1],
+
+    // 以小端字节序保存的 u64
+    seed: [u8; 8],
+
+    maker: Address,
+
+    mint_a: Address,
+
+    mint_b: Address,
+
+    // 最低出价（小端 u64），第一笔出价不能低于它
+    min_bid: [u8; 8],
+
+    // 结束时间（小端 i64，unix 秒），到达后不能再出价
+    end_time: [u8; 8],
+
+    // 当前最高出价者：全 0 地址表示还没有人出价
+    highest_bidder: Address,
+
+    // 当前最高出价（小端 u64）
+    highest_bid: [u8; 8],
+}
+
+impl ProgramAccountType for Auction {
+    // 1 + 1 + 8 + 32 * 3 + 8 + 8 + 32 + 8 = 162 字节
+    const LEN: usize = size_of::<u8>()                          // discriminator: 1 字节
+        + size_of::<[u8;1]>()                                   // bump: 1 字节
+        + size_of::<[u8;8]>()                                   // seed: 8 字节
+        + size_of::<Address>()                                  // maker: 32 字节
+        + size_of::<Address>()                                  // mint_a: 32 字节
+        + size_of::<Address>()                                  // mint_b: 32 字节
+        + size_of::<[u8;8]>()                                   // min_bid: 8 字节
+        + size_of::<[u8;8]>()                                   // end_time: 8 字节
+        + size_of::<Address>()                                  // highest_bidder: 32 字节
+        + size_of::<[u8;8]>();                                  // highest_bid: 8 字节
+
+    const DISCRIMINATOR: AccountDiscriminator = AccountDiscriminator::Auction;
+
+    // PDA 种子：["auction", maker, seed.to_le_bytes(), bump]
+    const SEEDS: &'static [&'static [u8]] = &[b"auction"];
+}
+
+impl Auction {
+    #[inline(always)]
+    pub fn bump(&self) -> [u8;1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn maker(&self) -> &Address {
+        &self.maker
+    }
+
+    #[inline(always)]
+    pub fn mint_a(&self) -> &Address {
+        &self.mint_a
+    }
+
+    #[inline(always)]
+    pub fn mint_b(&self) -> &Address {
+        &self.mint_b
+    }
+
+    #[inline(always)]
+    pub fn min_bid(&self) -> u64 {
+        u64::from_le_bytes(self.min_bid)
+    }
+
+    #[inline(always)]
+    pub fn end_time(&self) -> i64 {
+        i64::from_le_bytes(self.end_time)
+    }
+
+    // 到达结束时间的那一秒起不能再出价，可以结算
+    #[inline(always)]
+    pub fn is_ended(&self, now: i64) -> bool {
+        now >= self.end_time()
+    }
+
+    #[inline(always)]
+    pub fn highest_bidder(&self) -> Option<&Address> {
+        if self.highest_bidder == Address::default() {
+            return None;
+        }
+        Some(&self.highest_bidder)
+    }
+
+    #[inline(always)]
+    pub fn highest_bid(&self) -> u64 {
+        u64::from_le_bytes(self.highest_bid)
+    }
+
+    // 记录新的最高出价：第一笔不低于 min_bid，之后必须高于当前最高出价，否则返回 BidTooLow
+    #[inline(always)]
+    pub fn place_bid(&mut self, bidder: Address, amount: u64) -> Result<(), ProgramError> {
+        let too_low = match self.highest_bidder() {
+            None => amount < self.min_bid(),
+            Some(_) => amount <= self.highest_bid(),
+        };
+        if too_low {
+            return Err(EscrowError::BidTooLow.into());
+        }
+        self.highest_bidder = bidder;
+        self.highest_bid = amount.to_le_bytes();
+        Ok(())
+    }
+
+    #[inline(always)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_inner(&mut self, seed: u64, maker: Address, mint_a: Address, mint_b: Address, min_bid: u64, end_time: i64, bump: [u8;1]) {
+        self.bump = bump;
+        self.seed = seed.to_le_bytes();
+        self.maker = maker;
+        self.mint_a = mint_a;
+        self.mint_b = mint_b;
+        self.min_bid = min_bid.to_le_bytes();
+        self.end_time = end_time.to_le_bytes();
+        self.highest_bidder = Address::default();
+        self.highest_bid = [0; 8];
+    }
+}
+
+const _: () = assert!(Auction::LEN == size_of::<Auction>());
+const _: () = assert!(core::mem::align_of::<Auction>() == 1);
+
+// 每个组合托管每一边最多的代币种类数量
+pub const MAX_BUNDLE_LEGS: usize = 4;
+
+/*
+BundleEscrow：组合托管，一次交换多种代币
+PDA 种子 ["bundle", maker, seed, bump]，每种存入的代币各有一个金库（该 PDA 的 ATA）
+
+- BundleMake：创建者存入最多 MAX_BUNDLE_LEGS 种代币（give），要求换回最多 MAX_BUNDLE_LEGS 种代币（receive）
+- BundleTake：接受者支付全部 receive，取走全部 give，所有转账在同一条指令中完成，要么全部成功要么全部失败
+- BundleRefund：创建者取回全部 give 并关闭托管
+
+每一边的 mint 不能重复（同一 mint 的金库是同一个 ATA）；各代币的账户通过剩余账户按 give、receive 的顺序传入
+*/
+#[repr(C)]
+pub struct BundleEscrow {
+    // 账户类型判别器：固定为 AccountDiscriminator::BundleEscrow
+    discriminator: u8,
+
+    bump: [u8; 1],
+
+    // 以小端字节序保存的 u64
+    seed: [u8; 8],
+
+    maker: Address,
+
+    // 存入的代币种类数量：1..=MAX_BUNDLE_LEGS
+    give_count: u8,
+
+    // 要求换回的代币种类数量：1..=MAX_BUNDLE_LEGS
+    receive_count: u8,
+
+    // 存入的代币，只有前 give_count 个有效
+    give_mints: [Address; MAX_BUNDLE_LEGS],
+
+    // 各代币存入的数量（小端 u64）
+    give_amounts: [[u8; 8]; MAX_BUNDLE_LEGS],
+
+    // 要求换回的代币，只有前 receive_count 个有效
+    receive_mints: [Address; MAX_BUNDLE_LEGS],
+
+    // 各代币要求换回的数量（小端 u64）
+    receive_amounts: [[u8; 8]; MAX_BUNDLE_LEGS],
+}
+
+impl ProgramAccountType for BundleEscrow {
+    // 1 + 1 + 8 + 32 + 1 + 1 + (32 + 8) * 4 * 2 = 364 字节
+    const LEN: usize = size_of::<u8>()                          // discriminator: 1 字节
+        + size_of::<[u8;1]>()                                   // bump: 1 字节
+        + size_of::<[u8;8]>()                                   // seed: 8 字节
+        + size_of::<Address>()                                  // maker: 32 字节
+        + size_of::<u8>()                                       // give_count: 1 字节
+        + size_of::<u8>()                                       // receive_count: 1 字节
+        + size_of::<[Address; MAX_BUNDLE_LEGS]>()               // give_mints: 128 字节
+        + size_of::<[[u8;8]; MAX_BUNDLE_LEGS]>()                // give_amounts: 32 字节
+        + size_of::<[Address; MAX_BUNDLE_LEGS]>()               // receive_mints: 128 字节
+        + size_of::<[[u8;8]; MAX_BUNDLE_LEGS]>();               // receive_amounts: 32 字节
+
+    const DISCRIMINATOR: AccountDiscriminator = AccountDiscriminator::BundleEscrow;
+
+    // PDA 种子：["bundle", maker, seed.to_le_bytes(), bump]
+    const SEEDS: &'static [&'static [u8]] = &[b"bundle"];
+}
+
+impl BundleEscrow {
+    #[inline(always)]
+    pub fn bump(&self) -> [u8;1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn maker(&self) -> &Address {
+        &self.maker
+    }
+
+    #[inline(always)]
+    pub fn give_count(&self) -> usize {
+        self.give_count as usize
+    }
+
+    #[inline(always)]
+    pub fn receive_count(&self) -> usize {
+        self.receive_count as usize
+    }
+
+    // 第 index 种存入的代币及数量，超出 give_count 返回 None
+    #[inline(always)]
+    pub fn give(&self, index: usize) -> Option<(&Address, u64)> {
+        if index >= self.give_count() {
+            return None;
+        }
+        Some((&self.give_mints[index], u64::from_le_bytes(self.give_amounts[index])))
+    }
+
+    // 第 index 种要求换回的代币及数量，超出 receive_count 返回 None
+    #[inline(always)]
+    pub fn receive(&self, index: usize) -> Option<(&Address, u64)> {
+        if index >= self.receive_count() {
+            return None;
+        }
+        Some((&self.receive_mints[index], u64::from_le_bytes(self.receive_amounts[index])))
+    }
+
+    // 复制出全部存入的代币 (mint, 数量)，只有前 give_count 个有效
+    #[inline(always)]
+    pub fn give_legs(&self) -> [(Address, u64); MAX_BUNDLE_LEGS] {
+        core::array::from_fn(|index| (self.give_mints[index], u64::from_le_bytes(self.give_amounts[index])))
+    }
+
+    // 复制出全部要求换回的代币 (mint, 数量)，只有前 receive_count 个有效
+    #[inline(always)]
+    pub fn receive_legs(&self) -> [(Address, u64); MAX_BUNDLE_LEGS] {
+        core::array::from_fn(|index| (self.receive_mints[index], u64::from_le_bytes(self.receive_amounts[index])))
+    }
+
+    // 检查一边的代币：数量在 1..=MAX_BUNDLE_LEGS 之间，mint 不重复，数量不为 0
+    fn check_legs(legs: &[(Address, u64)]) -> Result<(), ProgramError> {
+        if legs.is_empty() || legs.len() > MAX_BUNDLE_LEGS {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        for (index, (mint, amount)) in legs.iter().enumerate() {
+            if *amount == 0 {
+                return Err(EscrowError::InvalidAmount.into());
+            }
+            if legs[..index].iter().any(|(other, _)| other == mint) {
+                return Err(EscrowError::InvalidAddress.into());
+            }
+        }
+        Ok(())
+    }
+
+    // 一次性设置所有字段，give / receive 为 (mint, 数量) 列表
+    #[inline(always)]
+    pub fn set_inner(&mut self, seed: u64, maker: Address, give: &[(Address, u64)], receive: &[(Address, u64)], bump: [u8;1]) -> Result<(), ProgramError> {
+        Self::check_legs(give)?;
+        Self::check_legs(receive)?;
+        self.bump = bump;
+        self.seed = seed.to_le_bytes();
+        self.maker = maker;
+        self.give_count = give.len() as u8;
+        self.receive_count = receive.len() as u8;
+        self.give_mints = [Address::default(); MAX_BUNDLE_LEGS];
+        self.give_amounts = [[0; 8]; MAX_BUNDLE_LEGS];
+        self.receive_mints = [Address::default(); MAX_BUNDLE_LEGS];
+        self.receive_amounts = [[0; 8]; MAX_BUNDLE_LEGS];
+        for (index, (mint, amount)) in give.iter().enumerate() {
+            self.give_mints[index] = *mint;
+            self.give_amounts[index] = amount.to_le_bytes();
+        }
+        for (index, (mint, amount)) in receive.iter().enumerate() {
+            self.receive_mints[index] = *mint;
+            self.receive_amounts[index] = amount.to_le_bytes();
+        }
+        Ok(())
+    }
+}
+
+const _: () = assert!(BundleEscrow::LEN == size_of::<BundleEscrow>());
+const _: () = assert!(core::mem::align_of::<BundleEscrow>() == 1);
+
+/*
+MakerState：每个创建者一个的托管 seed 计数器
+PDA 种子 ["maker", maker, bump]
+
+- Make 的指令数据中 seed 为 0 且在剩余账户中传入该账户时，取 next_seed 作为托管的 seed，然后加 1
+- 账户在创建者第一次使用计数器时创建（由 Make 的 payer 支付），next_seed 从 0 开始
+- 通过计数器创建的托管 seed 依次为 0..next_seed，客户端按序号派生地址即可列出创建者的全部托管，
+  不需要 getProgramAccounts 扫描（已成交或取消的托管账户不存在）
+- 计数器分配的 seed 与创建者自己指定的 seed 在同一个命名空间中：next_seed 已被占用时，客户端传入之后第一个空闲 seed 的托管，
+  Make 跳过中间的 seed（最多 Make::MAX_SEED_SKIP 个）
+*/
+#[repr(C)]
+pub struct MakerState {
+    // 账户类型判别器：固定为 AccountDiscriminator::MakerState
+    discriminator: u8,
+
+    bump: [u8; 1],
+
+    maker: Address,
+
+    // 以小端字节序保存的 u64：下一个托管使用的 seed
+    next_seed: [u8; 8],
+}
+
+impl ProgramAccountType for MakerState {
+    // 1 + 1 + 32 + 8 = 42 字节
+    const LEN: usize = size_of::<u8>()                          // discriminator: 1 字节
+        + size_of::<[u8;1]>()                                   // bump: 1 字节
+        + size_of::<Address>()                                  // maker: 32 字节
+        + size_of::<[u8;8]>();                                  // next_seed: 8 字节
+
+    const DISCRIMINATOR: AccountDiscriminator = AccountDiscriminator::MakerState;
+
+    // PDA 种子：["maker", maker, bump]
+    const SEEDS: &'static [&'static [u8]] = &[b"maker"];
+}
+
+impl MakerState {
+    #[inline(always)]
+    pub fn bump(&self) -> [u8;1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn maker(&self) -> &Address {
+        &self.maker
+    }
+
+    #[inline(always)]
+    pub fn next_seed(&self) -> u64 {
+        u64::from_le_bytes(self.next_seed)
+    }
+
+    // 取出下一个 seed 并把计数器加 1
+    #[inline(always)]
+    pub fn allocate_seed(&mut self) -> Result<u64, ProgramError> {
+        let seed = self.next_seed();
+        self.next_seed = seed
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            .to_le_bytes();
+        Ok(seed)
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, maker: Address, bump: [u8;1]) {
+        self.bump = bump;
+        self.maker = maker;
+        self.next_seed = [0; 8];
+    }
+}
+
+const _: () = assert!(MakerState::LEN == size_of::<MakerState>());
+const _: () = assert!(core::mem::align_of::<MakerState>() == 1);
+
+// 每个 Market 账户最多记录的挂单数量
+pub const MAX_MARKET_ORDERS: usize = 32;
+
+// Market 中的一条挂单：托管地址及其价格（用 receive / amount 两个数量表示，避免定点数的精度问题）
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct MarketOrder {
+    escrow: Address,
+
+    // 以小端字节序保存的 u64：托管要求换回的代币 B 数量
+    receive: [u8; 8],
+
+    // 以小端字节序保存的 u64：托管存入的代币 A 数量
+    amount: [u8; 8],
+}
+
+impl MarketOrder {
+    #[inline(always)]
+    pub fn escrow(&self) -> &Address {
+        &self.escrow
+    }
+
+    #[inline(always)]
+    pub fn receive(&self) -> u64 {
+        u64::from_le_bytes(self.receive)
+    }
+
+    #[inline(always)]
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+
+    // 每个代币 A 的价格是否严格低于 other：receive / amount < other.receive / other.amount
+    // 交叉相乘在 u128 中进行，不会溢出
+    #[inline(always)]
+    fn cheaper_than(&self, other: &MarketOrder) -> bool {
+        (self.receive() as u128) * (other.amount() as u128) < (other.receive() as u128) * (self.amount() as u128)
+    }
+}
+
+/*
+Market：一个交易对（mint_a -> mint_b）的链上挂单索引
+PDA 种子 ["market", mint_a, mint_b, bump]
+
+- 按价格（每个代币 A 要求的代币 B）从低到高排列，前端读取一个账户即可展示最优报价
+- Make 在剩余账户中传入 Market 时挂单（账户不存在则由 Make 的 payer 创建）
+- Update / TakePartial 传入时按新的期望数量和剩余数量重新挂单，关闭托管的指令（Take / Refund / Expire / TakeMany / Match 等）传入时撤单
+- 最多 MAX_MARKET_ORDERS 条：已满时新挂单比最差的挂单更便宜才会加入，并挤掉最差的一条
+  （被挤掉的托管仍然有效，只是不在索引中）
+- 价格相同时先挂单的排在前面
+- 索引只是辅助信息：调用者没有传入 Market 时索引不会更新，荷兰式拍卖的当前价格也随时间变化，
+  前端仍需读取托管账户确认当前条款；已关闭的托管可以由任何人通过 PruneMarket 移除
+*/
+#[repr(C)]
+pub struct Market {
+    // 账户类型判别器：固定为 AccountDiscriminator::Market
+    discriminator: u8,
+
+    bump: [u8; 1],
+
+    mint_a: Address,
+
+    mint_b: Address,
+
+    // 有效挂单数量：orders[..len]
+    len: u8,
+
+    orders: [MarketOrder; MAX_MARKET_ORDERS],
+}
+
+impl ProgramAccountType for Market {
+    // 1 + 1 + 32 + 32 + 1 + 48 * 32 = 1603 字节
+    const LEN: usize = size_of::<u8>()                          // discriminator: 1 字节
+        + size_of::<[u8;1]>()                                   // bump: 1 字节
+        + size_of::<Address>()                                  // mint_a: 32 字节
+        + size_of::<Address>()                                  // mint_b: 32 字节
+        + size_of::<u8>()                                       // len: 1 字节
+        + size_of::<MarketOrder>() * MAX_MARKET_ORDERS;         // orders: 48 * 32 字节
+
+    const DISCRIMINATOR: AccountDiscriminator = AccountDiscriminator::Market;
+
+    // PDA 种子：["market", mint_a, mint_b, bump]
+    const SEEDS: &'static [&'static [u8]] = &[b"market"];
+}
+
+impl Market {
+    #[inline(always)]
+    pub fn bump(&self) -> [u8;1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn mint_a(&self) -> &Address {
+        &self.mint_a
+    }
+
+    #[inline(always)]
+    pub fn mint_b(&self) -> &Address {
+        &self.mint_b
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        (self.len as usize).min(MAX_MARKET_ORDERS)
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // 按价格从低到高排列的挂单
+    #[inline(always)]
+    pub fn orders(&self) -> &[MarketOrder] {
+        &self.orders[..self.len()]
+    }
+
+    #[inline(always)]
+    pub fn contains(&self, escrow: &Address) -> bool {
+        self.orders().iter().any(|order| order.escrow() == escrow)
+    }
+
+    // 按价格插入挂单，同一个托管已在索引中时先移除旧的挂单
+    // 返回是否加入了索引：已满且不比最差的挂单便宜时不加入
+    #[inline(always)]
+    pub fn insert(&mut self, escrow: Address, receive: u64, amount: u64) -> bool {
+        self.remove(&escrow);
+
+        let order = MarketOrder {
+            escrow,
+            receive: receive.to_le_bytes(),
+            amount: amount.to_le_bytes(),
+        };
+        let len = self.len();
+        let position = self
+            .orders()
+            .iter()
+            .position(|existing| order.cheaper_than(existing))
+            .unwrap_or(len);
+        if position == MAX_MARKET_ORDERS {
+            return false;
+        }
+
+        // 已满时最后一条（最差的挂单）被移出
+        let end = (len + 1).min(MAX_MARKET_ORDERS);
+        self.orders[position..end].rotate_right(1);
+        self.orders[position] = order;
+        self.len = end as u8;
+        true
+    }
+
+    // 移除托管的挂单，返回是否找到
+    #[inline(always)]
+    pub fn remove(&mut self, escrow: &Address) -> bool {
+        let len = self.len();
+        let Some(index) = self.orders().iter().position(|order| order.escrow() == escrow) else {
+            return false;
+        };
+        self.orders[index..len].rotate_left(1);
+        self.orders[len - 1] = MarketOrder {
+            escrow: Address::default(),
+            receive: [0; 8],
+            amount: [0; 8],
+        };
+        self.len = (len - 1) as u8;
+        true
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, mint_a: Address, mint_b: Address, bump: [u8;1]) {
+        self.bump = bump;
+        self.mint_a = mint_a;
+        self.mint_b = mint_b;
+        self.len = 0;
+    }
+}
+
+const _: () = assert!(size_of::<MarketOrder>() == 48);
+const _: () = assert!(Market::LEN == size_of::<Market>());
+const _: () = assert!(core::mem::align_of::<Market>() == 1);
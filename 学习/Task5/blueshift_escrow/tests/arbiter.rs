@@ -0,0 +1,137 @@
+// 三方托管（仲裁人）的集成测试：构建与运行方式见 tests/common/mod.rs
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use mollusk_svm::{program, result::Check};
+use solana_program::program_error::ProgramError;
+use solana_pubkey::Pubkey;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+use common::*;
+
+// 三方托管：MAKER 为买方，TAKER 为卖方
+fn arbiter_escrow_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"arbiter_escrow", MAKER.as_ref(), &SEED.to_le_bytes()],
+        &ESCROW_ID,
+    )
+    .0
+}
+
+fn arbiter_make_instruction() -> Instruction {
+    let escrow = arbiter_escrow_address();
+    let mut data = vec![11u8];
+    data.extend_from_slice(&SEED.to_le_bytes());
+    data.extend_from_slice(&DEPOSIT.to_le_bytes());
+    data.extend_from_slice(TAKER.as_ref());
+    data.extend_from_slice(ARBITER.as_ref());
+
+    let mut accounts = vec![
+        AccountMeta::new(MAKER, true),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new_readonly(MINT_A, false),
+        AccountMeta::new(ata(&MAKER, &MINT_A), false),
+        AccountMeta::new(ata(&escrow, &MINT_A), false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    accounts.extend(hook_accounts());
+
+    Instruction::new_with_bytes(ESCROW_ID, &data, accounts)
+}
+
+// Release（12）把代币转给卖方，Cancel（13）退还给买方
+fn arbiter_settle_instruction(discriminator: u8, authority: Pubkey) -> Instruction {
+    let escrow = arbiter_escrow_address();
+    let recipient = if discriminator == 12 { TAKER } else { MAKER };
+    let mut accounts = vec![
+        AccountMeta::new(authority, true),
+        AccountMeta::new(MAKER, false),
+        AccountMeta::new_readonly(TAKER, false),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new_readonly(MINT_A, false),
+        AccountMeta::new(ata(&escrow, &MINT_A), false),
+        AccountMeta::new(ata(&recipient, &MINT_A), false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    accounts.extend(hook_accounts());
+
+    Instruction::new_with_bytes(ESCROW_ID, &[discriminator], accounts)
+}
+
+fn dispute_instruction(authority: Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        ESCROW_ID,
+        &[14],
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(arbiter_escrow_address(), false),
+        ],
+    )
+}
+
+#[test]
+fn buyer_releases_to_seller() {
+    let context = setup();
+    context.process_and_validate_instruction(&arbiter_make_instruction(), &[Check::success()]);
+
+    context.process_and_validate_instruction(
+        &arbiter_settle_instruction(12, MAKER),
+        &[Check::success(), Check::account(&arbiter_escrow_address()).closed().build()],
+    );
+
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), DEPOSIT);
+    assert_eq!(counter(&context), 2);
+}
+
+#[test]
+fn seller_cancels_to_buyer() {
+    let context = setup();
+    context.process_and_validate_instruction(&arbiter_make_instruction(), &[Check::success()]);
+
+    // 买方不能替卖方取消
+    context.process_and_validate_instruction(
+        &arbiter_settle_instruction(13, MAKER),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::InvalidAuthority as u32,
+        ))],
+    );
+    context.process_and_validate_instruction(
+        &arbiter_settle_instruction(13, TAKER),
+        &[Check::success(), Check::account(&arbiter_escrow_address()).closed().build()],
+    );
+
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_A)), DEPOSIT);
+}
+
+#[test]
+fn arbiter_decides_only_after_dispute() {
+    let context = setup();
+    context.process_and_validate_instruction(&arbiter_make_instruction(), &[Check::success()]);
+
+    context.process_and_validate_instruction(
+        &arbiter_settle_instruction(12, ARBITER),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::InvalidAuthority as u32,
+        ))],
+    );
+    // 仲裁人自己不能发起争议
+    context.process_and_validate_instruction(
+        &dispute_instruction(ARBITER),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::InvalidAuthority as u32,
+        ))],
+    );
+
+    context.process_and_validate_instruction(&dispute_instruction(TAKER), &[Check::success()]);
+    context.process_and_validate_instruction(
+        &arbiter_settle_instruction(12, ARBITER),
+        &[Check::success(), Check::account(&arbiter_escrow_address()).closed().build()],
+    );
+
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), DEPOSIT);
+}
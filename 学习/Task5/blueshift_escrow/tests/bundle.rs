@@ -0,0 +1,175 @@
+// 组合托管的集成测试：构建与运行方式见 tests/common/mod.rs
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use std::collections::HashMap;
+
+use mollusk_svm::{program, result::Check, MolluskContext};
+use solana_account::Account;
+use solana_program::program_error::ProgramError;
+use solana_pubkey::Pubkey;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+use common::*;
+
+const MINT_C: Pubkey = Pubkey::new_from_array([11u8; 32]);
+const MINT_D: Pubkey = Pubkey::new_from_array([12u8; 32]);
+
+// 组合托管：MAKER 存入 MINT_A（带钩子）和 MINT_C，要求换回 MINT_B 和 MINT_D
+fn bundle_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"bundle", MAKER.as_ref(), &SEED.to_le_bytes()],
+        &ESCROW_ID,
+    )
+    .0
+}
+
+fn bundle_make_instruction() -> Instruction {
+    let escrow = bundle_address();
+    let mut data = vec![22u8];
+    data.extend_from_slice(&SEED.to_le_bytes());
+    data.extend_from_slice(&[2, 2]);
+    for amount in [DEPOSIT, DEPOSIT, RECEIVE, RECEIVE] {
+        data.extend_from_slice(&amount.to_le_bytes());
+    }
+
+    let mut accounts = vec![
+        AccountMeta::new(MAKER, true),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    for mint in [MINT_A, MINT_C] {
+        accounts.extend([
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(ata(&MAKER, &mint), false),
+            AccountMeta::new(ata(&escrow, &mint), false),
+        ]);
+    }
+    accounts.extend([AccountMeta::new_readonly(MINT_B, false), AccountMeta::new_readonly(MINT_D, false)]);
+    accounts.extend(hook_accounts());
+
+    Instruction::new_with_bytes(ESCROW_ID, &data, accounts)
+}
+
+// receive 为要求换回的代币顺序，用于测试顺序错误的情况
+fn bundle_take_instruction(receive: [Pubkey; 2]) -> Instruction {
+    let escrow = bundle_address();
+    let mut accounts = vec![
+        AccountMeta::new(TAKER, true),
+        AccountMeta::new(MAKER, false),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    for mint in [MINT_A, MINT_C] {
+        accounts.extend([
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(ata(&escrow, &mint), false),
+            AccountMeta::new(ata(&TAKER, &mint), false),
+        ]);
+    }
+    for mint in receive {
+        accounts.extend([
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(ata(&TAKER, &mint), false),
+            AccountMeta::new(ata(&MAKER, &mint), false),
+        ]);
+    }
+    accounts.extend(hook_accounts());
+
+    Instruction::new_with_bytes(ESCROW_ID, &[23], accounts)
+}
+
+fn bundle_refund_instruction() -> Instruction {
+    let escrow = bundle_address();
+    let mut accounts = vec![
+        AccountMeta::new(MAKER, true),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    for mint in [MINT_A, MINT_C] {
+        accounts.extend([
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(ata(&escrow, &mint), false),
+            AccountMeta::new(ata(&MAKER, &mint), false),
+        ]);
+    }
+    accounts.extend(hook_accounts());
+
+    Instruction::new_with_bytes(ESCROW_ID, &[24], accounts)
+}
+
+// MAKER 另持有 DEPOSIT 个 MINT_C，TAKER 另持有 RECEIVE 个 MINT_D
+fn setup_bundle() -> MolluskContext<HashMap<Pubkey, Account>> {
+    let context = setup();
+    let accounts = [
+        (MINT_C, owned_account(&context, mint_data(false), &TOKEN_2022_ID)),
+        (MINT_D, owned_account(&context, mint_data(false), &TOKEN_2022_ID)),
+        (
+            ata(&MAKER, &MINT_C),
+            owned_account(&context, token_account_data(&MINT_C, &MAKER, DEPOSIT, false), &TOKEN_2022_ID),
+        ),
+        (
+            ata(&TAKER, &MINT_D),
+            owned_account(&context, token_account_data(&MINT_D, &TAKER, RECEIVE, false), &TOKEN_2022_ID),
+        ),
+    ];
+    for (address, account) in accounts {
+        context.account_store.borrow_mut().insert(address, account);
+    }
+    context.process_and_validate_instruction(&bundle_make_instruction(), &[Check::success()]);
+    context
+}
+
+#[test]
+fn bundle_take_settles_every_leg_at_once() {
+    let context = setup_bundle();
+    assert_eq!(token_amount(&context, &ata(&bundle_address(), &MINT_A)), DEPOSIT);
+    assert_eq!(token_amount(&context, &ata(&bundle_address(), &MINT_C)), DEPOSIT);
+
+    // 账户顺序必须与托管中记录的一致
+    context.process_and_validate_instruction(
+        &bundle_take_instruction([MINT_D, MINT_B]),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::InvalidAddress as u32,
+        ))],
+    );
+    context.process_and_validate_instruction(
+        &bundle_take_instruction([MINT_B, MINT_D]),
+        &[
+            Check::success(),
+            Check::account(&bundle_address()).closed().build(),
+            Check::account(&ata(&bundle_address(), &MINT_A)).closed().build(),
+            Check::account(&ata(&bundle_address(), &MINT_C)).closed().build(),
+        ],
+    );
+
+    for mint in [MINT_A, MINT_C] {
+        assert_eq!(token_amount(&context, &ata(&TAKER, &mint)), DEPOSIT);
+    }
+    for mint in [MINT_B, MINT_D] {
+        assert_eq!(token_amount(&context, &ata(&MAKER, &mint)), RECEIVE);
+        assert_eq!(token_amount(&context, &ata(&TAKER, &mint)), 0);
+    }
+    // 存入和取走 MINT_A 各触发一次转账钩子
+    assert_eq!(counter(&context), 2);
+}
+
+#[test]
+fn bundle_refund_returns_every_leg() {
+    let context = setup_bundle();
+
+    context.process_and_validate_instruction(
+        &bundle_refund_instruction(),
+        &[Check::success(), Check::account(&bundle_address()).closed().build()],
+    );
+
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_A)), DEPOSIT);
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_C)), DEPOSIT);
+}
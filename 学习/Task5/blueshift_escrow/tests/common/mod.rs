@@ -0,0 +1,427 @@
+// =============================================================================
+// 集成测试共用的常量、账户数据与指令构造
+// =============================================================================
+// 需要先编译两个 SBF 程序：
+//   cargo build-sbf
+//   cargo build-sbf --manifest-path programs/transfer_hook/Cargo.toml --sbf-out-dir target/deploy
+// 然后运行：
+//   cargo test-sbf  (或 cargo test --features test-sbf)
+//
+// 场景：mint_a 是带 TransferHook 扩展的 Token-2022 Mint，钩子程序为
+// programs/transfer_hook。钩子每执行一次就把 counter 账户中的计数加 1。
+// 每个测试文件只用到其中一部分
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use mollusk_svm::{program, result::Check, Mollusk, MolluskContext};
+use mollusk_svm_programs_token::{associated_token, token, token2022};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+pub const ESCROW_ID: Pubkey = blueshift_escrow::ID;
+pub const HOOK_ID: Pubkey = Pubkey::new_from_array([7u8; 32]);
+pub const TOKEN_2022_ID: Pubkey = token2022::ID;
+pub const ATA_ID: Pubkey = associated_token::ID;
+
+pub const MAKER: Pubkey = Pubkey::new_from_array([1u8; 32]);
+pub const TAKER: Pubkey = Pubkey::new_from_array([2u8; 32]);
+pub const MINT_A: Pubkey = Pubkey::new_from_array([3u8; 32]);
+pub const MINT_B: Pubkey = Pubkey::new_from_array([4u8; 32]);
+pub const COUNTER: Pubkey = Pubkey::new_from_array([5u8; 32]);
+pub const TREASURY: Pubkey = Pubkey::new_from_array([6u8; 32]);
+pub const REFERRER: Pubkey = Pubkey::new_from_array([8u8; 32]);
+pub const ARBITER: Pubkey = Pubkey::new_from_array([10u8; 32]);
+pub const NATIVE_MINT: Pubkey = Pubkey::new_from_array(*blueshift_escrow::NATIVE_MINT.as_array());
+
+pub const SEED: u64 = 42;
+pub const DEPOSIT: u64 = 1_000;
+pub const RECEIVE: u64 = 500;
+
+pub const NOW: i64 = 1_700_000_000;
+pub const TIMEOUT: i64 = NOW + 3_600;
+
+// sha256("spl-transfer-hook-interface:execute")[..8]
+pub const EXECUTE_DISCRIMINATOR: [u8; 8] = [105, 37, 101, 197, 75, 251, 102, 26];
+
+pub fn rent(context: &MolluskContext<HashMap<Pubkey, Account>>, len: usize) -> u64 {
+    context.mollusk.sysvars.rent.minimum_balance(len)
+}
+
+pub fn ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), TOKEN_2022_ID.as_ref(), mint.as_ref()],
+        &ATA_ID,
+    )
+    .0
+}
+
+pub fn escrow_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"escrow", MAKER.as_ref(), &SEED.to_le_bytes()],
+        &ESCROW_ID,
+    )
+    .0
+}
+
+pub fn config_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], &ESCROW_ID).0
+}
+
+pub fn extra_account_metas_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"extra-account-metas", MINT_A.as_ref()], &HOOK_ID).0
+}
+
+// Token-2022 Mint：82 字节基础数据，有扩展时补齐到 165 字节并追加账户类型和 TLV
+pub fn mint_data(with_hook: bool) -> Vec<u8> {
+    let mut data = Vec::with_capacity(234);
+    data.extend_from_slice(&[1, 0, 0, 0]); // mint_authority: Some
+    data.extend_from_slice(MAKER.as_ref());
+    data.extend_from_slice(&u64::MAX.to_le_bytes()); // supply
+    data.push(6); // decimals
+    data.push(1); // is_initialized
+    data.extend_from_slice(&[0u8; 36]); // freeze_authority: None
+
+    if with_hook {
+        data.resize(165, 0);
+        data.push(1); // AccountType::Mint
+        data.extend_from_slice(&14u16.to_le_bytes()); // ExtensionType::TransferHook
+        data.extend_from_slice(&64u16.to_le_bytes());
+        data.extend_from_slice(&[0u8; 32]); // authority
+        data.extend_from_slice(HOOK_ID.as_ref()); // program_id
+    }
+    data
+}
+
+// Token-2022 Token Account：带 ImmutableOwner，若 Mint 有钩子则带 TransferHookAccount
+pub fn token_account_data(mint: &Pubkey, owner: &Pubkey, amount: u64, with_hook: bool) -> Vec<u8> {
+    let mut data = Vec::with_capacity(175);
+    data.extend_from_slice(mint.as_ref());
+    data.extend_from_slice(owner.as_ref());
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&[0u8; 36]); // delegate: None
+    data.push(1); // AccountState::Initialized
+    data.extend_from_slice(&[0u8; 12]); // is_native: None
+    data.extend_from_slice(&[0u8; 8]); // delegated_amount
+    data.extend_from_slice(&[0u8; 36]); // close_authority: None
+
+    data.push(2); // AccountType::Account
+    data.extend_from_slice(&7u16.to_le_bytes()); // ExtensionType::ImmutableOwner
+    data.extend_from_slice(&0u16.to_le_bytes());
+    if with_hook {
+        data.extend_from_slice(&15u16.to_le_bytes()); // ExtensionType::TransferHookAccount
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.push(0); // transferring
+    }
+    data
+}
+
+// ExtraAccountMetaList：只登记一个固定地址的可写账户（counter）
+pub fn extra_account_metas_data() -> Vec<u8> {
+    let mut data = Vec::with_capacity(51);
+    data.extend_from_slice(&EXECUTE_DISCRIMINATOR);
+    data.extend_from_slice(&39u32.to_le_bytes()); // 4 (count) + 35 (一个 ExtraAccountMeta)
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.push(0); // discriminator：固定地址
+    data.extend_from_slice(COUNTER.as_ref());
+    data.push(0); // is_signer
+    data.push(1); // is_writable
+    data
+}
+
+pub fn owned_account(context: &MolluskContext<HashMap<Pubkey, Account>>, data: Vec<u8>, owner: &Pubkey) -> Account {
+    Account {
+        lamports: rent(context, data.len()),
+        data,
+        owner: *owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+// 全局 Config：协议费 fee_bps，从代币 B 收取，MAKER 作为管理员
+pub fn init_config_instruction(fee_bps: u16) -> Instruction {
+    let mut data = vec![5u8];
+    data.extend_from_slice(&fee_bps.to_le_bytes());
+    data.push(0); // FeeSide::TokenB
+    data.extend_from_slice(TREASURY.as_ref());
+
+    Instruction::new_with_bytes(
+        ESCROW_ID,
+        &data,
+        vec![
+            AccountMeta::new(MAKER, true),
+            AccountMeta::new(config_address(), false),
+            AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        ],
+    )
+}
+
+pub fn setup() -> MolluskContext<HashMap<Pubkey, Account>> {
+    setup_with_fee(0)
+}
+
+pub fn setup_with_fee(fee_bps: u16) -> MolluskContext<HashMap<Pubkey, Account>> {
+    let mut mollusk = Mollusk::new(&ESCROW_ID, "blueshift_escrow");
+    mollusk.add_program(&HOOK_ID, "transfer_hook");
+    token2022::add_program(&mut mollusk);
+    associated_token::add_program(&mut mollusk);
+
+    let context = mollusk.with_context(HashMap::new());
+
+    let accounts = [
+        (MAKER, Account::new(10_000_000_000, 0, &program::keyed_account_for_system_program().0)),
+        (TAKER, Account::new(10_000_000_000, 0, &program::keyed_account_for_system_program().0)),
+        (TREASURY, Account::new(0, 0, &program::keyed_account_for_system_program().0)),
+        (ARBITER, Account::new(10_000_000_000, 0, &program::keyed_account_for_system_program().0)),
+        (REFERRER, Account::new(0, 0, &program::keyed_account_for_system_program().0)),
+        (MINT_A, owned_account(&context, mint_data(true), &TOKEN_2022_ID)),
+        (MINT_B, owned_account(&context, mint_data(false), &TOKEN_2022_ID)),
+        (NATIVE_MINT, owned_account(&context, mint_data(false), &token::ID)),
+        (extra_account_metas_address(), owned_account(&context, extra_account_metas_data(), &HOOK_ID)),
+        (COUNTER, owned_account(&context, vec![0u8; 8], &HOOK_ID)),
+        (
+            ata(&MAKER, &MINT_A),
+            owned_account(&context, token_account_data(&MINT_A, &MAKER, DEPOSIT, true), &TOKEN_2022_ID),
+        ),
+        (
+            ata(&TAKER, &MINT_B),
+            owned_account(&context, token_account_data(&MINT_B, &TAKER, RECEIVE, false), &TOKEN_2022_ID),
+        ),
+    ];
+    for (address, account) in accounts {
+        context.account_store.borrow_mut().insert(address, account);
+    }
+
+    context.process_and_validate_instruction(&init_config_instruction(fee_bps), &[Check::success()]);
+
+    context
+}
+
+pub fn hook_accounts() -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(HOOK_ID, false),
+        AccountMeta::new_readonly(extra_account_metas_address(), false),
+        AccountMeta::new(COUNTER, false),
+    ]
+}
+
+pub fn make_instruction(hook_accounts: Vec<AccountMeta>) -> Instruction {
+    make_instruction_with_options(&[], hook_accounts)
+}
+
+pub fn make_instruction_with_options(options: &[u8], hook_accounts: Vec<AccountMeta>) -> Instruction {
+    make_instruction_with(MINT_A, MINT_B, options, hook_accounts)
+}
+
+// options：追加在 seed/receive/amount 之后的可选字段
+pub fn make_instruction_with(mint_a: Pubkey, mint_b: Pubkey, options: &[u8], hook_accounts: Vec<AccountMeta>) -> Instruction {
+    let escrow = escrow_address();
+    let mut data = vec![0u8];
+    data.extend_from_slice(&SEED.to_le_bytes());
+    data.extend_from_slice(&RECEIVE.to_le_bytes());
+    data.extend_from_slice(&DEPOSIT.to_le_bytes());
+    data.extend_from_slice(options);
+
+    let mut accounts = vec![
+        AccountMeta::new(MAKER, true),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new_readonly(mint_a, false),
+        AccountMeta::new_readonly(mint_b, false),
+        AccountMeta::new(ata(&MAKER, &mint_a), false),
+        AccountMeta::new(ata(&escrow, &mint_a), false),
+        // payer：创建者自己支付租金时传入本程序 ID 占位
+        AccountMeta::new_readonly(ESCROW_ID, false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    accounts.extend(hook_accounts);
+
+    Instruction::new_with_bytes(ESCROW_ID, &data, accounts)
+}
+
+pub fn take_instruction() -> Instruction {
+    take_instruction_with_referrer(None)
+}
+
+pub fn take_instruction_with_referrer(referrer: Option<Pubkey>) -> Instruction {
+    take_instruction_with(MINT_A, MINT_B, referrer, hook_accounts())
+}
+
+// 不使用推荐人时，referrer 与 referrer_ata 位置传入程序 ID 作为占位
+// min_amount_a 为 0（不限制），需要时直接改写 data[1..9]
+pub fn take_instruction_with(mint_a: Pubkey, mint_b: Pubkey, referrer: Option<Pubkey>, hook_accounts: Vec<AccountMeta>) -> Instruction {
+    let escrow = escrow_address();
+    let (referrer, referrer_ata) = match referrer {
+        Some(referrer) => (AccountMeta::new(referrer, false), AccountMeta::new(ata(&referrer, &mint_b), false)),
+        None => (AccountMeta::new_readonly(ESCROW_ID, false), AccountMeta::new_readonly(ESCROW_ID, false)),
+    };
+    let mut accounts = vec![
+        AccountMeta::new(TAKER, true),
+        AccountMeta::new(MAKER, false),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new_readonly(mint_a, false),
+        AccountMeta::new_readonly(mint_b, false),
+        AccountMeta::new(ata(&escrow, &mint_a), false),
+        AccountMeta::new(ata(&TAKER, &mint_a), false),
+        AccountMeta::new(ata(&TAKER, &mint_b), false),
+        AccountMeta::new(ata(&MAKER, &mint_b), false),
+        AccountMeta::new_readonly(config_address(), false),
+        AccountMeta::new(TREASURY, false),
+        AccountMeta::new(ata(&TREASURY, &mint_b), false),
+        referrer,
+        referrer_ata,
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    accounts.extend(hook_accounts);
+
+    let mut data = vec![1u8];
+    data.extend_from_slice(&0u64.to_le_bytes());
+
+    Instruction::new_with_bytes(ESCROW_ID, &data, accounts)
+}
+
+pub fn take_instruction_with_max_pay(max_pay: u64) -> Instruction {
+    let mut instruction = take_instruction();
+    instruction.data.extend_from_slice(&max_pay.to_le_bytes());
+    instruction
+}
+
+pub fn refund_instruction() -> Instruction {
+    refund_instruction_with(MINT_A, hook_accounts())
+}
+
+pub fn refund_instruction_with(mint_a: Pubkey, hook_accounts: Vec<AccountMeta>) -> Instruction {
+    let escrow = escrow_address();
+    let mut accounts = vec![
+        AccountMeta::new(MAKER, true),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new_readonly(mint_a, false),
+        AccountMeta::new(ata(&escrow, &mint_a), false),
+        AccountMeta::new(ata(&MAKER, &mint_a), false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    accounts.extend(hook_accounts);
+
+    Instruction::new_with_bytes(ESCROW_ID, &[2], accounts)
+}
+
+// TakeMany 的第二个报价：ARBITER 存入 DEPOSIT 个代币 A，同样要求 RECEIVE 个代币 B
+pub fn second_escrow_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"escrow", ARBITER.as_ref(), &SEED.to_le_bytes()],
+        &ESCROW_ID,
+    )
+    .0
+}
+
+pub fn second_make_instruction() -> Instruction {
+    let escrow = second_escrow_address();
+    let mut instruction = make_instruction(hook_accounts());
+    instruction.accounts[0] = AccountMeta::new(ARBITER, true);
+    instruction.accounts[1] = AccountMeta::new(escrow, false);
+    instruction.accounts[4] = AccountMeta::new(ata(&ARBITER, &MINT_A), false);
+    instruction.accounts[5] = AccountMeta::new(ata(&escrow, &MINT_A), false);
+    instruction
+}
+
+// 两个报价都已创建，TAKER 持有 2 * RECEIVE 个代币 B
+pub fn setup_take_many() -> MolluskContext<HashMap<Pubkey, Account>> {
+    let context = setup();
+    let accounts = [
+        (
+            ata(&ARBITER, &MINT_A),
+            owned_account(&context, token_account_data(&MINT_A, &ARBITER, DEPOSIT, true), &TOKEN_2022_ID),
+        ),
+        (
+            ata(&TAKER, &MINT_B),
+            owned_account(&context, token_account_data(&MINT_B, &TAKER, 2 * RECEIVE, false), &TOKEN_2022_ID),
+        ),
+    ];
+    for (address, account) in accounts {
+        context.account_store.borrow_mut().insert(address, account);
+    }
+    context.process_and_validate_instruction(&make_instruction(hook_accounts()), &[Check::success()]);
+    context.process_and_validate_instruction(&second_make_instruction(), &[Check::success()]);
+    context
+}
+
+// Update：receive 为 0 表示不修改，expires_at 不传表示不修改
+pub fn update_instruction(receive: u64, deposit: u64, withdraw: u64, expires_at: Option<i64>) -> Instruction {
+    let escrow = escrow_address();
+    let mut data = vec![7u8];
+    data.extend_from_slice(&receive.to_le_bytes());
+    data.extend_from_slice(&deposit.to_le_bytes());
+    data.extend_from_slice(&withdraw.to_le_bytes());
+    if let Some(expires_at) = expires_at {
+        data.extend_from_slice(&expires_at.to_le_bytes());
+    }
+
+    let mut accounts = vec![
+        AccountMeta::new(MAKER, true),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new_readonly(MINT_A, false),
+        AccountMeta::new(ata(&escrow, &MINT_A), false),
+        AccountMeta::new(ata(&MAKER, &MINT_A), false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    accounts.extend(hook_accounts());
+
+    Instruction::new_with_bytes(ESCROW_ID, &data, accounts)
+}
+
+// TakeMany：依次接受 MAKER 与 ARBITER 的托管
+pub fn take_many_instruction(max_pay: u64, all_or_nothing: bool) -> Instruction {
+    let mut data = vec![26u8];
+    data.extend_from_slice(&max_pay.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes()); // min_amount_a
+    data.push(all_or_nothing as u8);
+    data.push(2);
+
+    let mut accounts = vec![
+        AccountMeta::new(TAKER, true),
+        AccountMeta::new_readonly(MINT_A, false),
+        AccountMeta::new_readonly(MINT_B, false),
+        AccountMeta::new(ata(&TAKER, &MINT_A), false),
+        AccountMeta::new(ata(&TAKER, &MINT_B), false),
+        AccountMeta::new_readonly(config_address(), false),
+        AccountMeta::new_readonly(TREASURY, false),
+        AccountMeta::new(ata(&TREASURY, &MINT_B), false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    for (maker, escrow) in [(MAKER, escrow_address()), (ARBITER, second_escrow_address())] {
+        accounts.extend([
+            AccountMeta::new(maker, false),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new(ata(&escrow, &MINT_A), false),
+            AccountMeta::new(ata(&maker, &MINT_B), false),
+        ]);
+    }
+    accounts.extend(hook_accounts());
+
+    Instruction::new_with_bytes(ESCROW_ID, &data, accounts)
+}
+
+pub fn lamports(context: &MolluskContext<HashMap<Pubkey, Account>>, address: &Pubkey) -> u64 {
+    context.account_store.borrow().get(address).map_or(0, |account| account.lamports)
+}
+
+pub fn counter(context: &MolluskContext<HashMap<Pubkey, Account>>) -> u64 {
+    let data = context.account_store.borrow()[&COUNTER].data.clone();
+    u64::from_le_bytes(data[..8].try_into().unwrap())
+}
+
+pub fn token_amount(context: &MolluskContext<HashMap<Pubkey, Account>>, address: &Pubkey) -> u64 {
+    let data = context.account_store.borrow()[address].data.clone();
+    u64::from_le_bytes(data[64..72].try_into().unwrap())
+}
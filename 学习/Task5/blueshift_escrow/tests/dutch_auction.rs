@@ -0,0 +1,54 @@
+// 荷兰式拍卖报价的集成测试：构建与运行方式见 tests/common/mod.rs
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use mollusk_svm::result::Check;
+use solana_program::program_error::ProgramError;
+use solana_sdk::instruction::Instruction;
+
+use common::*;
+
+// 荷兰式拍卖：期望数量在 [NOW, NOW + 300] 之间从 RECEIVE 线性下降到 end_receive
+fn make_auction_instruction(end_receive: u64) -> Instruction {
+    let mut options = vec![0u8; 8 + 32 + 32 + 2 + 1];
+    options.extend_from_slice(&end_receive.to_le_bytes());
+    options.extend_from_slice(&NOW.to_le_bytes());
+    options.extend_from_slice(&(NOW + 300).to_le_bytes());
+    make_instruction_with_options(&options, hook_accounts())
+}
+
+#[test]
+fn take_pays_current_auction_price_within_max_pay() {
+    let mut context = setup();
+    context.mollusk.sysvars.clock.unix_timestamp = NOW;
+    context.process_and_validate_instruction(&make_auction_instruction(200), &[Check::success()]);
+
+    // 过了一半：500 - 300 / 2 = 350
+    context.mollusk.sysvars.clock.unix_timestamp = NOW + 150;
+    context.process_and_validate_instruction(
+        &take_instruction_with_max_pay(349),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::SlippageExceeded as u32,
+        ))],
+    );
+    context.process_and_validate_instruction(
+        &take_instruction_with_max_pay(350),
+        &[Check::success(), Check::account(&escrow_address()).closed().build()],
+    );
+
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), DEPOSIT);
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_B)), 350);
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_B)), RECEIVE - 350);
+}
+
+#[test]
+fn make_rejects_rising_auction() {
+    let context = setup();
+    context.process_and_validate_instruction(
+        &make_auction_instruction(RECEIVE + 1),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::InvalidAmount as u32,
+        ))],
+    );
+}
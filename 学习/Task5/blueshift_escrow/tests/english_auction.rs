@@ -0,0 +1,192 @@
+// 英式拍卖的集成测试：构建与运行方式见 tests/common/mod.rs
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use std::collections::HashMap;
+
+use mollusk_svm::{program, result::Check, MolluskContext};
+use solana_account::Account;
+use solana_program::program_error::ProgramError;
+use solana_pubkey::Pubkey;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+use common::*;
+
+// 英式拍卖：MAKER 拍卖 DEPOSIT 个代币 A，以代币 B 出价，TIMEOUT 结束
+const MIN_BID: u64 = 100;
+
+fn auction_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"auction", MAKER.as_ref(), &SEED.to_le_bytes()],
+        &ESCROW_ID,
+    )
+    .0
+}
+
+fn bid_address(bidder: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"bid", auction_address().as_ref(), bidder.as_ref()], &ESCROW_ID).0
+}
+
+fn auction_make_instruction() -> Instruction {
+    let auction = auction_address();
+    let mut data = vec![19u8];
+    data.extend_from_slice(&SEED.to_le_bytes());
+    data.extend_from_slice(&DEPOSIT.to_le_bytes());
+    data.extend_from_slice(&MIN_BID.to_le_bytes());
+    data.extend_from_slice(&TIMEOUT.to_le_bytes());
+
+    let mut accounts = vec![
+        AccountMeta::new(MAKER, true),
+        AccountMeta::new(auction, false),
+        AccountMeta::new_readonly(MINT_A, false),
+        AccountMeta::new_readonly(MINT_B, false),
+        AccountMeta::new(ata(&MAKER, &MINT_A), false),
+        AccountMeta::new(ata(&auction, &MINT_A), false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    accounts.extend(hook_accounts());
+
+    Instruction::new_with_bytes(ESCROW_ID, &data, accounts)
+}
+
+// previous 为被超过的最高出价者；首次出价或加价时用本程序 ID 占位
+fn place_bid_instruction(bidder: Pubkey, amount: u64, previous: Option<Pubkey>) -> Instruction {
+    let mut data = vec![20u8];
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    let previous_accounts = match previous {
+        Some(previous) => [previous, bid_address(&previous), ata(&bid_address(&previous), &MINT_B), ata(&previous, &MINT_B)],
+        None => [ESCROW_ID; 4],
+    };
+    let mut accounts = vec![
+        AccountMeta::new(bidder, true),
+        AccountMeta::new(auction_address(), false),
+        AccountMeta::new_readonly(MINT_B, false),
+        AccountMeta::new(ata(&bidder, &MINT_B), false),
+        AccountMeta::new_readonly(bid_address(&bidder), false),
+        AccountMeta::new(ata(&bid_address(&bidder), &MINT_B), false),
+    ];
+    accounts.extend(previous_accounts.map(|address| AccountMeta::new(address, false)));
+    accounts.extend([
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ]);
+
+    Instruction::new_with_bytes(ESCROW_ID, &data, accounts)
+}
+
+// 任何人都可以结算：这里由 TAKER 发起；没有人出价时 winner 为 MAKER
+fn settle_auction_instruction(winner: Pubkey) -> Instruction {
+    let auction = auction_address();
+    let mut accounts = vec![
+        AccountMeta::new(TAKER, true),
+        AccountMeta::new(MAKER, false),
+        AccountMeta::new(auction, false),
+        AccountMeta::new_readonly(MINT_A, false),
+        AccountMeta::new_readonly(MINT_B, false),
+        AccountMeta::new(ata(&auction, &MINT_A), false),
+        AccountMeta::new(winner, false),
+        AccountMeta::new(ata(&winner, &MINT_A), false),
+        AccountMeta::new_readonly(bid_address(&winner), false),
+        AccountMeta::new(ata(&bid_address(&winner), &MINT_B), false),
+        AccountMeta::new(ata(&MAKER, &MINT_B), false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    accounts.extend(hook_accounts());
+
+    Instruction::new_with_bytes(ESCROW_ID, &[21], accounts)
+}
+
+// 拍卖开始于 NOW；ARBITER 作为第二位出价者，持有 DEPOSIT 个代币 B
+fn setup_auction() -> MolluskContext<HashMap<Pubkey, Account>> {
+    let mut context = setup();
+    context.mollusk.sysvars.clock.unix_timestamp = NOW;
+    let arbiter_ata_b = owned_account(&context, token_account_data(&MINT_B, &ARBITER, DEPOSIT, false), &TOKEN_2022_ID);
+    context.account_store.borrow_mut().insert(ata(&ARBITER, &MINT_B), arbiter_ata_b);
+    context.process_and_validate_instruction(&auction_make_instruction(), &[Check::success()]);
+    context
+}
+
+#[test]
+fn outbid_bidder_is_refunded_and_winner_settles() {
+    let mut context = setup_auction();
+
+    context.process_and_validate_instruction(
+        &place_bid_instruction(TAKER, MIN_BID - 1, None),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::BidTooLow as u32,
+        ))],
+    );
+    context.process_and_validate_instruction(&place_bid_instruction(TAKER, 200, None), &[Check::success()]);
+    assert_eq!(token_amount(&context, &ata(&bid_address(&TAKER), &MINT_B)), 200);
+
+    // 必须严格高于当前最高出价；超过后上一位出价者拿回全部代币 B
+    context.process_and_validate_instruction(
+        &place_bid_instruction(ARBITER, 200, Some(TAKER)),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::BidTooLow as u32,
+        ))],
+    );
+    context.process_and_validate_instruction(
+        &place_bid_instruction(ARBITER, 300, Some(TAKER)),
+        &[Check::success(), Check::account(&ata(&bid_address(&TAKER), &MINT_B)).closed().build()],
+    );
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_B)), RECEIVE);
+
+    // 最高出价者加价只补足差额
+    context.process_and_validate_instruction(&place_bid_instruction(ARBITER, 400, None), &[Check::success()]);
+    assert_eq!(token_amount(&context, &ata(&bid_address(&ARBITER), &MINT_B)), 400);
+    assert_eq!(token_amount(&context, &ata(&ARBITER, &MINT_B)), DEPOSIT - 400);
+
+    context.process_and_validate_instruction(
+        &settle_auction_instruction(ARBITER),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::EscrowNotExpired as u32,
+        ))],
+    );
+
+    context.mollusk.sysvars.clock.unix_timestamp = TIMEOUT;
+    context.process_and_validate_instruction(
+        &place_bid_instruction(TAKER, 500, Some(ARBITER)),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::EscrowExpired as u32,
+        ))],
+    );
+    context.process_and_validate_instruction(
+        &settle_auction_instruction(ARBITER),
+        &[
+            Check::success(),
+            Check::account(&auction_address()).closed().build(),
+            Check::account(&ata(&bid_address(&ARBITER), &MINT_B)).closed().build(),
+        ],
+    );
+
+    assert_eq!(token_amount(&context, &ata(&ARBITER, &MINT_A)), DEPOSIT);
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_B)), 400);
+}
+
+#[test]
+fn auction_without_bids_returns_tokens_to_maker() {
+    let mut context = setup_auction();
+    context.mollusk.sysvars.clock.unix_timestamp = TIMEOUT;
+
+    // 没有人出价时获胜者只能是创建者
+    context.process_and_validate_instruction(
+        &settle_auction_instruction(TAKER),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::InvalidAddress as u32,
+        ))],
+    );
+    context.process_and_validate_instruction(
+        &settle_auction_instruction(MAKER),
+        &[Check::success(), Check::account(&auction_address()).closed().build()],
+    );
+
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_A)), DEPOSIT);
+}
@@ -0,0 +1,117 @@
+// 协议费计算与 Config 布局的主机端测试：不需要 SBF 产物，cargo test 直接运行
+use blueshift_escrow::{apply_bps, split_fees, AccountDiscriminator, Config, EscrowError, FeeSide, ProgramAccountType, BPS_DENOMINATOR};
+use pinocchio::error::ProgramError;
+use pinocchio::Address;
+
+#[test]
+fn apply_bps_rounds_down() {
+    assert_eq!(apply_bps(10_000, 30), Ok(30));
+    // 999 * 30 / 10_000 = 2.997 -> 2
+    assert_eq!(apply_bps(999, 30), Ok(2));
+    // 不足 1 个最小单位的费用被舍去
+    assert_eq!(apply_bps(333, 1), Ok(0));
+}
+
+#[test]
+fn apply_bps_bounds() {
+    assert_eq!(apply_bps(12_345, 0), Ok(0));
+    assert_eq!(apply_bps(12_345, BPS_DENOMINATOR), Ok(12_345));
+    assert_eq!(apply_bps(0, BPS_DENOMINATOR), Ok(0));
+}
+
+#[test]
+fn apply_bps_does_not_overflow_at_u64_max() {
+    // u64::MAX * 10_000 超出 u64，但在 u128 中计算不会溢出也不会回绕
+    assert_eq!(apply_bps(u64::MAX, BPS_DENOMINATOR), Ok(u64::MAX));
+    assert_eq!(apply_bps(u64::MAX, 5_000), Ok(u64::MAX / 2));
+    assert_eq!(apply_bps(u64::MAX, 1), Ok(u64::MAX / 10_000));
+}
+
+#[test]
+fn apply_bps_rejects_more_than_100_percent() {
+    assert_eq!(
+        apply_bps(1, BPS_DENOMINATOR + 1),
+        Err(ProgramError::from(EscrowError::InvalidFee))
+    );
+    assert_eq!(
+        apply_bps(u64::MAX, u16::MAX),
+        Err(ProgramError::from(EscrowError::InvalidFee))
+    );
+}
+
+#[test]
+fn split_fees_without_config_or_referrer_pays_the_maker_everything() {
+    // 不传 Config（占位）时 fee_bps 按 0 计算
+    assert_eq!(split_fees(1_000, 0, 0), Ok((1_000, 0, 0)));
+    assert_eq!(split_fees(u64::MAX, 0, 0), Ok((u64::MAX, 0, 0)));
+    assert_eq!(split_fees(0, 100, 50), Ok((0, 0, 0)));
+}
+
+#[test]
+fn split_fees_takes_fee_and_referral_from_the_amount() {
+    // 1% 协议费 + 0.5% 推荐费：两者都按成交数量向下取整
+    assert_eq!(split_fees(1_000, 100, 50), Ok((985, 10, 5)));
+    assert_eq!(split_fees(999, 100, 50), Ok((986, 9, 4)));
+    // 三方之和总是等于成交数量，舍入误差归创建者
+    let (maker, fee, referral) = split_fees(u64::MAX, 30, 25).unwrap();
+    assert_eq!(maker as u128 + fee as u128 + referral as u128, u64::MAX as u128);
+}
+
+#[test]
+fn split_fees_rejects_fee_and_referral_above_the_amount() {
+    assert_eq!(split_fees(1_000, BPS_DENOMINATOR, 0), Ok((0, 1_000, 0)));
+    assert_eq!(split_fees(1_000, 6_000, 4_000), Ok((0, 600, 400)));
+    assert_eq!(split_fees(1_000, 6_000, 4_010), Err(ProgramError::from(EscrowError::InvalidFee)));
+    assert_eq!(split_fees(1_000, BPS_DENOMINATOR + 1, 0), Err(ProgramError::from(EscrowError::InvalidFee)));
+}
+
+#[test]
+fn fee_side_from_u8() {
+    assert_eq!(FeeSide::try_from(0), Ok(FeeSide::TokenB));
+    assert_eq!(FeeSide::try_from(1), Ok(FeeSide::TokenA));
+    assert_eq!(FeeSide::try_from(2), Err(ProgramError::from(EscrowError::InvalidFee)));
+}
+
+#[test]
+fn config_round_trips_every_field() {
+    let admin = Address::new_from_array([1; 32]);
+    let treasury = Address::new_from_array([2; 32]);
+    let mut data = [0u8; Config::LEN];
+
+    Config::init(&mut data)
+        .unwrap()
+        .set_inner([253], 250, FeeSide::TokenA, admin, treasury)
+        .unwrap();
+
+    let config = Config::load(&data).unwrap();
+    assert_eq!(config.bump(), [253]);
+    assert_eq!(config.fee_bps(), 250);
+    assert_eq!(config.fee_side(), Ok(FeeSide::TokenA));
+    assert_eq!(config.admin(), &admin);
+    assert_eq!(config.treasury(), &treasury);
+    assert_eq!(config.fee(1_000_000), Ok(25_000));
+
+    assert_eq!(Config::LEN, 1 + 1 + 2 + 1 + 32 + 32);
+    assert_eq!(data[0], AccountDiscriminator::Config as u8);
+    assert_eq!(data[2..4], 250u16.to_le_bytes());
+}
+
+#[test]
+fn config_rejects_invalid_fee() {
+    let mut data = [0u8; Config::LEN];
+    let config = Config::init(&mut data).unwrap();
+
+    assert_eq!(
+        config.set_inner([0], BPS_DENOMINATOR + 1, FeeSide::TokenB, Address::default(), Address::default()),
+        Err(ProgramError::from(EscrowError::InvalidFee))
+    );
+}
+
+#[test]
+fn config_and_escrow_discriminators_differ() {
+    let mut data = [0u8; Config::LEN];
+    Config::init(&mut data).unwrap();
+
+    // Config 账户不能被当作 Escrow 加载（长度和判别器都不同）
+    assert!(blueshift_escrow::Escrow::load(&data).is_err());
+}
@@ -0,0 +1,72 @@
+// 闪电成交（Take flash + SettleFlashTake）的集成测试：构建与运行方式见 tests/common/mod.rs
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use mollusk_svm::result::Check;
+use solana_program::program_error::ProgramError;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+use common::*;
+
+// 闪电成交：Take 数据追加 max_pay 与 flash = 1，剩余账户附带 Instructions sysvar
+fn flash_take_instruction() -> Instruction {
+    let mut instruction = take_instruction_with_max_pay(u64::MAX);
+    instruction.data.push(1);
+    instruction.accounts.push(AccountMeta::new_readonly(solana_sdk::sysvar::instructions::ID, false));
+    instruction
+}
+
+// SettleFlashTake 与 Take 使用相同的账户列表
+fn settle_flash_take_instruction() -> Instruction {
+    let mut instruction = take_instruction();
+    instruction.data = vec![27];
+    instruction
+}
+
+#[test]
+fn flash_take_pays_token_b_in_a_later_instruction() {
+    let context = setup();
+    context.process_and_validate_instruction(&make_instruction(hook_accounts()), &[Check::success()]);
+
+    context.process_and_validate_instruction_chain(&[
+        (
+            &flash_take_instruction(),
+            &[Check::success(), Check::account(&escrow_address()).owner(&ESCROW_ID).build()],
+        ),
+        (
+            &settle_flash_take_instruction(),
+            &[
+                Check::success(),
+                Check::account(&escrow_address()).closed().build(),
+                Check::account(&ata(&escrow_address(), &MINT_A)).closed().build(),
+            ],
+        ),
+    ]);
+
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), DEPOSIT);
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_B)), RECEIVE);
+}
+
+#[test]
+fn flash_take_requires_settlement_in_same_transaction() {
+    let context = setup();
+    context.process_and_validate_instruction(&make_instruction(hook_accounts()), &[Check::success()]);
+
+    // 交易中没有随后的 SettleFlashTake
+    context.process_and_validate_instruction(
+        &flash_take_instruction(),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::InvalidFlashTake as u32,
+        ))],
+    );
+
+    // 没有先执行闪电成交时，金库仍有余额，不能直接结算
+    context.process_and_validate_instruction(
+        &settle_flash_take_instruction(),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::InvalidFlashTake as u32,
+        ))],
+    );
+    assert_eq!(token_amount(&context, &ata(&escrow_address(), &MINT_A)), DEPOSIT);
+}
@@ -0,0 +1,157 @@
+// 哈希时间锁托管（HTLC）的集成测试：构建与运行方式见 tests/common/mod.rs
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use std::collections::HashMap;
+
+use mollusk_svm::{program, result::Check, MolluskContext};
+use solana_account::Account;
+use solana_program::program_error::ProgramError;
+use solana_pubkey::Pubkey;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+use common::*;
+
+fn htlc_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"htlc", MAKER.as_ref(), &SEED.to_le_bytes()],
+        &ESCROW_ID,
+    )
+    .0
+}
+
+const PREIMAGE: [u8; 32] = [9u8; 32];
+
+fn htlc_lock_instruction() -> Instruction {
+    let htlc = htlc_address();
+    let mut data = vec![8u8];
+    data.extend_from_slice(&SEED.to_le_bytes());
+    data.extend_from_slice(&DEPOSIT.to_le_bytes());
+    data.extend_from_slice(&solana_program::hash::hash(&PREIMAGE).to_bytes());
+    data.extend_from_slice(&TIMEOUT.to_le_bytes());
+    data.extend_from_slice(TAKER.as_ref());
+
+    let mut accounts = vec![
+        AccountMeta::new(MAKER, true),
+        AccountMeta::new(htlc, false),
+        AccountMeta::new_readonly(MINT_A, false),
+        AccountMeta::new(ata(&MAKER, &MINT_A), false),
+        AccountMeta::new(ata(&htlc, &MINT_A), false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    accounts.extend(hook_accounts());
+
+    Instruction::new_with_bytes(ESCROW_ID, &data, accounts)
+}
+
+fn htlc_claim_instruction(taker: Pubkey, preimage: [u8; 32]) -> Instruction {
+    let htlc = htlc_address();
+    let mut data = vec![9u8];
+    data.extend_from_slice(&preimage);
+
+    let mut accounts = vec![
+        AccountMeta::new(taker, true),
+        AccountMeta::new(MAKER, false),
+        AccountMeta::new(htlc, false),
+        AccountMeta::new_readonly(MINT_A, false),
+        AccountMeta::new(ata(&htlc, &MINT_A), false),
+        AccountMeta::new(ata(&taker, &MINT_A), false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    accounts.extend(hook_accounts());
+
+    Instruction::new_with_bytes(ESCROW_ID, &data, accounts)
+}
+
+fn htlc_refund_instruction() -> Instruction {
+    let htlc = htlc_address();
+    let mut accounts = vec![
+        AccountMeta::new(MAKER, true),
+        AccountMeta::new(htlc, false),
+        AccountMeta::new_readonly(MINT_A, false),
+        AccountMeta::new(ata(&htlc, &MINT_A), false),
+        AccountMeta::new(ata(&MAKER, &MINT_A), false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    accounts.extend(hook_accounts());
+
+    Instruction::new_with_bytes(ESCROW_ID, &[10], accounts)
+}
+
+// 在 NOW 时锁定，返回的上下文时钟仍为 NOW
+fn setup_htlc() -> MolluskContext<HashMap<Pubkey, Account>> {
+    let mut context = setup();
+    context.mollusk.sysvars.clock.unix_timestamp = NOW;
+    context.process_and_validate_instruction(&htlc_lock_instruction(), &[Check::success()]);
+    context
+}
+
+#[test]
+fn htlc_claim_with_preimage_pays_taker() {
+    let context = setup_htlc();
+    assert_eq!(token_amount(&context, &ata(&htlc_address(), &MINT_A)), DEPOSIT);
+
+    context.process_and_validate_instruction(
+        &htlc_claim_instruction(TAKER, PREIMAGE),
+        &[
+            Check::success(),
+            Check::account(&htlc_address()).closed().build(),
+            Check::account(&ata(&htlc_address(), &MINT_A)).closed().build(),
+        ],
+    );
+
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), DEPOSIT);
+    // 锁定与领取各触发一次转账钩子
+    assert_eq!(counter(&context), 2);
+}
+
+#[test]
+fn htlc_claim_rejects_wrong_preimage_or_taker() {
+    let context = setup_htlc();
+
+    context.process_and_validate_instruction(
+        &htlc_claim_instruction(TAKER, [0u8; 32]),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::InvalidPreimage as u32,
+        ))],
+    );
+    context.process_and_validate_instruction(
+        &htlc_claim_instruction(MAKER, PREIMAGE),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::InvalidTaker as u32,
+        ))],
+    );
+}
+
+#[test]
+fn htlc_refund_only_after_timeout() {
+    let mut context = setup_htlc();
+
+    context.process_and_validate_instruction(
+        &htlc_refund_instruction(),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::EscrowNotExpired as u32,
+        ))],
+    );
+
+    context.mollusk.sysvars.clock.unix_timestamp = TIMEOUT;
+    context.process_and_validate_instruction(
+        &htlc_claim_instruction(TAKER, PREIMAGE),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::EscrowExpired as u32,
+        ))],
+    );
+    context.process_and_validate_instruction(
+        &htlc_refund_instruction(),
+        &[Check::success(), Check::account(&htlc_address()).closed().build()],
+    );
+
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_A)), DEPOSIT);
+}
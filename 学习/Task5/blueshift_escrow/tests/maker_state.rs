@@ -0,0 +1,99 @@
+// MakerState 自动分配 seed 的集成测试：构建与运行方式见 tests/common/mod.rs
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use mollusk_svm::result::Check;
+use solana_program::program_error::ProgramError;
+use solana_pubkey::Pubkey;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+use common::*;
+
+fn maker_state_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"maker", MAKER.as_ref()], &ESCROW_ID).0
+}
+
+fn escrow_address_for_seed(seed: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"escrow", MAKER.as_ref(), &seed.to_le_bytes()],
+        &ESCROW_ID,
+    )
+    .0
+}
+
+// 由 MakerState 计数器分配 seed 的 Make：指令数据中的 seed 为 0，
+// 客户端按计数器的当前值（seed）派生托管地址，并在剩余账户中追加 MakerState
+fn auto_seed_make_instruction(seed: u64) -> Instruction {
+    let escrow = escrow_address_for_seed(seed);
+    let mut instruction = make_instruction(hook_accounts());
+    instruction.data[1..9].copy_from_slice(&0u64.to_le_bytes());
+    instruction.accounts[1] = AccountMeta::new(escrow, false);
+    instruction.accounts[5] = AccountMeta::new(ata(&escrow, &MINT_A), false);
+    instruction.accounts.push(AccountMeta::new(maker_state_address(), false));
+    instruction
+}
+
+#[test]
+fn make_allocates_seeds_from_maker_counter() {
+    let context = setup();
+    context.account_store.borrow_mut().insert(
+        ata(&MAKER, &MINT_A),
+        owned_account(&context, token_account_data(&MINT_A, &MAKER, 2 * DEPOSIT, true), &TOKEN_2022_ID),
+    );
+
+    // 第一次使用计数器时创建 MakerState，seed 从 0 开始依次分配
+    context.process_and_validate_instruction(
+        &auto_seed_make_instruction(0),
+        &[Check::success(), Check::account(&maker_state_address()).owner(&ESCROW_ID).build()],
+    );
+    context.process_and_validate_instruction(&auto_seed_make_instruction(1), &[Check::success()]);
+
+    let data = context.account_store.borrow()[&maker_state_address()].data.clone();
+    assert_eq!(data[0], blueshift_escrow::AccountDiscriminator::MakerState as u8);
+    assert_eq!(u64::from_le_bytes(data[34..42].try_into().unwrap()), 2);
+    for seed in 0..2 {
+        assert_eq!(token_amount(&context, &ata(&escrow_address_for_seed(seed), &MINT_A)), DEPOSIT);
+    }
+
+    // 客户端派生地址用的 seed 已经分配过，或者超出计数器能跳过的范围时，托管无法创建
+    for seed in [1, 3 + blueshift_escrow::Make::MAX_SEED_SKIP as u64] {
+        assert!(context
+            .process_instruction(&auto_seed_make_instruction(seed))
+            .program_result
+            .is_err());
+    }
+
+    // 不传入 MakerState 时 0 是自己指定的 seed，已被使用：返回明确的错误
+    let mut instruction = auto_seed_make_instruction(0);
+    instruction.accounts.pop();
+    context.process_and_validate_instruction(
+        &instruction,
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::SeedInUse as u32,
+        ))],
+    );
+}
+
+#[test]
+fn make_counter_skips_seeds_taken_explicitly() {
+    let context = setup();
+    context.account_store.borrow_mut().insert(
+        ata(&MAKER, &MINT_A),
+        owned_account(&context, token_account_data(&MINT_A, &MAKER, 2 * DEPOSIT, true), &TOKEN_2022_ID),
+    );
+
+    // 自己指定 seed 0（不传入 MakerState）
+    let mut explicit = auto_seed_make_instruction(0);
+    explicit.accounts.pop();
+    context.process_and_validate_instruction(&explicit, &[Check::success()]);
+
+    // 计数器从 0 开始，但 seed 0 已被占用：客户端传入 seed 1 的托管，计数器跳过 0
+    context.process_and_validate_instruction(&auto_seed_make_instruction(1), &[Check::success()]);
+
+    let data = context.account_store.borrow()[&maker_state_address()].data.clone();
+    assert_eq!(u64::from_le_bytes(data[34..42].try_into().unwrap()), 2);
+    for seed in 0..2 {
+        assert_eq!(token_amount(&context, &ata(&escrow_address_for_seed(seed), &MINT_A)), DEPOSIT);
+    }
+}
@@ -0,0 +1,157 @@
+// Market 挂单索引的集成测试：构建与运行方式见 tests/common/mod.rs
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use std::collections::HashMap;
+
+use blueshift_escrow::{Escrow, EscrowError, ProgramAccountType};
+use mollusk_svm::{result::Check, MolluskContext};
+use solana_account::Account;
+use solana_program::program_error::ProgramError;
+use solana_pubkey::Pubkey;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+use common::*;
+
+fn market_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"market", MINT_A.as_ref(), MINT_B.as_ref()], &ESCROW_ID).0
+}
+
+// 在剩余账户中追加 Market 挂单索引
+fn with_market(mut instruction: Instruction) -> Instruction {
+    instruction.accounts.push(AccountMeta::new(market_address(), false));
+    instruction
+}
+
+fn prune_market_instruction(escrows: &[Pubkey]) -> Instruction {
+    let mut accounts = vec![AccountMeta::new(market_address(), false)];
+    accounts.extend(escrows.iter().map(|escrow| AccountMeta::new_readonly(*escrow, false)));
+    Instruction::new_with_bytes(ESCROW_ID, &[28], accounts)
+}
+
+// Market 中按价格排列的托管地址
+fn market_orders(context: &MolluskContext<HashMap<Pubkey, Account>>) -> Vec<Pubkey> {
+    let data = context.account_store.borrow()[&market_address()].data.clone();
+    data[67..]
+        .chunks_exact(48)
+        .take(data[66] as usize)
+        .map(|order| Pubkey::new_from_array(order[..32].try_into().unwrap()))
+        .collect()
+}
+
+// Market 中每条挂单的 (receive, amount)
+fn market_prices(context: &MolluskContext<HashMap<Pubkey, Account>>) -> Vec<(u64, u64)> {
+    let data = context.account_store.borrow()[&market_address()].data.clone();
+    data[67..]
+        .chunks_exact(48)
+        .take(data[66] as usize)
+        .map(|order| {
+            (
+                u64::from_le_bytes(order[32..40].try_into().unwrap()),
+                u64::from_le_bytes(order[40..48].try_into().unwrap()),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn market_indexes_offers_by_price() {
+    let context = setup_take_many();
+    context.account_store.borrow_mut().insert(
+        ata(&MAKER, &MINT_A),
+        owned_account(&context, token_account_data(&MINT_A, &MAKER, DEPOSIT, true), &TOKEN_2022_ID),
+    );
+    context.account_store.borrow_mut().insert(
+        ata(&ARBITER, &MINT_A),
+        owned_account(&context, token_account_data(&MINT_A, &ARBITER, DEPOSIT, true), &TOKEN_2022_ID),
+    );
+    // setup_take_many 创建的托管没有挂单，先退还 MAKER 的托管，再带 Market 重新创建
+    context.process_and_validate_instruction(&refund_instruction(), &[Check::success()]);
+    context.process_and_validate_instruction(&with_market(make_instruction(hook_accounts())), &[Check::success()]);
+    assert_eq!(market_orders(&context), vec![escrow_address()]);
+
+    // 更便宜的报价排在前面：ARBITER 用已有托管之外的 seed 创建
+    let mut cheaper = second_make_instruction();
+    let cheaper_escrow = Pubkey::find_program_address(&[b"escrow", ARBITER.as_ref(), &0u64.to_le_bytes()], &ESCROW_ID).0;
+    cheaper.data[1..9].copy_from_slice(&0u64.to_le_bytes());
+    cheaper.data[9..17].copy_from_slice(&(RECEIVE - 100).to_le_bytes());
+    cheaper.accounts[1] = AccountMeta::new(cheaper_escrow, false);
+    cheaper.accounts[5] = AccountMeta::new(ata(&cheaper_escrow, &MINT_A), false);
+    context.process_and_validate_instruction(&with_market(cheaper), &[Check::success()]);
+    assert_eq!(market_orders(&context), vec![cheaper_escrow, escrow_address()]);
+
+    // Take 传入 Market 时撤单
+    context.process_and_validate_instruction(&with_market(take_instruction()), &[Check::success()]);
+    assert_eq!(market_orders(&context), vec![cheaper_escrow]);
+
+    // 挂过单的托管关闭时必须传入 Market
+    let mut refund = refund_instruction();
+    refund.accounts[0] = AccountMeta::new(ARBITER, true);
+    refund.accounts[1] = AccountMeta::new(cheaper_escrow, false);
+    refund.accounts[3] = AccountMeta::new(ata(&cheaper_escrow, &MINT_A), false);
+    refund.accounts[4] = AccountMeta::new(ata(&ARBITER, &MINT_A), false);
+    context.process_and_validate_instruction(
+        &refund,
+        &[Check::err(ProgramError::Custom(EscrowError::MissingMarket as u32))],
+    );
+
+    // 没有挂单标记的旧托管不经过索引关闭，留在 Market 中，直到被 PruneMarket 清理；仍然存在的托管不受影响
+    context.account_store.borrow_mut().get_mut(&cheaper_escrow).unwrap().data[Escrow::LEN - 1] = 0;
+    context.process_and_validate_instruction(&prune_market_instruction(&[cheaper_escrow]), &[Check::success()]);
+    assert_eq!(market_orders(&context), vec![cheaper_escrow]);
+    context.process_and_validate_instruction(&refund, &[Check::success()]);
+    context.process_and_validate_instruction(
+        &prune_market_instruction(&[second_escrow_address(), cheaper_escrow]),
+        &[Check::success()],
+    );
+    assert!(market_orders(&context).is_empty());
+}
+
+#[test]
+fn update_and_partial_take_reprice_the_entry() {
+    let context = setup();
+    context.process_and_validate_instruction(&with_market(make_instruction(hook_accounts())), &[Check::success()]);
+    assert_eq!(market_prices(&context), vec![(RECEIVE, DEPOSIT)]);
+
+    // 挂过单的托管重新挂单时必须传入 Market，否则索引中会留下过期的价格
+    context.process_and_validate_instruction(
+        &update_instruction(2 * RECEIVE, 0, DEPOSIT / 2, None),
+        &[Check::err(ProgramError::Custom(EscrowError::MissingMarket as u32))],
+    );
+
+    // Update 修改期望数量并取回部分代币 A
+    context.process_and_validate_instruction(
+        &with_market(update_instruction(2 * RECEIVE, 0, DEPOSIT / 2, None)),
+        &[Check::success()],
+    );
+    assert_eq!(market_prices(&context), vec![(2 * RECEIVE, DEPOSIT / 2)]);
+
+    // 部分成交后按剩余的期望数量和金库余额重新挂单
+    let mut take_partial = with_market(take_instruction());
+    take_partial.data = vec![4];
+    take_partial.data.extend_from_slice(&RECEIVE.to_le_bytes());
+    take_partial.data.extend_from_slice(&0u64.to_le_bytes()); // min_amount_a
+    context.process_and_validate_instruction(&take_partial, &[Check::success()]);
+    assert_eq!(market_prices(&context), vec![(RECEIVE, DEPOSIT / 4)]);
+    assert_eq!(market_orders(&context), vec![escrow_address()]);
+}
+
+#[test]
+fn take_many_unlists_filled_escrows() {
+    let context = setup_take_many();
+    context.account_store.borrow_mut().insert(
+        ata(&MAKER, &MINT_A),
+        owned_account(&context, token_account_data(&MINT_A, &MAKER, DEPOSIT, true), &TOKEN_2022_ID),
+    );
+    // setup_take_many 创建的托管没有挂单，带 Market 重新创建 MAKER 的托管
+    context.process_and_validate_instruction(&refund_instruction(), &[Check::success()]);
+    context.process_and_validate_instruction(&with_market(make_instruction(hook_accounts())), &[Check::success()]);
+    assert_eq!(market_orders(&context), vec![escrow_address()]);
+
+    context.process_and_validate_instruction(
+        &with_market(take_many_instruction(2 * RECEIVE, true)),
+        &[Check::success(), Check::account(&escrow_address()).closed().build()],
+    );
+    assert!(market_orders(&context).is_empty());
+}
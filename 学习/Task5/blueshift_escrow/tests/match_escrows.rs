@@ -0,0 +1,131 @@
+// Match 撮合指令的集成测试：构建与运行方式见 tests/common/mod.rs
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use mollusk_svm::{program, result::Check};
+use solana_program::program_error::ProgramError;
+use solana_pubkey::Pubkey;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+use common::*;
+
+// 与 MAKER 的托管方向相反的报价：TAKER 存入全部 RECEIVE 个代币 B，要求换回 receive 个代币 A
+fn counter_escrow_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"escrow", TAKER.as_ref(), &SEED.to_le_bytes()],
+        &ESCROW_ID,
+    )
+    .0
+}
+
+fn counter_make_instruction(receive: u64) -> Instruction {
+    let escrow = counter_escrow_address();
+    let mut data = vec![0u8];
+    data.extend_from_slice(&SEED.to_le_bytes());
+    data.extend_from_slice(&receive.to_le_bytes());
+    data.extend_from_slice(&RECEIVE.to_le_bytes());
+
+    Instruction::new_with_bytes(
+        ESCROW_ID,
+        &data,
+        vec![
+            AccountMeta::new(TAKER, true),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new_readonly(MINT_B, false),
+            AccountMeta::new_readonly(MINT_A, false),
+            AccountMeta::new(ata(&TAKER, &MINT_B), false),
+            AccountMeta::new(ata(&escrow, &MINT_B), false),
+            AccountMeta::new_readonly(ESCROW_ID, false),
+            AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+            AccountMeta::new_readonly(TOKEN_2022_ID, false),
+            AccountMeta::new_readonly(ATA_ID, false),
+        ],
+    )
+}
+
+// ARBITER 作为撮合者
+fn match_instruction() -> Instruction {
+    let escrow_1 = escrow_address();
+    let escrow_2 = counter_escrow_address();
+    let mut accounts = vec![
+        AccountMeta::new(ARBITER, true),
+        AccountMeta::new(MAKER, false),
+        AccountMeta::new(escrow_1, false),
+        AccountMeta::new(TAKER, false),
+        AccountMeta::new(escrow_2, false),
+        AccountMeta::new_readonly(MINT_A, false),
+        AccountMeta::new_readonly(MINT_B, false),
+        AccountMeta::new(ata(&escrow_1, &MINT_A), false),
+        AccountMeta::new(ata(&escrow_2, &MINT_B), false),
+        AccountMeta::new(ata(&MAKER, &MINT_B), false),
+        AccountMeta::new(ata(&TAKER, &MINT_A), false),
+        AccountMeta::new(ata(&ARBITER, &MINT_A), false),
+        AccountMeta::new(ata(&ARBITER, &MINT_B), false),
+        AccountMeta::new_readonly(config_address(), false),
+        AccountMeta::new_readonly(TREASURY, false),
+        AccountMeta::new(ata(&TREASURY, &MINT_A), false),
+        AccountMeta::new(ata(&TREASURY, &MINT_B), false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    accounts.extend(hook_accounts());
+
+    Instruction::new_with_bytes(ESCROW_ID, &[25], accounts)
+}
+
+#[test]
+fn match_settles_crossing_escrows_and_pays_surplus_to_matcher() {
+    let context = setup();
+    context.process_and_validate_instruction(&make_instruction(hook_accounts()), &[Check::success()]);
+    // MAKER 出 DEPOSIT 个 A 要 RECEIVE 个 B；TAKER 出 RECEIVE 个 B 只要 DEPOSIT - 100 个 A
+    context.process_and_validate_instruction(&counter_make_instruction(DEPOSIT - 100), &[Check::success()]);
+
+    context.process_and_validate_instruction(
+        &match_instruction(),
+        &[
+            Check::success(),
+            Check::account(&escrow_address()).closed().build(),
+            Check::account(&counter_escrow_address()).closed().build(),
+        ],
+    );
+
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_B)), RECEIVE);
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), DEPOSIT - 100);
+    // 代币 A 一侧的价差归撮合者，代币 B 一侧没有价差
+    assert_eq!(token_amount(&context, &ata(&ARBITER, &MINT_A)), 100);
+    assert_eq!(lamports(&context, &ata(&ARBITER, &MINT_B)), 0);
+}
+
+#[test]
+fn match_rejects_offers_that_do_not_cross() {
+    let context = setup();
+    context.process_and_validate_instruction(&make_instruction(hook_accounts()), &[Check::success()]);
+    context.process_and_validate_instruction(&counter_make_instruction(DEPOSIT + 1), &[Check::success()]);
+
+    context.process_and_validate_instruction(
+        &match_instruction(),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::OffersDoNotCross as u32,
+        ))],
+    );
+}
+
+#[test]
+fn match_charges_protocol_fee_on_both_legs() {
+    // 100 bps = 1%（FeeSide::TokenB）：创建者 1 收到的 RECEIVE = 500 个 B 扣除 5，
+    // 创建者 2 收到的 DEPOSIT - 100 = 900 个 A 扣除 9
+    let context = setup_with_fee(100);
+    context.process_and_validate_instruction(&make_instruction(hook_accounts()), &[Check::success()]);
+    context.process_and_validate_instruction(&counter_make_instruction(DEPOSIT - 100), &[Check::success()]);
+
+    context.process_and_validate_instruction(&match_instruction(), &[Check::success()]);
+
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_B)), RECEIVE - 5);
+    assert_eq!(token_amount(&context, &ata(&TREASURY, &MINT_B)), 5);
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), DEPOSIT - 100 - 9);
+    assert_eq!(token_amount(&context, &ata(&TREASURY, &MINT_A)), 9);
+    // 价差不受协议费影响
+    assert_eq!(token_amount(&context, &ata(&ARBITER, &MINT_A)), 100);
+}
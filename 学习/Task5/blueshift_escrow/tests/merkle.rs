@@ -0,0 +1,110 @@
+// 接受者白名单 Merkle 证明的主机端测试：不需要 SBF 产物，cargo test 直接运行
+use blueshift_escrow::{EscrowError, MerkleProof, MAX_MERKLE_PROOF_DEPTH};
+use pinocchio::error::ProgramError;
+use pinocchio::Address;
+
+fn address(n: u8) -> Address {
+    Address::new_from_array([n; 32])
+}
+
+// 由叶子逐层构建 Merkle 树，返回每一层（第 0 层为叶子，最后一层为根）
+// 奇数个节点时最后一个节点直接提升到上一层
+fn build_tree(addresses: &[Address]) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![addresses.iter().map(MerkleProof::leaf).collect::<Vec<_>>()];
+    while levels.last().unwrap().len() > 1 {
+        let next = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => MerkleProof::node(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+fn proof_for(levels: &[Vec<[u8; 32]>], mut index: usize) -> Vec<u8> {
+    let mut proof = Vec::new();
+    for level in &levels[..levels.len() - 1] {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.extend_from_slice(sibling);
+        }
+        index /= 2;
+    }
+    proof
+}
+
+#[test]
+fn every_member_verifies() {
+    let addresses: Vec<Address> = (1..=5).map(address).collect();
+    let levels = build_tree(&addresses);
+    let root = levels.last().unwrap()[0];
+
+    for (index, member) in addresses.iter().enumerate() {
+        let proof = proof_for(&levels, index);
+        assert_eq!(MerkleProof::verify(&root, member, &proof), Ok(()));
+    }
+}
+
+#[test]
+fn single_member_tree_needs_no_proof() {
+    let root = MerkleProof::leaf(&address(1));
+
+    assert_eq!(MerkleProof::verify(&root, &address(1), &[]), Ok(()));
+}
+
+#[test]
+fn non_member_is_rejected() {
+    let addresses: Vec<Address> = (1..=4).map(address).collect();
+    let levels = build_tree(&addresses);
+    let root = levels.last().unwrap()[0];
+    let proof = proof_for(&levels, 0);
+
+    assert_eq!(
+        MerkleProof::verify(&root, &address(9), &proof),
+        Err(ProgramError::from(EscrowError::TakerNotAllowlisted))
+    );
+}
+
+#[test]
+fn inner_node_cannot_be_used_as_leaf() {
+    let addresses: Vec<Address> = (1..=4).map(address).collect();
+    let levels = build_tree(&addresses);
+    let root = levels.last().unwrap()[0];
+
+    // 把第 1 层的节点当作“地址”，配合剩余的证明也不能得到根
+    let forged = Address::new_from_array(levels[1][0]);
+    assert!(MerkleProof::verify(&root, &forged, &levels[1][1]).is_err());
+}
+
+#[test]
+fn malformed_proof_is_rejected() {
+    let root = MerkleProof::leaf(&address(1));
+
+    assert_eq!(
+        MerkleProof::verify(&root, &address(1), &[0; 31]),
+        Err(ProgramError::InvalidInstructionData)
+    );
+    assert_eq!(
+        MerkleProof::verify(&root, &address(1), &vec![0; 32 * (MAX_MERKLE_PROOF_DEPTH + 1)]),
+        Err(ProgramError::InvalidInstructionData)
+    );
+}
+
+#[test]
+fn hashes_match_the_documented_rule() {
+    // 客户端按 helpers.rs 中的规则用任意 sha256 实现构建白名单
+    let taker = address(1);
+    let leaf = solana_program::hash::hashv(&[taker.as_ref(), b"leaf", blueshift_escrow::ID.as_ref()]).to_bytes();
+    assert_eq!(MerkleProof::leaf(&taker), leaf);
+
+    let other = MerkleProof::leaf(&address(2));
+    let (left, right) = if leaf <= other { (leaf, other) } else { (other, leaf) };
+    let node = solana_program::hash::hashv(&[&left, b"node", &right]).to_bytes();
+    assert_eq!(MerkleProof::node(&leaf, &other), node);
+    assert_eq!(MerkleProof::node(&other, &leaf), node);
+}
@@ -0,0 +1,197 @@
+// 里程碑托管的集成测试：构建与运行方式见 tests/common/mod.rs
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use mollusk_svm::{program, result::Check};
+use solana_program::program_error::ProgramError;
+use solana_pubkey::Pubkey;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+use common::*;
+
+// 里程碑托管：MAKER 为付款方，TAKER 为收款方
+fn milestone_escrow_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"milestone", MAKER.as_ref(), &SEED.to_le_bytes()],
+        &ESCROW_ID,
+    )
+    .0
+}
+
+// 三个里程碑，合计 DEPOSIT；arbiter 为全 0 时不设仲裁人
+const MILESTONES: [u64; 3] = [200, 300, 500];
+
+fn milestone_make_instruction(arbiter: Pubkey) -> Instruction {
+    let escrow = milestone_escrow_address();
+    let mut data = vec![15u8];
+    data.extend_from_slice(&SEED.to_le_bytes());
+    data.extend_from_slice(TAKER.as_ref());
+    data.extend_from_slice(arbiter.as_ref());
+    for amount in MILESTONES {
+        data.extend_from_slice(&amount.to_le_bytes());
+    }
+
+    let mut accounts = vec![
+        AccountMeta::new(MAKER, true),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new_readonly(MINT_A, false),
+        AccountMeta::new(ata(&MAKER, &MINT_A), false),
+        AccountMeta::new(ata(&escrow, &MINT_A), false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    accounts.extend(hook_accounts());
+
+    Instruction::new_with_bytes(ESCROW_ID, &data, accounts)
+}
+
+fn approve_milestone_instruction(authority: Pubkey, index: u8) -> Instruction {
+    let escrow = milestone_escrow_address();
+    let mut accounts = vec![
+        AccountMeta::new(authority, true),
+        AccountMeta::new(MAKER, false),
+        AccountMeta::new_readonly(TAKER, false),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new_readonly(MINT_A, false),
+        AccountMeta::new(ata(&escrow, &MINT_A), false),
+        AccountMeta::new(ata(&TAKER, &MINT_A), false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    accounts.extend(hook_accounts());
+
+    Instruction::new_with_bytes(ESCROW_ID, &[16, index], accounts)
+}
+
+fn refund_milestones_instruction(authority: Pubkey) -> Instruction {
+    let escrow = milestone_escrow_address();
+    let mut accounts = vec![
+        AccountMeta::new(authority, true),
+        AccountMeta::new(MAKER, false),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new_readonly(MINT_A, false),
+        AccountMeta::new(ata(&escrow, &MINT_A), false),
+        AccountMeta::new(ata(&MAKER, &MINT_A), false),
+        AccountMeta::new_readonly(program::keyed_account_for_system_program().0, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+        AccountMeta::new_readonly(ATA_ID, false),
+    ];
+    accounts.extend(hook_accounts());
+
+    Instruction::new_with_bytes(ESCROW_ID, &[17], accounts)
+}
+
+#[test]
+fn approving_every_milestone_pays_payee_and_closes_escrow() {
+    let context = setup();
+    context.process_and_validate_instruction(&milestone_make_instruction(Pubkey::default()), &[Check::success()]);
+    let vault = ata(&milestone_escrow_address(), &MINT_A);
+    assert_eq!(token_amount(&context, &vault), DEPOSIT);
+
+    // 收款方不能自己批准
+    context.process_and_validate_instruction(
+        &approve_milestone_instruction(TAKER, 1),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::InvalidAuthority as u32,
+        ))],
+    );
+
+    context.process_and_validate_instruction(&approve_milestone_instruction(MAKER, 1), &[Check::success()]);
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), MILESTONES[1]);
+    assert_eq!(token_amount(&context, &vault), DEPOSIT - MILESTONES[1]);
+
+    context.process_and_validate_instruction(
+        &approve_milestone_instruction(MAKER, 1),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::MilestoneAlreadyReleased as u32,
+        ))],
+    );
+    context.process_and_validate_instruction(
+        &approve_milestone_instruction(MAKER, 3),
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+
+    context.process_and_validate_instruction(&approve_milestone_instruction(MAKER, 0), &[Check::success()]);
+    context.process_and_validate_instruction(
+        &approve_milestone_instruction(MAKER, 2),
+        &[
+            Check::success(),
+            Check::account(&milestone_escrow_address()).closed().build(),
+            Check::account(&vault).closed().build(),
+        ],
+    );
+
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), DEPOSIT);
+    // Make 一次 + 三次批准
+    assert_eq!(counter(&context), 4);
+}
+
+#[test]
+fn last_milestone_sweeps_tokens_sent_to_vault() {
+    let context = setup();
+    context.process_and_validate_instruction(&milestone_make_instruction(Pubkey::default()), &[Check::success()]);
+    context.process_and_validate_instruction(&approve_milestone_instruction(MAKER, 0), &[Check::success()]);
+    context.process_and_validate_instruction(&approve_milestone_instruction(MAKER, 1), &[Check::success()]);
+
+    // 模拟任何人直接向金库 ATA 转入 1 个代币
+    let vault = ata(&milestone_escrow_address(), &MINT_A);
+    context.account_store.borrow_mut().get_mut(&vault).unwrap().data[64..72]
+        .copy_from_slice(&(MILESTONES[2] + 1).to_le_bytes());
+
+    context.process_and_validate_instruction(
+        &approve_milestone_instruction(MAKER, 2),
+        &[
+            Check::success(),
+            Check::account(&milestone_escrow_address()).closed().build(),
+            Check::account(&vault).closed().build(),
+        ],
+    );
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), DEPOSIT + 1);
+}
+
+#[test]
+fn payer_refunds_unreleased_milestones() {
+    let context = setup();
+    context.process_and_validate_instruction(&milestone_make_instruction(Pubkey::default()), &[Check::success()]);
+    context.process_and_validate_instruction(&approve_milestone_instruction(MAKER, 0), &[Check::success()]);
+
+    context.process_and_validate_instruction(
+        &refund_milestones_instruction(TAKER),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::InvalidAuthority as u32,
+        ))],
+    );
+    context.process_and_validate_instruction(
+        &refund_milestones_instruction(MAKER),
+        &[Check::success(), Check::account(&milestone_escrow_address()).closed().build()],
+    );
+
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_A)), DEPOSIT - MILESTONES[0]);
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), MILESTONES[0]);
+}
+
+#[test]
+fn arbiter_gates_milestone_refund() {
+    let context = setup();
+    context.process_and_validate_instruction(&milestone_make_instruction(ARBITER), &[Check::success()]);
+
+    // 设置了仲裁人后付款方不能单方面撤回
+    context.process_and_validate_instruction(
+        &refund_milestones_instruction(MAKER),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::InvalidAuthority as u32,
+        ))],
+    );
+    // 仲裁人也可以批准里程碑
+    context.process_and_validate_instruction(&approve_milestone_instruction(ARBITER, 2), &[Check::success()]);
+    context.process_and_validate_instruction(
+        &refund_milestones_instruction(ARBITER),
+        &[Check::success(), Check::account(&milestone_escrow_address()).closed().build()],
+    );
+
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_A)), DEPOSIT - MILESTONES[2]);
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), MILESTONES[2]);
+}
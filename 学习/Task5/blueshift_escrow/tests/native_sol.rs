@@ -0,0 +1,103 @@
+// 原生 SOL 托管的集成测试：构建与运行方式见 tests/common/mod.rs
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use blueshift_escrow::ProgramAccountType;
+use mollusk_svm::result::Check;
+use solana_program::program_error::ProgramError;
+use solana_pubkey::Pubkey;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+use common::*;
+
+// 原生 SOL 托管：native = 1 表示代币 A 为 SOL，2 表示代币 B 为 SOL
+fn make_native_instruction(native: u8, mint_a: Pubkey, mint_b: Pubkey, hook_accounts: Vec<AccountMeta>) -> Instruction {
+    let mut options = vec![0u8; 8 + 32 + 32 + 2];
+    options.push(native);
+    make_instruction_with(mint_a, mint_b, &options, hook_accounts)
+}
+
+#[test]
+fn native_sol_as_token_a_is_held_in_escrow_pda() {
+    let context = setup();
+    let maker_before = lamports(&context, &MAKER);
+
+    context.process_and_validate_instruction(
+        &make_native_instruction(1, NATIVE_MINT, MINT_B, Vec::new()),
+        &[Check::success()],
+    );
+    // 不创建金库 ATA：lamports 直接保存在托管 PDA 中
+    let escrow_rent = rent(&context, blueshift_escrow::Escrow::LEN);
+    assert_eq!(lamports(&context, &escrow_address()), escrow_rent + DEPOSIT);
+    assert!(!context.account_store.borrow().contains_key(&ata(&escrow_address(), &NATIVE_MINT)));
+    assert_eq!(lamports(&context, &MAKER), maker_before - escrow_rent - DEPOSIT);
+
+    let taker_before = lamports(&context, &TAKER);
+    context.process_and_validate_instruction(
+        &take_instruction_with(NATIVE_MINT, MINT_B, None, Vec::new()),
+        &[Check::success(), Check::account(&escrow_address()).closed().build()],
+    );
+
+    // 接受者收到 DEPOSIT lamports（并为创建者的代币 B ATA 支付租金），创建者收回托管租金
+    let maker_ata_b_rent = lamports(&context, &ata(&MAKER, &MINT_B));
+    assert_eq!(lamports(&context, &TAKER), taker_before + DEPOSIT - maker_ata_b_rent);
+    assert_eq!(lamports(&context, &MAKER), maker_before - DEPOSIT);
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_B)), RECEIVE);
+}
+
+#[test]
+fn native_sol_as_token_a_refunds_deposit_and_rent() {
+    let context = setup();
+    let maker_before = lamports(&context, &MAKER);
+
+    context.process_and_validate_instruction(
+        &make_native_instruction(1, NATIVE_MINT, MINT_B, Vec::new()),
+        &[Check::success()],
+    );
+    context.process_and_validate_instruction(
+        &refund_instruction_with(NATIVE_MINT, Vec::new()),
+        &[Check::success(), Check::account(&escrow_address()).closed().build()],
+    );
+
+    assert_eq!(lamports(&context, &MAKER), maker_before);
+}
+
+#[test]
+fn native_sol_as_token_b_pays_maker_lamports() {
+    // 协议费 100 bps 也以 lamports 支付给国库
+    let context = setup_with_fee(100);
+
+    context.process_and_validate_instruction(
+        &make_native_instruction(2, MINT_A, NATIVE_MINT, hook_accounts()),
+        &[Check::success()],
+    );
+    let maker_before = lamports(&context, &MAKER);
+    let closed_rent = lamports(&context, &escrow_address()) + lamports(&context, &ata(&escrow_address(), &MINT_A));
+    let taker_before = lamports(&context, &TAKER);
+
+    context.process_and_validate_instruction(
+        &take_instruction_with(MINT_A, NATIVE_MINT, None, hook_accounts()),
+        &[Check::success(), Check::account(&escrow_address()).closed().build()],
+    );
+
+    let taker_ata_a_rent = lamports(&context, &ata(&TAKER, &MINT_A));
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), DEPOSIT);
+    assert_eq!(lamports(&context, &MAKER), maker_before + RECEIVE - 5 + closed_rent);
+    assert_eq!(lamports(&context, &TREASURY), 5);
+    assert_eq!(lamports(&context, &TAKER), taker_before - RECEIVE - taker_ata_a_rent);
+    // 代币 B 一侧没有创建任何 ATA
+    assert!(!context.account_store.borrow().contains_key(&ata(&MAKER, &NATIVE_MINT)));
+}
+
+#[test]
+fn make_rejects_native_side_without_native_mint() {
+    let context = setup();
+
+    context.process_and_validate_instruction(
+        &make_native_instruction(1, MINT_A, MINT_B, hook_accounts()),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::InvalidAddress as u32,
+        ))],
+    );
+}
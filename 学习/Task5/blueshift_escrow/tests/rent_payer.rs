@@ -0,0 +1,46 @@
+// 由单独的支付者垫付租金的集成测试：构建与运行方式见 tests/common/mod.rs
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use mollusk_svm::result::Check;
+use solana_sdk::instruction::AccountMeta;
+
+use common::*;
+
+#[test]
+fn separate_payer_funds_rent_and_receives_it_back() {
+    let context = setup();
+    let maker_lamports = lamports(&context, &MAKER);
+    let payer_lamports = lamports(&context, &ARBITER);
+
+    // payer 位置上的账户必须签名
+    let mut make = make_instruction(hook_accounts());
+    make.accounts[6] = AccountMeta::new(ARBITER, false);
+    assert!(context.process_instruction(&make).program_result.is_err());
+
+    // ARBITER 作为 payer 签名并支付托管和金库的租金，创建者只存入代币
+    make.accounts[6] = AccountMeta::new(ARBITER, true);
+    context.process_and_validate_instruction(&make, &[Check::success()]);
+    assert_eq!(lamports(&context, &MAKER), maker_lamports);
+    assert!(lamports(&context, &ARBITER) < payer_lamports);
+
+    // 退款时租金退还给支付者，必须在剩余账户中传入
+    assert!(context
+        .process_instruction(&refund_instruction())
+        .program_result
+        .is_err());
+    let mut refund = refund_instruction();
+    refund.accounts.push(AccountMeta::new(ARBITER, false));
+    context.process_and_validate_instruction(
+        &refund,
+        &[
+            Check::success(),
+            Check::account(&escrow_address()).closed().build(),
+        ],
+    );
+
+    assert_eq!(lamports(&context, &MAKER), maker_lamports);
+    assert_eq!(lamports(&context, &ARBITER), payer_lamports);
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_A)), DEPOSIT);
+}
@@ -0,0 +1,143 @@
+// 链下签名订单：SignedOrder 编码与 Ed25519 预编译指令检查
+// solana-precompiles / solana-feature-set 已标记为 deprecated，这里只用来在测试中运行真实的签名验证
+#![allow(deprecated)]
+
+use blueshift_escrow::{Ed25519Instruction, EscrowError, SignedOrder, ED25519_PROGRAM_ID};
+use pinocchio::error::ProgramError;
+use pinocchio::Address;
+use solana_feature_set::FeatureSet;
+use solana_sdk::signature::{Keypair, Signer};
+
+fn maker() -> Keypair {
+    Keypair::new_from_array([1; 32])
+}
+
+fn order(maker: &Keypair) -> SignedOrder {
+    SignedOrder {
+        maker: Address::new_from_array(maker.pubkey().to_bytes()),
+        mint_a: Address::new_from_array([2; 32]),
+        mint_b: Address::new_from_array([3; 32]),
+        amount_a: 1_000,
+        amount_b: 500,
+        expires_at: 1_700_000_000,
+        nonce: 4097,
+    }
+}
+
+// 与 solana_ed25519_program::new_ed25519_instruction 相同的单签名布局：offsets、公钥、签名、消息
+fn ed25519_instruction_data(signer: &Keypair, message: &[u8]) -> Vec<u8> {
+    let public_key_offset = 16u16;
+    let signature_offset = public_key_offset + 32;
+    let message_offset = signature_offset + 64;
+
+    let mut data = vec![1, 0];
+    for field in [
+        signature_offset,
+        u16::MAX,
+        public_key_offset,
+        u16::MAX,
+        message_offset,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(&signer.pubkey().to_bytes());
+    data.extend_from_slice(signer.sign_message(message).as_ref());
+    data.extend_from_slice(message);
+    data
+}
+
+// 用运行时的预编译实现验证签名，确认测试构造的指令本身有效
+fn precompile_accepts(data: &[u8]) -> bool {
+    let precompile = solana_precompiles::get_precompiles()
+        .iter()
+        .find(|precompile| precompile.program_id.to_bytes() == ED25519_PROGRAM_ID.to_bytes())
+        .unwrap();
+    precompile.verify(data, &[data], &FeatureSet::all_enabled()).is_ok()
+}
+
+#[test]
+fn order_round_trips_through_signed_message() {
+    let order = order(&maker());
+    let bytes = order.to_bytes();
+    assert_eq!(&bytes[..32], blueshift_escrow::ID.as_ref());
+
+    let parsed = SignedOrder::try_from(&bytes[..]).unwrap();
+    assert_eq!(parsed.to_bytes(), bytes);
+    assert_eq!(parsed.nonce, 4097);
+
+    // 过期时间包含边界，0 表示永不过期
+    assert!(!parsed.is_expired(order.expires_at - 1));
+    assert!(parsed.is_expired(order.expires_at));
+    assert!(!SignedOrder { expires_at: 0, ..parsed }.is_expired(i64::MAX));
+}
+
+#[test]
+fn order_rejects_other_programs_and_empty_amounts() {
+    let mut bytes = order(&maker()).to_bytes();
+    assert_eq!(SignedOrder::try_from(&bytes[1..]).err(), Some(ProgramError::InvalidInstructionData));
+
+    bytes[0] ^= 1;
+    assert_eq!(
+        SignedOrder::try_from(&bytes[..]).err(),
+        Some(ProgramError::Custom(EscrowError::InvalidSignature as u32))
+    );
+
+    let mut bytes = order(&maker()).to_bytes();
+    bytes[128..136].copy_from_slice(&0u64.to_le_bytes());
+    assert_eq!(
+        SignedOrder::try_from(&bytes[..]).err(),
+        Some(ProgramError::Custom(EscrowError::InvalidAmount as u32))
+    );
+}
+
+#[test]
+fn accepts_precompile_signed_by_maker() {
+    let maker = maker();
+    let order = order(&maker);
+    let data = ed25519_instruction_data(&maker, &order.to_bytes());
+
+    assert!(precompile_accepts(&data));
+    assert_eq!(Ed25519Instruction::verify(&data, &order.maker, &order.to_bytes()), Ok(()));
+}
+
+#[test]
+fn rejects_other_signer_or_message() {
+    let maker = maker();
+    let order = order(&maker);
+    let invalid = Err(ProgramError::Custom(EscrowError::InvalidSignature as u32));
+
+    // 其他人签名的同一订单
+    let other = Keypair::new_from_array([9; 32]);
+    let data = ed25519_instruction_data(&other, &order.to_bytes());
+    assert!(precompile_accepts(&data));
+    assert_eq!(Ed25519Instruction::verify(&data, &order.maker, &order.to_bytes()), invalid);
+
+    // 创建者签名的另一笔订单
+    let cheaper = SignedOrder { amount_b: 1, ..order };
+    let data = ed25519_instruction_data(&maker, &cheaper.to_bytes());
+    assert_eq!(Ed25519Instruction::verify(&data, &order.maker, &order.to_bytes()), invalid);
+}
+
+#[test]
+fn rejects_data_borrowed_from_other_instructions() {
+    let maker = maker();
+    let order = order(&maker);
+    let message = order.to_bytes();
+    let invalid = Err(ProgramError::Custom(EscrowError::InvalidSignature as u32));
+
+    // 任何一个 instruction_index 指向其他指令，读到的公钥或消息就不是被验证的那份
+    for field in [1, 3, 6] {
+        let mut data = ed25519_instruction_data(&maker, &message);
+        let offset = 2 + field * 2;
+        data[offset..offset + 2].copy_from_slice(&0u16.to_le_bytes());
+        assert_eq!(Ed25519Instruction::verify(&data, &order.maker, &message), invalid);
+    }
+
+    // 只支持单个签名
+    let mut data = ed25519_instruction_data(&maker, &message);
+    data[0] = 2;
+    assert_eq!(Ed25519Instruction::verify(&data, &order.maker, &message), invalid);
+    assert_eq!(Ed25519Instruction::verify(&data[..15], &order.maker, &message), invalid);
+}
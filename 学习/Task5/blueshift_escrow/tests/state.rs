@@ -0,0 +1,571 @@
+// Escrow 零拷贝布局的主机端测试：不需要 SBF 产物，cargo test 直接运行
+use blueshift_escrow::{AccountDiscriminator, ArbiterEscrow, Auction, BundleEscrow, Escrow, EscrowError, Htlc, MakerState, Market, MilestoneEscrow, NativeSide, NonceBitmap, ProgramAccountType, MAX_BUNDLE_LEGS, MAX_MARKET_ORDERS, MAX_MILESTONES, NATIVE_MINT, NONCES_PER_PAGE};
+use pinocchio::error::ProgramError;
+use pinocchio::Address;
+
+const SEED: u64 = 0x0102_0304_0506_0708;
+const RECEIVE: u64 = u64::MAX - 1;
+const BUMP: u8 = 254;
+const EXPIRES_AT: i64 = 1_700_000_000;
+const ALLOWLIST_ROOT: [u8; 32] = [5; 32];
+const REFERRAL_BPS: u16 = 0x0102;
+
+fn maker() -> Address {
+    Address::new_from_array([1; 32])
+}
+
+fn mint_a() -> Address {
+    Address::new_from_array([2; 32])
+}
+
+fn mint_b() -> Address {
+    Address::new_from_array([3; 32])
+}
+
+fn taker() -> Address {
+    Address::new_from_array([4; 32])
+}
+
+// 在 buffer[offset..offset + LEN] 中初始化一个 Escrow 并写入所有字段
+fn init_at(buffer: &mut [u8], offset: usize) {
+    let escrow = Escrow::init(&mut buffer[offset..offset + Escrow::LEN]).unwrap();
+    escrow.set_inner(SEED, maker(), mint_a(), mint_b(), RECEIVE, [BUMP], EXPIRES_AT, taker(), ALLOWLIST_ROOT, REFERRAL_BPS, NativeSide::TokenB);
+}
+
+fn assert_fields(escrow: &Escrow) {
+    assert_eq!(escrow.discriminator(), AccountDiscriminator::Escrow as u8);
+    assert_eq!(escrow.seed(), SEED);
+    assert_eq!(escrow.maker(), &maker());
+    assert_eq!(escrow.mint_a(), &mint_a());
+    assert_eq!(escrow.mint_b(), &mint_b());
+    assert_eq!(escrow.receive(), RECEIVE);
+    assert_eq!(escrow.bump(), [BUMP]);
+    assert_eq!(escrow.expires_at(), EXPIRES_AT);
+    assert_eq!(escrow.taker(), &taker());
+    assert_eq!(escrow.allowlist_root(), Some(&ALLOWLIST_ROOT));
+    assert_eq!(escrow.referral_bps(), REFERRAL_BPS);
+    assert_eq!(escrow.native(), Ok(NativeSide::TokenB));
+}
+
+#[test]
+fn len_matches_layout() {
+    assert_eq!(Escrow::LEN, 1 + 1 + 8 + 32 * 3 + 8 + 8 + 32 + 32 + 2 + 1 + 8 * 3 + 32 + 1);
+    assert_eq!(Escrow::LEN, core::mem::size_of::<Escrow>());
+    assert_eq!(core::mem::align_of::<Escrow>(), 1);
+}
+
+#[test]
+fn set_inner_round_trips_every_field() {
+    let mut data = [0u8; Escrow::LEN];
+    init_at(&mut data, 0);
+
+    assert_fields(Escrow::load(&data).unwrap());
+    assert_fields(Escrow::load_mut(&mut data).unwrap());
+}
+
+#[test]
+fn setters_round_trip_every_field() {
+    let mut data = [0u8; Escrow::LEN];
+    let escrow = Escrow::init(&mut data).unwrap();
+    escrow.set_seed(SEED);
+    escrow.set_maker(maker());
+    escrow.set_mint_a(mint_a());
+    escrow.set_mint_b(mint_b());
+    escrow.set_receive(RECEIVE);
+    escrow.set_bump([BUMP]);
+    escrow.set_expires_at(EXPIRES_AT);
+    escrow.set_taker(taker());
+    escrow.set_allowlist_root(ALLOWLIST_ROOT);
+    escrow.set_referral_bps(REFERRAL_BPS);
+    escrow.set_native(NativeSide::TokenB);
+
+    assert_fields(Escrow::load(&data).unwrap());
+}
+
+#[test]
+fn fields_are_little_endian_at_fixed_offsets() {
+    let mut data = [0u8; Escrow::LEN];
+    init_at(&mut data, 0);
+
+    assert_eq!(data[0], AccountDiscriminator::Escrow as u8);
+    assert_eq!(data[1], BUMP);
+    assert_eq!(data[2..10], SEED.to_le_bytes());
+    assert_eq!(data[10..42], *maker().as_array());
+    assert_eq!(data[42..74], *mint_a().as_array());
+    assert_eq!(data[74..106], *mint_b().as_array());
+    assert_eq!(data[106..114], RECEIVE.to_le_bytes());
+    assert_eq!(data[114..122], EXPIRES_AT.to_le_bytes());
+    assert_eq!(data[122..154], *taker().as_array());
+    assert_eq!(data[154..186], ALLOWLIST_ROOT);
+    assert_eq!(data[186..188], REFERRAL_BPS.to_le_bytes());
+    assert_eq!(data[188], NativeSide::TokenB as u8);
+}
+
+#[test]
+fn rent_recipient_is_stored_after_the_auction_fields() {
+    let mut data = [0u8; Escrow::LEN];
+    let escrow = Escrow::init(&mut data).unwrap();
+    escrow.set_rent_recipient(taker());
+
+    assert_eq!(escrow.rent_recipient(), &taker());
+    assert_eq!(data[213..245], *taker().as_array());
+}
+
+#[test]
+fn listed_flag_is_the_last_byte() {
+    let mut data = [0u8; Escrow::LEN];
+    let escrow = Escrow::init(&mut data).unwrap();
+    assert!(!escrow.is_listed());
+
+    escrow.set_listed();
+
+    assert!(escrow.is_listed());
+    assert_eq!(data[245], 1);
+}
+
+#[test]
+fn zero_allowlist_root_disables_allowlist() {
+    let mut data = [0u8; Escrow::LEN];
+    let escrow = Escrow::init(&mut data).unwrap();
+
+    assert_eq!(escrow.allowlist_root(), None);
+}
+
+#[test]
+fn native_flag_defaults_to_spl_and_rejects_unknown_values() {
+    let mut data = [0u8; Escrow::LEN];
+    Escrow::init(&mut data).unwrap();
+    assert_eq!(Escrow::load(&data).unwrap().native(), Ok(NativeSide::None));
+
+    data[188] = 3;
+    assert_eq!(
+        Escrow::load(&data).unwrap().native(),
+        Err(ProgramError::InvalidAccountData)
+    );
+
+    assert_eq!(NativeSide::try_from(1), Ok(NativeSide::TokenA));
+    assert_eq!(NativeSide::try_from(3), Err(ProgramError::InvalidInstructionData));
+}
+
+#[test]
+fn native_mint_is_wrapped_sol() {
+    let wsol: solana_pubkey::Pubkey = "So11111111111111111111111111111111111111112".parse().unwrap();
+    assert_eq!(NATIVE_MINT.as_array(), &wsol.to_bytes());
+}
+
+#[test]
+fn designated_taker_restricts_take() {
+    let mut data = [0u8; Escrow::LEN];
+    let escrow = Escrow::init(&mut data).unwrap();
+
+    // 未指定接受者：任何人都可以接受
+    assert!(escrow.can_take(&taker()));
+    assert!(escrow.can_take(&maker()));
+
+    escrow.set_taker(taker());
+    assert!(escrow.can_take(&taker()));
+    assert!(!escrow.can_take(&maker()));
+}
+
+#[test]
+fn expiry_is_inclusive_and_zero_never_expires() {
+    let mut data = [0u8; Escrow::LEN];
+    let escrow = Escrow::init(&mut data).unwrap();
+
+    assert!(!escrow.is_expired(i64::MAX));
+
+    escrow.set_expires_at(EXPIRES_AT);
+    assert!(!escrow.is_expired(EXPIRES_AT - 1));
+    assert!(escrow.is_expired(EXPIRES_AT));
+    assert!(escrow.is_expired(EXPIRES_AT + 1));
+}
+
+#[test]
+fn load_at_unaligned_offset() {
+    // 在奇数偏移处放置账户数据，验证没有未对齐访问
+    let mut buffer = [0u8; Escrow::LEN + 1];
+    init_at(&mut buffer, 1);
+
+    assert_fields(Escrow::load(&buffer[1..]).unwrap());
+}
+
+#[test]
+fn load_rejects_wrong_length() {
+    let mut data = [0u8; Escrow::LEN + 1];
+    data[0] = AccountDiscriminator::Escrow as u8;
+
+    assert_eq!(Escrow::load(&data).err(), Some(ProgramError::InvalidAccountData));
+    assert_eq!(
+        Escrow::load(&data[..Escrow::LEN - 1]).err(),
+        Some(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn load_rejects_other_discriminators() {
+    let mut data = [0u8; Escrow::LEN];
+    assert_eq!(Escrow::load(&data).err(), Some(ProgramError::InvalidAccountData));
+
+    data[0] = AccountDiscriminator::Closed as u8;
+    assert_eq!(Escrow::load(&data).err(), Some(ProgramError::InvalidAccountData));
+}
+
+#[test]
+fn init_rejects_initialized_account() {
+    let mut data = [0u8; Escrow::LEN];
+    init_at(&mut data, 0);
+
+    assert_eq!(
+        Escrow::init(&mut data).err(),
+        Some(ProgramError::AccountAlreadyInitialized)
+    );
+}
+
+#[test]
+fn fill_amount_a_is_proportional_and_rounds_down() {
+    let mut data = [0u8; Escrow::LEN];
+    let escrow = Escrow::init(&mut data).unwrap();
+    escrow.set_receive(300);
+
+    // 1000 * 100 / 300 = 333.33... -> 333，舍入误差留在金库
+    assert_eq!(escrow.fill_amount_a(1_000, 100, 0), Some(333));
+    // 全部成交释放金库全部余额
+    assert_eq!(escrow.fill_amount_a(1_000, 300, 0), Some(1_000));
+    // 大数不溢出
+    escrow.set_receive(u64::MAX);
+    assert_eq!(escrow.fill_amount_a(u64::MAX, u64::MAX - 1, 0), Some(u64::MAX - 1));
+}
+
+#[test]
+fn fill_amount_a_rejects_invalid_amounts() {
+    let mut data = [0u8; Escrow::LEN];
+    let escrow = Escrow::init(&mut data).unwrap();
+    escrow.set_receive(1_000);
+
+    assert_eq!(escrow.fill_amount_a(1_000, 0, 0), None);
+    assert_eq!(escrow.fill_amount_a(1_000, 1_001, 0), None);
+    // 1 * 999 / 1000 被舍入为 0：接受者付款却拿不到代币 A
+    assert_eq!(escrow.fill_amount_a(1, 999, 0), None);
+}
+
+#[test]
+fn auction_price_decays_linearly_and_rounds_up() {
+    let mut data = [0u8; Escrow::LEN];
+    let escrow = Escrow::init(&mut data).unwrap();
+    escrow.set_receive(1_000);
+
+    // 未设置拍卖：任何时间都是固定价格
+    assert!(!escrow.is_auction());
+    assert_eq!(escrow.receive_at(i64::MAX), 1_000);
+
+    escrow.set_auction(400, EXPIRES_AT, EXPIRES_AT + 300).unwrap();
+    assert!(escrow.is_auction());
+    assert_eq!(escrow.receive_at(0), 1_000);
+    assert_eq!(escrow.receive_at(EXPIRES_AT), 1_000);
+    assert_eq!(escrow.receive_at(EXPIRES_AT + 150), 700);
+    // 下降量 600 * 1 / 300 = 2；600 * 100 / 300 = 200
+    assert_eq!(escrow.receive_at(EXPIRES_AT + 1), 998);
+    assert_eq!(escrow.receive_at(EXPIRES_AT + 100), 800);
+    // 1000 - floor(600 * 299 / 300) = 1000 - 598：价格向上取整
+    assert_eq!(escrow.receive_at(EXPIRES_AT + 299), 402);
+    assert_eq!(escrow.receive_at(EXPIRES_AT + 300), 400);
+    assert_eq!(escrow.receive_at(i64::MAX), 400);
+
+    // 部分成交按当前价格计算比例
+    assert_eq!(escrow.fill_amount_a(1_000, 350, EXPIRES_AT + 150), Some(500));
+    assert_eq!(escrow.fill_amount_a(1_000, 701, EXPIRES_AT + 150), None);
+
+    assert_eq!(data[189..197], 400u64.to_le_bytes());
+    assert_eq!(data[197..205], EXPIRES_AT.to_le_bytes());
+    assert_eq!(data[205..213], (EXPIRES_AT + 300).to_le_bytes());
+}
+
+#[test]
+fn auction_rejects_rising_or_empty_schedules() {
+    let mut data = [0u8; Escrow::LEN];
+    let escrow = Escrow::init(&mut data).unwrap();
+    escrow.set_receive(1_000);
+    let invalid = Some(ProgramError::Custom(EscrowError::InvalidAmount as u32));
+
+    assert_eq!(escrow.set_auction(1_001, 0, 1).err(), invalid);
+    assert_eq!(escrow.set_auction(500, 10, 10).err(), invalid);
+    assert_eq!(escrow.set_auction(500, 10, 0).err(), invalid);
+    assert_eq!(escrow.set_auction(0, 0, 0), Ok(()));
+    assert_eq!(escrow.set_auction(1_000, -5, 5), Ok(()));
+}
+
+#[test]
+fn scale_receive_keeps_the_auction_curve_proportional() {
+    let mut data = [0u8; Escrow::LEN];
+    let escrow = Escrow::init(&mut data).unwrap();
+
+    // 固定价格：正好减去 amount_b
+    escrow.set_receive(1_000);
+    escrow.scale_receive(1_000, 300);
+    assert_eq!(escrow.receive(), 700);
+    assert_eq!(escrow.end_receive(), 0);
+
+    // 拍卖中价格为 700 时成交 350：整条曲线减半
+    escrow.set_receive(1_000);
+    escrow.set_auction(400, EXPIRES_AT, EXPIRES_AT + 300).unwrap();
+    escrow.scale_receive(700, 350);
+    assert_eq!(escrow.receive(), 500);
+    assert_eq!(escrow.end_receive(), 200);
+    assert_eq!(escrow.receive_at(EXPIRES_AT + 150), 350);
+
+    // 向上取整：1000 * 1 / 3 = 333.3 -> 334
+    escrow.set_receive(1_000);
+    escrow.set_auction(0, 0, 0).unwrap();
+    escrow.scale_receive(3, 2);
+    assert_eq!(escrow.receive(), 334);
+}
+
+#[test]
+fn htlc_round_trips_and_times_out_inclusively() {
+    assert_eq!(Htlc::LEN, 1 + 1 + 8 + 32 * 3 + 32 + 8);
+
+    let mut data = [0u8; Htlc::LEN];
+    let htlc = Htlc::init(&mut data).unwrap();
+    htlc.set_inner(SEED, maker(), taker(), mint_a(), ALLOWLIST_ROOT, EXPIRES_AT, [BUMP]);
+
+    let htlc = Htlc::load(&data).unwrap();
+    assert_eq!(htlc.seed(), SEED);
+    assert_eq!(htlc.maker(), &maker());
+    assert_eq!(htlc.taker(), &taker());
+    assert_eq!(htlc.mint_a(), &mint_a());
+    assert_eq!(htlc.hashlock(), &ALLOWLIST_ROOT);
+    assert_eq!(htlc.timeout(), EXPIRES_AT);
+    assert_eq!(htlc.bump(), [BUMP]);
+
+    assert!(!htlc.is_timed_out(EXPIRES_AT - 1));
+    assert!(htlc.is_timed_out(EXPIRES_AT));
+}
+
+#[test]
+fn htlc_and_escrow_cannot_be_confused() {
+    let mut data = [0u8; Htlc::LEN];
+    Htlc::init(&mut data).unwrap();
+    assert_eq!(data[0], AccountDiscriminator::Htlc as u8);
+
+    // 长度不同，判别器也不同：两种账户互相加载都会失败
+    let mut escrow = [0u8; Escrow::LEN];
+    escrow[0] = AccountDiscriminator::Htlc as u8;
+    assert_eq!(Escrow::load(&escrow).err(), Some(ProgramError::InvalidAccountData));
+    data[0] = AccountDiscriminator::Escrow as u8;
+    assert_eq!(Htlc::load(&data).err(), Some(ProgramError::InvalidAccountData));
+}
+
+#[test]
+fn arbiter_acts_only_after_dispute() {
+    let arbiter = Address::new_from_array([9; 32]);
+    let mut data = [0u8; ArbiterEscrow::LEN];
+    let escrow = ArbiterEscrow::init(&mut data).unwrap();
+    // buyer = maker，seller = taker
+    escrow.set_inner(SEED, maker(), taker(), arbiter, mint_a(), [BUMP]);
+
+    assert!(!escrow.is_disputed());
+    assert!(escrow.can_release(&maker()));
+    assert!(escrow.can_cancel(&taker()));
+    // 双方都不能替对方做决定
+    assert!(!escrow.can_release(&taker()));
+    assert!(!escrow.can_cancel(&maker()));
+    assert!(!escrow.can_release(&arbiter));
+    assert!(!escrow.can_cancel(&arbiter));
+
+    escrow.set_disputed();
+    assert!(escrow.is_disputed());
+    assert!(escrow.can_release(&arbiter));
+    assert!(escrow.can_cancel(&arbiter));
+    assert!(escrow.can_release(&maker()));
+    assert!(escrow.can_cancel(&taker()));
+}
+
+#[test]
+fn milestones_release_once_each_and_complete_together() {
+    let mut data = [0u8; MilestoneEscrow::LEN];
+    let escrow = MilestoneEscrow::init(&mut data).unwrap();
+    escrow.set_inner(SEED, maker(), taker(), Address::default(), mint_a(), &[100, 200, 300], [BUMP]).unwrap();
+
+    assert_eq!(escrow.count(), 3);
+    assert_eq!(escrow.amount(2), Some(300));
+    assert_eq!(escrow.amount(3), None);
+    assert!(!escrow.is_complete());
+
+    // 可以不按顺序批准；同一里程碑不能释放两次
+    assert_eq!(escrow.release(1), Ok(200));
+    assert_eq!(escrow.release(1).err(), Some(ProgramError::Custom(EscrowError::MilestoneAlreadyReleased as u32)));
+    assert_eq!(escrow.release(3).err(), Some(ProgramError::InvalidInstructionData));
+    assert_eq!(escrow.release(0), Ok(100));
+    assert!(!escrow.is_complete());
+    assert_eq!(escrow.release(2), Ok(300));
+    assert!(escrow.is_complete());
+    assert_eq!(escrow.released(), 0b111);
+
+    // 数量必须在 1..=MAX_MILESTONES 之间
+    assert!(escrow.set_inner(SEED, maker(), taker(), Address::default(), mint_a(), &[], [BUMP]).is_err());
+    assert!(escrow.set_inner(SEED, maker(), taker(), Address::default(), mint_a(), &[1; MAX_MILESTONES + 1], [BUMP]).is_err());
+    escrow.set_inner(SEED, maker(), taker(), Address::default(), mint_a(), &[1; MAX_MILESTONES], [BUMP]).unwrap();
+    for index in 0..MAX_MILESTONES {
+        escrow.release(index).unwrap();
+    }
+    assert!(escrow.is_complete());
+}
+
+#[test]
+fn milestone_arbiter_takes_over_refunds() {
+    let arbiter = Address::new_from_array([9; 32]);
+    let mut data = [0u8; MilestoneEscrow::LEN];
+    let escrow = MilestoneEscrow::init(&mut data).unwrap();
+
+    // 没有仲裁人：只有付款方可以批准和退款
+    escrow.set_inner(SEED, maker(), taker(), Address::default(), mint_a(), &[1], [BUMP]).unwrap();
+    assert_eq!(escrow.arbiter(), None);
+    assert!(escrow.can_approve(&maker()));
+    assert!(escrow.can_refund(&maker()));
+    assert!(!escrow.can_approve(&taker()));
+    assert!(!escrow.can_refund(&taker()));
+
+    // 有仲裁人：付款方仍可批准，但退款只能由仲裁人决定
+    escrow.set_inner(SEED, maker(), taker(), arbiter, mint_a(), &[1], [BUMP]).unwrap();
+    assert_eq!(escrow.arbiter(), Some(&arbiter));
+    assert!(escrow.can_approve(&maker()));
+    assert!(escrow.can_approve(&arbiter));
+    assert!(!escrow.can_refund(&maker()));
+    assert!(escrow.can_refund(&arbiter));
+    assert!(!escrow.can_approve(&taker()));
+}
+
+#[test]
+fn nonce_bitmap_uses_each_nonce_once_per_page() {
+    let mut data = [0u8; NonceBitmap::LEN];
+    let bitmap = NonceBitmap::init(&mut data).unwrap();
+    let page = NonceBitmap::page_of(NONCES_PER_PAGE * 3 + 5);
+    assert_eq!(page, 3);
+    bitmap.set_inner(maker(), page, [BUMP]);
+
+    let first = NONCES_PER_PAGE * 3;
+    let last = NONCES_PER_PAGE * 4 - 1;
+    assert_eq!(bitmap.is_used(first), Ok(false));
+    bitmap.use_nonce(first).unwrap();
+    bitmap.use_nonce(last).unwrap();
+    assert_eq!(bitmap.is_used(first), Ok(true));
+    assert_eq!(bitmap.is_used(first + 1), Ok(false));
+    assert_eq!(bitmap.use_nonce(last).err(), Some(ProgramError::Custom(EscrowError::NonceAlreadyUsed as u32)));
+
+    // 其他页的 nonce 不能记录在这个账户中
+    assert_eq!(bitmap.use_nonce(last + 1).err(), Some(ProgramError::InvalidArgument));
+    assert_eq!(bitmap.is_used(first - 1).err(), Some(ProgramError::InvalidArgument));
+
+    assert_eq!(bitmap.maker(), &maker());
+    assert_eq!(bitmap.page(), 3);
+    assert_eq!(data[0], AccountDiscriminator::NonceBitmap as u8);
+}
+
+#[test]
+fn auction_bids_must_beat_minimum_and_leader() {
+    let mut data = [0u8; Auction::LEN];
+    let auction = Auction::init(&mut data).unwrap();
+    auction.set_inner(SEED, maker(), mint_a(), mint_b(), 100, EXPIRES_AT, [BUMP]);
+    assert_eq!(auction.highest_bidder(), None);
+    assert!(!auction.is_ended(EXPIRES_AT - 1));
+    assert!(auction.is_ended(EXPIRES_AT));
+
+    // 第一笔出价可以等于最低出价，之后必须严格高于当前最高出价
+    assert_eq!(auction.place_bid(taker(), 99).err(), Some(ProgramError::Custom(EscrowError::BidTooLow as u32)));
+    auction.place_bid(taker(), 100).unwrap();
+    assert_eq!(auction.highest_bidder(), Some(&taker()));
+    assert_eq!(auction.place_bid(maker(), 100).err(), Some(ProgramError::Custom(EscrowError::BidTooLow as u32)));
+    auction.place_bid(maker(), 101).unwrap();
+    assert_eq!(auction.highest_bidder(), Some(&maker()));
+    assert_eq!(auction.highest_bid(), 101);
+    assert_eq!(data[0], AccountDiscriminator::Auction as u8);
+}
+
+#[test]
+fn bundle_legs_round_trip_and_reject_duplicates() {
+    let mut data = [0u8; BundleEscrow::LEN];
+    let escrow = BundleEscrow::init(&mut data).unwrap();
+    escrow.set_inner(SEED, maker(), &[(mint_a(), 10), (taker(), 20)], &[(mint_b(), 30)], [BUMP]).unwrap();
+
+    assert_eq!(escrow.give_count(), 2);
+    assert_eq!(escrow.receive_count(), 1);
+    assert_eq!(escrow.give(1), Some((&taker(), 20)));
+    assert_eq!(escrow.give(2), None);
+    assert_eq!(escrow.receive(0), Some((&mint_b(), 30)));
+    assert_eq!(escrow.receive(1), None);
+    assert_eq!(escrow.give_legs()[0], (mint_a(), 10));
+    assert_eq!(escrow.receive_legs()[1], (Address::default(), 0));
+
+    // 每一边 1..=MAX_BUNDLE_LEGS 种代币，mint 不能重复，数量不能为 0
+    assert_eq!(escrow.set_inner(SEED, maker(), &[], &[(mint_b(), 1)], [BUMP]).err(), Some(ProgramError::InvalidInstructionData));
+    assert_eq!(
+        escrow.set_inner(SEED, maker(), &[(mint_a(), 1); MAX_BUNDLE_LEGS + 1], &[(mint_b(), 1)], [BUMP]).err(),
+        Some(ProgramError::InvalidInstructionData)
+    );
+    assert_eq!(
+        escrow.set_inner(SEED, maker(), &[(mint_a(), 1), (mint_a(), 2)], &[(mint_b(), 1)], [BUMP]).err(),
+        Some(ProgramError::Custom(EscrowError::InvalidAddress as u32))
+    );
+    assert_eq!(
+        escrow.set_inner(SEED, maker(), &[(mint_a(), 1)], &[(mint_b(), 0)], [BUMP]).err(),
+        Some(ProgramError::Custom(EscrowError::InvalidAmount as u32))
+    );
+    assert_eq!(data[0], AccountDiscriminator::BundleEscrow as u8);
+}
+
+#[test]
+fn maker_state_allocates_increasing_seeds() {
+    let mut data = [0u8; MakerState::LEN];
+    let state = MakerState::init(&mut data).unwrap();
+    state.set_inner(maker(), [BUMP]);
+
+    assert_eq!(state.next_seed(), 0);
+    assert_eq!(state.allocate_seed(), Ok(0));
+    assert_eq!(state.allocate_seed(), Ok(1));
+    assert_eq!(state.next_seed(), 2);
+    assert_eq!(state.maker(), &maker());
+    assert_eq!(data[0], AccountDiscriminator::MakerState as u8);
+
+    // 计数器用尽时不回绕到已分配过的 seed
+    data[34..42].copy_from_slice(&u64::MAX.to_le_bytes());
+    let state = MakerState::load_mut(&mut data).unwrap();
+    assert_eq!(state.allocate_seed(), Err(ProgramError::ArithmeticOverflow));
+    assert_eq!(state.next_seed(), u64::MAX);
+}
+
+#[test]
+fn market_keeps_best_offers_sorted_by_price() {
+    let mut data = [0u8; Market::LEN];
+    let market = Market::init(&mut data).unwrap();
+    market.set_inner(mint_a(), mint_b(), [BUMP]);
+    let escrow = |index: u8| Address::new_from_array([index; 32]);
+    let escrows = |market: &Market| market.orders().iter().map(|order| *order.escrow()).collect::<Vec<_>>();
+
+    // 价格 = receive / amount：2.0、1.0、2.0（与第一条同价，排在其后）
+    assert!(market.insert(escrow(1), 200, 100));
+    assert!(market.insert(escrow(2), 50, 50));
+    assert!(market.insert(escrow(3), 400, 200));
+    assert_eq!(escrows(market), vec![escrow(2), escrow(1), escrow(3)]);
+    assert_eq!(market.orders()[1].receive(), 200);
+    assert_eq!(market.orders()[1].amount(), 100);
+
+    // 重新挂单时替换旧的价格
+    assert!(market.insert(escrow(3), 10, 100));
+    assert_eq!(escrows(market), vec![escrow(3), escrow(2), escrow(1)]);
+
+    assert!(market.remove(&escrow(2)));
+    assert!(!market.remove(&escrow(2)));
+    assert_eq!(escrows(market), vec![escrow(3), escrow(1)]);
+
+    // 已满时只接受比最差挂单更便宜的报价，并挤掉最差的一条
+    for index in 0..(MAX_MARKET_ORDERS - 2) as u8 {
+        assert!(market.insert(escrow(10 + index), 300, 100));
+    }
+    assert_eq!(market.len(), MAX_MARKET_ORDERS);
+    assert!(!market.insert(escrow(100), 300, 100));
+    assert!(market.insert(escrow(101), 150, 100));
+    assert_eq!(market.len(), MAX_MARKET_ORDERS);
+    assert_eq!(escrows(market)[..3], [escrow(3), escrow(101), escrow(1)]);
+    assert!(!market.contains(&escrow(10 + (MAX_MARKET_ORDERS - 3) as u8)));
+    assert_eq!(data[0], AccountDiscriminator::Market as u8);
+}
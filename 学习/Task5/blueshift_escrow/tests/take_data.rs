@@ -0,0 +1,90 @@
+// Take / TakePartial / TakeMany 指令数据解析的主机端测试：不需要 SBF 产物，cargo test 直接运行
+use blueshift_escrow::{TakeInstructionData, TakeManyInstructionData, TakePartialInstructionData};
+use pinocchio::error::ProgramError;
+
+fn take_data(min_amount_a: u64, proof: &[u8], suffix: &[u8]) -> Vec<u8> {
+    let mut data = min_amount_a.to_le_bytes().to_vec();
+    data.extend_from_slice(proof);
+    data.extend_from_slice(suffix);
+    data
+}
+
+#[test]
+fn take_data_requires_min_amount_a() {
+    assert!(matches!(
+        TakeInstructionData::try_from(&[0u8; 7][..]),
+        Err(ProgramError::InvalidInstructionData)
+    ));
+
+    let data = take_data(1_000, &[], &[]);
+    let parsed = TakeInstructionData::try_from(&data[..]).unwrap();
+    assert_eq!(parsed.min_amount_a, 1_000);
+    assert!(parsed.proof.is_empty());
+    assert_eq!(parsed.max_pay, None);
+    assert!(!parsed.flash);
+}
+
+#[test]
+fn take_data_splits_proof_max_pay_and_flash() {
+    let data = take_data(1, &[7; 64], &500u64.to_le_bytes());
+    let parsed = TakeInstructionData::try_from(&data[..]).unwrap();
+    assert_eq!(parsed.proof, &[7; 64]);
+    assert_eq!(parsed.max_pay, Some(500));
+    assert!(!parsed.flash);
+
+    let mut suffix = u64::MAX.to_le_bytes().to_vec();
+    suffix.push(1);
+    let data = take_data(1, &[], &suffix);
+    let parsed = TakeInstructionData::try_from(&data[..]).unwrap();
+    assert_eq!(parsed.max_pay, Some(u64::MAX));
+    assert!(parsed.flash);
+
+    // 既不是 max_pay 也不是 max_pay + flash 的余数
+    let data = take_data(1, &[7; 32], &[1, 2, 3]);
+    assert!(matches!(
+        TakeInstructionData::try_from(&data[..]),
+        Err(ProgramError::InvalidInstructionData)
+    ));
+}
+
+#[test]
+fn take_partial_data_reads_amount_b_then_min_amount_a() {
+    let mut data = 250u64.to_le_bytes().to_vec();
+    data.extend_from_slice(&take_data(400, &[7; 32], &[]));
+    let parsed = TakePartialInstructionData::try_from(&data[..]).unwrap();
+    assert_eq!(parsed.amount_b, 250);
+    assert_eq!(parsed.min_amount_a, 400);
+    assert_eq!(parsed.proof, &[7; 32]);
+
+    // 缺少 min_amount_a
+    assert!(matches!(
+        TakePartialInstructionData::try_from(&250u64.to_le_bytes()[..]),
+        Err(ProgramError::InvalidInstructionData)
+    ));
+
+    // 部分成交不接受 max_pay
+    let mut data = 250u64.to_le_bytes().to_vec();
+    data.extend_from_slice(&take_data(400, &[], &500u64.to_le_bytes()));
+    assert!(matches!(
+        TakePartialInstructionData::try_from(&data[..]),
+        Err(ProgramError::InvalidInstructionData)
+    ));
+}
+
+#[test]
+fn take_many_data_layout() {
+    let mut data = 1_000u64.to_le_bytes().to_vec();
+    data.extend_from_slice(&1_500u64.to_le_bytes());
+    data.extend_from_slice(&[1, 3]);
+    let parsed = TakeManyInstructionData::try_from(&data[..]).unwrap();
+    assert_eq!(parsed.max_pay, 1_000);
+    assert_eq!(parsed.min_amount_a, 1_500);
+    assert!(parsed.all_or_nothing);
+    assert_eq!(parsed.count, 3);
+
+    // 旧布局（没有 min_amount_a）被拒绝
+    assert!(matches!(
+        TakeManyInstructionData::try_from(&data[8..]),
+        Err(ProgramError::InvalidInstructionData)
+    ));
+}
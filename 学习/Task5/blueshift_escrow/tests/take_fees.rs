@@ -0,0 +1,99 @@
+// 协议费与推荐费的集成测试：构建与运行方式见 tests/common/mod.rs
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use mollusk_svm::result::Check;
+use solana_program::program_error::ProgramError;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+use common::*;
+
+// 带推荐费的 Make：expires_at、taker、allowlist_root 均为 0（不启用）
+fn make_instruction_with_referral(referral_bps: u16) -> Instruction {
+    let mut options = vec![0u8; 8 + 32 + 32];
+    options.extend_from_slice(&referral_bps.to_le_bytes());
+    make_instruction_with_options(&options, hook_accounts())
+}
+
+#[test]
+fn take_routes_protocol_fee_to_treasury() {
+    // 100 bps = 1%：RECEIVE = 500 -> 协议费 5
+    let context = setup_with_fee(100);
+
+    context.process_and_validate_instruction(&make_instruction(hook_accounts()), &[Check::success()]);
+    context.process_and_validate_instruction(&take_instruction(), &[Check::success()]);
+
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), DEPOSIT);
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_B)), RECEIVE - 5);
+    assert_eq!(token_amount(&context, &ata(&TREASURY, &MINT_B)), 5);
+}
+
+#[test]
+fn take_pays_referral_from_maker_proceeds() {
+    // 协议费 100 bps（5），推荐费 200 bps（10），都从 RECEIVE = 500 中扣除
+    let context = setup_with_fee(100);
+
+    context.process_and_validate_instruction(&make_instruction_with_referral(200), &[Check::success()]);
+    context.process_and_validate_instruction(&take_instruction_with_referrer(Some(REFERRER)), &[Check::success()]);
+
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), DEPOSIT);
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_B)), RECEIVE - 5 - 10);
+    assert_eq!(token_amount(&context, &ata(&TREASURY, &MINT_B)), 5);
+    assert_eq!(token_amount(&context, &ata(&REFERRER, &MINT_B)), 10);
+}
+
+#[test]
+fn take_without_referrer_pays_no_referral() {
+    let context = setup();
+
+    context.process_and_validate_instruction(&make_instruction_with_referral(200), &[Check::success()]);
+    context.process_and_validate_instruction(&take_instruction(), &[Check::success()]);
+
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_B)), RECEIVE);
+    assert!(!context.account_store.borrow().contains_key(&ata(&REFERRER, &MINT_B)));
+}
+
+#[test]
+fn make_rejects_referral_above_100_percent() {
+    let context = setup();
+
+    context.process_and_validate_instruction(
+        &make_instruction_with_referral(10_001),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::InvalidFee as u32,
+        ))],
+    );
+}
+
+#[test]
+fn take_without_config_charges_no_fee() {
+    // Config 中设置了 1% 的协议费，但 config / treasury / treasury_ata 传入程序 ID 占位时不收取
+    let context = setup_with_fee(100);
+
+    let mut take = take_instruction();
+    for meta in &mut take.accounts[9..12] {
+        *meta = AccountMeta::new_readonly(ESCROW_ID, false);
+    }
+    context.process_and_validate_instruction(&make_instruction(hook_accounts()), &[Check::success()]);
+    context.process_and_validate_instruction(&take, &[Check::success()]);
+
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), DEPOSIT);
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_B)), RECEIVE);
+    assert!(!context.account_store.borrow().contains_key(&ata(&TREASURY, &MINT_B)));
+}
+
+#[test]
+fn take_rejects_taker_as_referrer() {
+    let context = setup();
+
+    context.process_and_validate_instruction(&make_instruction_with_referral(200), &[Check::success()]);
+    context.process_and_validate_instruction(
+        &take_instruction_with_referrer(Some(TAKER)),
+        &[Check::err(ProgramError::Custom(blueshift_escrow::EscrowError::InvalidReferrer as u32))],
+    );
+
+    // 不传推荐人时正常成交，创建者收到全部代币 B
+    context.process_and_validate_instruction(&take_instruction(), &[Check::success()]);
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_B)), RECEIVE);
+}
@@ -0,0 +1,54 @@
+// TakeMany 的集成测试：构建与运行方式见 tests/common/mod.rs
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use mollusk_svm::result::Check;
+use solana_program::program_error::ProgramError;
+
+use common::*;
+
+#[test]
+fn take_many_sweeps_every_escrow() {
+    let context = setup_take_many();
+
+    context.process_and_validate_instruction(
+        &take_many_instruction(2 * RECEIVE, true),
+        &[
+            Check::success(),
+            Check::account(&escrow_address()).closed().build(),
+            Check::account(&second_escrow_address()).closed().build(),
+        ],
+    );
+
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), 2 * DEPOSIT);
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_B)), RECEIVE);
+    assert_eq!(token_amount(&context, &ata(&ARBITER, &MINT_B)), RECEIVE);
+    // 两次 Make + 两次成交
+    assert_eq!(counter(&context), 4);
+}
+
+#[test]
+fn take_many_respects_max_pay_and_all_or_nothing() {
+    let context = setup_take_many();
+
+    // 合计超出 max_pay：全部成交模式下整条指令失败
+    context.process_and_validate_instruction(
+        &take_many_instruction(2 * RECEIVE - 1, true),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::SlippageExceeded as u32,
+        ))],
+    );
+
+    // 部分成交模式：跳过超出预算的第二个托管
+    context.process_and_validate_instruction(
+        &take_many_instruction(2 * RECEIVE - 1, false),
+        &[Check::success(), Check::account(&escrow_address()).closed().build()],
+    );
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), DEPOSIT);
+    assert_eq!(token_amount(&context, &ata(&second_escrow_address(), &MINT_A)), DEPOSIT);
+
+    // 已被接受的托管同样被跳过
+    context.process_and_validate_instruction(&take_many_instruction(RECEIVE, false), &[Check::success()]);
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), 2 * DEPOSIT);
+}
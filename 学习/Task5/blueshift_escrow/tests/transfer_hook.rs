@@ -0,0 +1,81 @@
+// =============================================================================
+// Token-2022 转账钩子集成测试
+// =============================================================================
+// 构建与运行方式见 tests/common/mod.rs
+//
+// 钩子每执行一次就把 counter 账户中的计数加 1，
+// 因此计数可以证明 Make / Take / Refund 正确转发了钩子账户。
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use mollusk_svm::result::Check;
+use solana_program::program_error::ProgramError;
+use solana_sdk::instruction::AccountMeta;
+
+use common::*;
+
+#[test]
+fn make_and_take_invoke_transfer_hook() {
+    let context = setup();
+
+    context.process_and_validate_instruction(&make_instruction(hook_accounts()), &[Check::success()]);
+    assert_eq!(counter(&context), 1);
+    assert_eq!(token_amount(&context, &ata(&escrow_address(), &MINT_A)), DEPOSIT);
+
+    context.process_and_validate_instruction(
+        &take_instruction(),
+        &[
+            Check::success(),
+            Check::account(&escrow_address()).closed().build(),
+            Check::account(&ata(&escrow_address(), &MINT_A)).closed().build(),
+        ],
+    );
+    // 只有 mint_a 带钩子：vault -> taker 触发一次
+    assert_eq!(counter(&context), 2);
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), DEPOSIT);
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_B)), RECEIVE);
+}
+
+#[test]
+fn make_and_refund_invoke_transfer_hook() {
+    let context = setup();
+
+    context.process_and_validate_instruction(&make_instruction(hook_accounts()), &[Check::success()]);
+    context.process_and_validate_instruction(
+        &refund_instruction(),
+        &[
+            Check::success(),
+            Check::account(&escrow_address()).closed().build(),
+        ],
+    );
+
+    assert_eq!(counter(&context), 2);
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_A)), DEPOSIT);
+}
+
+#[test]
+fn make_without_hook_accounts_fails() {
+    let context = setup();
+
+    context.process_and_validate_instruction(
+        &make_instruction(Vec::new()),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::InvalidTransferHookAccounts as u32,
+        ))],
+    );
+    assert_eq!(counter(&context), 0);
+}
+
+#[test]
+fn accounts_after_hook_accounts_are_not_forwarded() {
+    let context = setup();
+
+    // 钩子账户之后的其他账户不转发给 TransferChecked，不会超出单次转账的账户上限
+    let mut accounts = hook_accounts();
+    accounts.extend((0..blueshift_escrow::MAX_TRANSFER_HOOK_ACCOUNTS).map(|_| AccountMeta::new_readonly(TREASURY, false)));
+    context.process_and_validate_instruction(&make_instruction(accounts), &[Check::success()]);
+
+    assert_eq!(counter(&context), 1);
+    assert_eq!(token_amount(&context, &ata(&escrow_address(), &MINT_A)), DEPOSIT);
+}
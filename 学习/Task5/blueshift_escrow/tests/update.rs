@@ -0,0 +1,122 @@
+// Update 指令的集成测试：构建与运行方式见 tests/common/mod.rs
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use std::collections::HashMap;
+
+use mollusk_svm::{result::Check, MolluskContext};
+use solana_account::Account;
+use solana_program::program_error::ProgramError;
+use solana_pubkey::Pubkey;
+use solana_sdk::instruction::Instruction;
+
+use common::*;
+
+fn escrow_receive(context: &MolluskContext<HashMap<Pubkey, Account>>) -> u64 {
+    let data = context.account_store.borrow()[&escrow_address()].data.clone();
+    u64::from_le_bytes(data[106..114].try_into().unwrap())
+}
+
+fn escrow_expires_at(context: &MolluskContext<HashMap<Pubkey, Account>>) -> i64 {
+    let data = context.account_store.borrow()[&escrow_address()].data.clone();
+    i64::from_le_bytes(data[114..122].try_into().unwrap())
+}
+
+#[test]
+fn update_withdraws_and_changes_terms_without_remaking() {
+    let context = setup();
+
+    context.process_and_validate_instruction(&make_instruction(hook_accounts()), &[Check::success()]);
+    // 取回 400 个代币 A，并把期望数量改为 300、过期时间改为 i64::MAX
+    context.process_and_validate_instruction(&update_instruction(300, 0, 400, Some(i64::MAX)), &[Check::success()]);
+
+    assert_eq!(counter(&context), 2);
+    assert_eq!(token_amount(&context, &ata(&escrow_address(), &MINT_A)), DEPOSIT - 400);
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_A)), 400);
+    assert_eq!(escrow_receive(&context), 300);
+    assert_eq!(escrow_expires_at(&context), i64::MAX);
+
+    // 接受者按新条件成交
+    context.process_and_validate_instruction(&take_instruction(), &[Check::success()]);
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), DEPOSIT - 400);
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_B)), 300);
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_B)), RECEIVE - 300);
+}
+
+#[test]
+fn update_tops_up_vault() {
+    let context = setup();
+
+    context.process_and_validate_instruction(&make_instruction(hook_accounts()), &[Check::success()]);
+    context.process_and_validate_instruction(&update_instruction(0, 0, 400, None), &[Check::success()]);
+    context.process_and_validate_instruction(&update_instruction(0, 400, 0, Some(0)), &[Check::success()]);
+
+    assert_eq!(counter(&context), 3);
+    assert_eq!(token_amount(&context, &ata(&escrow_address(), &MINT_A)), DEPOSIT);
+    assert_eq!(token_amount(&context, &ata(&MAKER, &MINT_A)), 0);
+    // receive 为 0 表示不修改
+    assert_eq!(escrow_receive(&context), RECEIVE);
+    assert_eq!(escrow_expires_at(&context), 0);
+}
+
+#[test]
+fn update_cannot_empty_vault() {
+    let context = setup();
+
+    context.process_and_validate_instruction(&make_instruction(hook_accounts()), &[Check::success()]);
+    context.process_and_validate_instruction(
+        &update_instruction(0, 0, DEPOSIT, None),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::InvalidAmount as u32,
+        ))],
+    );
+    assert_eq!(token_amount(&context, &ata(&escrow_address(), &MINT_A)), DEPOSIT);
+}
+
+// 接受者签名时看到金库中有 DEPOSIT 个代币 A，创建者抢先用 Update 取走了大部分
+fn take_instruction_with_min_amount_a(min_amount_a: u64) -> Instruction {
+    let mut instruction = take_instruction();
+    instruction.data[1..9].copy_from_slice(&min_amount_a.to_le_bytes());
+    instruction
+}
+
+#[test]
+fn take_rejects_vault_drained_by_update() {
+    let context = setup();
+
+    context.process_and_validate_instruction(&make_instruction(hook_accounts()), &[Check::success()]);
+    context.process_and_validate_instruction(&update_instruction(0, 0, DEPOSIT - 1, None), &[Check::success()]);
+
+    context.process_and_validate_instruction(
+        &take_instruction_with_min_amount_a(DEPOSIT),
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::SlippageExceeded as u32,
+        ))],
+    );
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_B)), RECEIVE);
+
+    // 接受者接受新的金库余额时可以成交
+    context.process_and_validate_instruction(&take_instruction_with_min_amount_a(1), &[Check::success()]);
+    assert_eq!(token_amount(&context, &ata(&TAKER, &MINT_A)), 1);
+}
+
+#[test]
+fn take_partial_rejects_vault_drained_by_update() {
+    let context = setup();
+
+    context.process_and_validate_instruction(&make_instruction(hook_accounts()), &[Check::success()]);
+    context.process_and_validate_instruction(&update_instruction(0, 0, DEPOSIT / 2, None), &[Check::success()]);
+
+    // 支付一半的代币 B，签名时预期得到 DEPOSIT / 2 个代币 A，金库减半后只能得到 DEPOSIT / 4
+    let mut take_partial = take_instruction();
+    take_partial.data = vec![4];
+    take_partial.data.extend_from_slice(&(RECEIVE / 2).to_le_bytes());
+    take_partial.data.extend_from_slice(&(DEPOSIT / 2).to_le_bytes());
+    context.process_and_validate_instruction(
+        &take_partial,
+        &[Check::err(ProgramError::Custom(
+            blueshift_escrow::EscrowError::SlippageExceeded as u32,
+        ))],
+    );
+}